@@ -1,466 +1,6782 @@
 use serde::{Deserialize, Serialize};
-use std::process::Command;
+use tauri::{AppHandle, Emitter, Manager, RunEvent, State};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, Serialize, Deserialize)]
+// All diagnostics go through here rather than `println!`, so the log mode decides where
+// they end up. Messages keep their `[LEVEL]` prefix.
+macro_rules! log_line {
+    ($($arg:tt)*) => {
+        emit_log(&format!($($arg)*))
+    };
+}
+
+// Where log lines go. Text is the classic `[INFO] ...` on stdout; when the library is
+// embedded in a tool that parses our stdout, Json moves logs to stderr and Quiet drops them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogMode {
+    #[default]
+    Text,
+    Json,
+    Quiet,
+}
+
+// Environment variable read by `run()` to pick the log mode: text, json or quiet
+pub const LOG_MODE_ENV: &str = "KILL_PROCESS_LOG";
+
+static LOG_MODE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_log_mode(mode: LogMode) {
+    LOG_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+pub fn log_mode() -> LogMode {
+    match LOG_MODE.load(Ordering::Relaxed) {
+        1 => LogMode::Json,
+        2 => LogMode::Quiet,
+        _ => LogMode::Text,
+    }
+}
+
+// Destination of a rendered log line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogOutput {
+    Stdout(String),
+    Stderr(String),
+    Dropped,
+}
+
+// Render a `[LEVEL] message` line for the given mode. Json lines look like
+// `{"level":"info","message":"...","timestamp":1700000000000}`.
+pub fn render_log_line(mode: LogMode, line: &str, timestamp: u64) -> LogOutput {
+    match mode {
+        LogMode::Text => LogOutput::Stdout(line.to_string()),
+        LogMode::Quiet => LogOutput::Dropped,
+        LogMode::Json => {
+            let (level, message) = match line.strip_prefix('[').and_then(|rest| rest.split_once("] ")) {
+                Some((level, message)) => (level.to_lowercase(), message),
+                None => ("info".to_string(), line),
+            };
+            let entry = serde_json::json!({ "level": level, "message": message, "timestamp": timestamp });
+            LogOutput::Stderr(entry.to_string())
+        }
+    }
+}
+
+fn emit_log(line: &str) {
+    match render_log_line(log_mode(), line, now_millis()) {
+        LogOutput::Stdout(text) => println!("{}", text),
+        LogOutput::Stderr(text) => eprintln!("{}", text),
+        LogOutput::Dropped => {}
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ProcessInfo {
     pub pid: String,
     pub name: String,
     pub port: String,
+    pub match_range: Option<(usize, usize)>, // Byte range of the search match within `name`
+    #[serde(default)]
+    pub score: Option<f64>, // Fuzzy match similarity in 0.0..=1.0; only set by fuzzy searches
+    #[serde(default)]
+    pub socket_type: Option<String>, // "TCP", "UDP" or "unix"; None when not found through a socket
+    #[serde(default)]
+    pub fd: Option<String>, // lsof FD column: "cwd", "txt", "mem" or a number with its mode, e.g. "20u"
+    #[serde(default)]
+    pub fd_type: Option<String>, // lsof TYPE column, e.g. "REG", "DIR", "IPv4", "unix"
+    #[serde(default)]
+    pub note: Option<String>, // Session label set with `set_process_note`
+    #[serde(default)]
+    pub depth: Option<usize>, // Levels below the root PID in `get_descendants`; 1 for direct children
+    #[serde(default)]
+    pub is_privileged_port: bool, // Bound to a port below 1024, which takes root or CAP_NET_BIND_SERVICE
+}
+
+// Ports below this need elevated privileges to bind on Unix
+pub const PRIVILEGED_PORT_LIMIT: u16 = 1024;
+
+// Whether binding `port` takes privileges; port 0 (any port) doesn't count
+pub fn is_privileged_port(port: u16) -> bool {
+    (1..PRIVILEGED_PORT_LIMIT).contains(&port)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessDetail {
     pub pid: String,
     pub name: String,
     pub port: String,
     pub user: Option<String>,
+    #[serde(default)]
+    pub user_truncated: bool, // ps cut the user name short and marked it with a trailing `+`
+    #[serde(default)]
+    pub real_user: Option<String>, // User who launched the process; differs from `user` for setuid binaries
+    #[serde(default)]
+    pub ruid: Option<u32>,
+    #[serde(default)]
+    pub euid: Option<u32>, // Decides permissions, including who may signal the process
     pub command: Option<String>,
-    pub cpu_usage: Option<String>,
-    pub memory_usage: Option<String>,
-    pub start_time: Option<String>,
+    pub cpu_usage: Option<String>, // Raw ps pcpu; can exceed 100% for multithreaded processes
+    #[serde(default)]
+    pub cpu_cores: Option<f64>, // pcpu normalized to core-equivalents (2.5 = two and a half cores busy)
+    pub memory_usage: Option<String>, // Share of physical memory, e.g. "1.2% of RAM"
+    #[serde(default)]
+    pub rss_bytes: Option<u64>, // Resident set: physical memory in use, including shared pages
+    #[serde(default)]
+    pub vsz_bytes: Option<u64>, // Virtual size: all mapped address space, resident or not
+    #[serde(default)]
+    pub shared_bytes: Option<u64>, // Resident pages backed by files or shared memory (Linux only)
+    pub start_time: Option<String>, // ps lstart, locale-dependent and meant for display
+    #[serde(default)]
+    pub start_time_iso: Option<String>, // RFC 3339 in UTC, e.g. "2024-01-15T10:30:00Z"
+    #[serde(default)]
+    pub elapsed_seconds: Option<u64>, // How long the process has been running
+    #[serde(default)]
+    pub uptime_human: Option<String>, // `elapsed_seconds` for display, e.g. "2h 14m"
+    #[serde(default)]
+    pub cpu_time: Option<String>, // Total CPU time consumed as ps prints it, e.g. "01:23:45"
+    #[serde(default)]
+    pub cpu_time_seconds: Option<u64>,
+    #[serde(default)]
+    pub container_id: Option<String>, // Docker/containerd container owning the process (Linux only)
+    #[serde(default = "default_killable")]
+    pub is_killable: bool, // False for D-state (stuck in I/O) or zombie processes, where signals have no effect
+    #[serde(default)]
+    pub note: Option<String>, // Session label set with `set_process_note`
+    #[serde(default)]
+    pub oom_score: Option<i32>, // Kernel's OOM-kill badness; the highest score is killed first (Linux only)
+    #[serde(default)]
+    pub oom_score_adj: Option<i32>, // User adjustment of the score, -1000 (never kill) to 1000 (Linux only)
+}
+
+// Details saved before `is_killable` existed are assumed killable, as `Default` does
+fn default_killable() -> bool {
+    true
+}
+
+impl Default for ProcessDetail {
+    fn default() -> Self {
+        ProcessDetail {
+            pid: String::new(),
+            name: String::new(),
+            port: String::new(),
+            user: None,
+            user_truncated: false,
+            real_user: None,
+            ruid: None,
+            euid: None,
+            command: None,
+            cpu_usage: None,
+            cpu_cores: None,
+            memory_usage: None,
+            rss_bytes: None,
+            vsz_bytes: None,
+            shared_bytes: None,
+            start_time: None,
+            start_time_iso: None,
+            elapsed_seconds: None,
+            uptime_human: None,
+            cpu_time: None,
+            cpu_time_seconds: None,
+            container_id: None,
+            is_killable: true,
+            note: None,
+            oom_score: None,
+            oom_score_adj: None,
+        }
+    }
+}
+
+// Wire format version of the top-level results, bumped on changes the frontend must adapt to
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PortCheckResult {
+    pub is_occupied: bool,
+    pub processes: Vec<ProcessInfo>,
+    #[serde(default)]
+    pub binding: Option<String>, // "ipv4", "ipv6" or "dual" for an occupied port
+    pub error: Option<String>,
+    #[serde(default)]
+    pub duration_ms: Option<u64>, // How long the lsof scan took; None when answered without one
+    #[serde(default)]
+    pub output_truncated: bool, // lsof output went over the size limit and the rest was dropped
+    #[serde(default)]
+    pub schema_version: u32, // SCHEMA_VERSION when produced; 0 for data saved before versioning
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProcessSearchResult {
+    pub processes: Vec<ProcessInfo>,
+    #[serde(default)]
+    pub total_matches: usize, // All matches found, even those cut off by `max_results`
+    #[serde(flatten)]
+    pub totals: ResourceTotals,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub duration_ms: Option<u64>, // How long the ps scan took; None when none ran
+    #[serde(default)]
+    pub output_truncated: bool, // ps output went over the size limit and the rest was dropped
+    #[serde(default)]
+    pub schema_version: u32, // SCHEMA_VERSION when produced; 0 for data saved before versioning
+}
+
+// Combined resource footprint of a set of processes, e.g. every Chrome helper a search found
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceTotals {
+    #[serde(default)]
+    pub total_cpu: Option<f64>, // Sum of pcpu, so 250.0 means two and a half cores busy
+    #[serde(default)]
+    pub total_mem: Option<f64>, // Sum of pmem, as a percentage of physical memory
+    #[serde(default)]
+    pub total_rss_bytes: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProcessSnapshot {
+    pub listening_ports: Vec<ProcessInfo>,
+    pub timestamp: u64, // Milliseconds since the Unix epoch
+    #[serde(default)]
+    pub schema_version: u32, // SCHEMA_VERSION when produced; 0 for data saved before versioning
+}
+
+// What changed between two snapshots, with listeners keyed by PID and port
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub added: Vec<ProcessInfo>,   // Listening only in the later snapshot
+    pub removed: Vec<ProcessInfo>, // Listening only in the earlier snapshot
+    pub changed: Vec<ProcessInfo>, // In both, but with a different name or socket type; as in the later snapshot
+    #[serde(default)]
+    pub schema_version: u32, // SCHEMA_VERSION when produced; 0 for data saved before versioning
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortEntry {
+    pub port: u16,
+    pub state: Option<String>, // TCP state such as "LISTEN" or "ESTABLISHED"; UDP sockets have none
+    pub protocol: String,      // "TCP" or "UDP"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessPorts {
+    pub pid: String,
+    pub name: String,
+    pub ports: Vec<PortEntry>,
+}
+
+// A single socket of a process, as reported by lsof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Connection {
+    pub local: String,
+    pub remote: Option<String>, // Only set for connected sockets (`local->remote`)
+    pub state: Option<String>,  // TCP state; UDP rows have none
+    pub protocol: String,
+}
+
+// A socket with its owning process, split into first-class fields so the frontend
+// doesn't have to pick apart `ProcessInfo.port`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionInfo {
+    pub pid: String,
+    pub name: String,
+    pub user: String,
+    pub protocol: String,      // "TCP" or "UDP"
+    pub address: String,       // Local host, e.g. "127.0.0.1", "::1" or "*"; without any zone index
+    pub port: Option<u16>,     // None for unbound sockets such as `*:*`
+    pub state: Option<String>, // TCP state; UDP rows have none
+    #[serde(default)]
+    pub fd: Option<String>,      // lsof FD column, e.g. "20u"
+    #[serde(default)]
+    pub fd_type: Option<String>, // lsof TYPE column: "IPv4" or "IPv6"
+    #[serde(default)]
+    pub zone: Option<String>,    // IPv6 zone index of a link-local address, e.g. "en0" for `fe80::1%en0`
+}
+
+// Address family `check_port` asks lsof for: "v4", "v6" or "any"
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpFamily {
+    #[default]
+    Any,
+    V4,
+    V6,
+}
+
+impl IpFamily {
+    // The address for lsof's `-i` option: `4:8080` and `6:8080` are lsof's `-i4`/`-i6`
+    // narrowed to the port, `:8080` covers both families
+    pub fn lsof_address(self, port: u16) -> String {
+        match self {
+            IpFamily::Any => format!(":{}", port),
+            IpFamily::V4 => format!("4:{}", port),
+            IpFamily::V6 => format!("6:{}", port),
+        }
+    }
+    
+    // The `-i` selection of every TCP socket in the family, for checks spanning many ports
+    pub fn lsof_tcp_selection(self) -> &'static str {
+        match self {
+            IpFamily::Any => "-iTCP",
+            IpFamily::V4 => "-i4TCP",
+            IpFamily::V6 => "-i6TCP",
+        }
+    }
+}
+
+// How multiple search terms combine: "any" matches if one term matches, "all" needs every term
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TermCombinator {
+    #[default]
+    Any,
+    All,
+}
+
+// Where a search term has to appear in the process name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    #[default]
+    Substring,
+    Prefix,
+    Suffix,
+    Exact,
+}
+
+impl MatchMode {
+    // Both arguments are expected to be lowercased already
+    pub fn matches(self, name_lower: &str, term_lower: &str) -> bool {
+        match self {
+            MatchMode::Substring => name_lower.contains(term_lower),
+            MatchMode::Prefix => name_lower.starts_with(term_lower),
+            MatchMode::Suffix => name_lower.ends_with(term_lower),
+            MatchMode::Exact => name_lower == term_lower,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub terms: Vec<String>,
+    pub combinator: TermCombinator,
+    pub match_mode: MatchMode,
+    pub max_results: Option<usize>,
+    pub hide_kernel_threads: bool, // Skip kernel threads like `[kworker/0:1]`, which can't be killed
+    pub fuzzy: bool,               // Tolerate typos, ranking matches by similarity instead of `match_mode`
+    pub fuzzy_threshold: f64,      // Minimum similarity a fuzzy match needs
+    pub glob: bool,                // Terms are shell globs (`node*`, `py?hon`) matching the whole name
+    pub whole_word: bool,          // Terms only match between non-word characters: `ssh` but not `sshd`
+}
+
+// Default minimum similarity for fuzzy search; "ngnix" vs "nginx" scores about 0.95
+pub const FUZZY_THRESHOLD: f64 = 0.85;
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            terms: vec![],
+            combinator: TermCombinator::default(),
+            match_mode: MatchMode::default(),
+            max_results: None,
+            hide_kernel_threads: true,
+            fuzzy: false,
+            fuzzy_threshold: FUZZY_THRESHOLD,
+            glob: false,
+            whole_word: false,
+        }
+    }
+}
+
+impl SearchOptions {
+    pub fn for_term(term: &str) -> Self {
+        SearchOptions {
+            terms: vec![term.to_string()],
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KillResult {
+    pub pid: String,
+    pub success: bool,
+    pub message: String,
+}
+
+// Criteria for `kill_matching`, e.g. "mine, named like node*, on ports 3000-3010, older
+// than a minute". Every criterion given must hold; at least one is required.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KillQuery {
+    #[serde(default)]
+    pub name: Option<String>,  // Glob over the process name, case-insensitive, e.g. "node*"
+    #[serde(default)]
+    pub user: Option<String>,  // Owning user name
+    #[serde(default)]
+    pub ports: Option<String>, // Any pattern `check_port` accepts, e.g. "3000-3010"; only listeners match
+    #[serde(default)]
+    pub min_age_seconds: Option<u64>,
+}
+
+// A process a kill query matched, with what each criterion was checked against
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KillCandidate {
+    pub pid: String,
+    pub name: String,
+    pub user: String,
+    pub age_seconds: Option<u64>,
+    pub ports: Vec<u16>, // Listening ports; only looked up when the query has a port criterion
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct KillQueryResult {
+    pub matches: Vec<KillCandidate>,
+    pub results: Vec<KillResult>, // Empty for a dry run
+    #[serde(default)]
+    pub schema_version: u32, // SCHEMA_VERSION when produced; 0 for data saved before versioning
+}
+
+// Outcome for one listener of one port in a port-range kill
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortKillOutcome {
+    pub port: u16,
+    pub pid: String,
+    pub name: String,
+    pub skipped: bool, // Protected by a guard, or only a dry run
+    pub success: bool,
+    pub message: String,
+}
+
+// PIDs that must never be signalled: 1 is init/launchd, and losing it takes the system down
+pub const CRITICAL_PIDS: [u32; 1] = [1];
+
+// Why a PID must not be killed by a bulk operation, if it is protected
+pub fn protected_pid_reason(pid: &str, own_pid: u32) -> Option<&'static str> {
+    match pid.trim().parse::<u32>() {
+        Ok(pid) if pid == own_pid => Some("it is this application"),
+        Ok(pid) if CRITICAL_PIDS.contains(&pid) => Some("it is a critical system process"),
+        _ => None,
+    }
+}
+
+// Processes `kill_by_path` matched by executable path, and what happened to each
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathKillResult {
+    pub exe_path: String,
+    pub matched: Vec<ProcessInfo>,
+    pub results: Vec<KillResult>,
+    #[serde(default)]
+    pub cleaned_files: Vec<FileCleanup>, // One per companion file the caller asked to remove
+}
+
+// What `kill_by_port` did to a port's listeners, and to the companion files it was asked
+// to remove afterwards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortKillResult {
+    pub port: u16,
+    pub results: Vec<KillResult>,
+    #[serde(default)]
+    pub cleaned_files: Vec<FileCleanup>, // One per companion file the caller asked to remove
+}
+
+// Outcome of removing one companion file (a stale `.pid`, `.lock` or `.sock`) after a kill
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileCleanup {
+    pub path: String,
+    pub removed: bool,
+    pub reason: Option<String>, // Why the file was left in place
+}
+
+// Payload of the `kill-progress` event emitted after each PID in a batch or tree kill
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillProgress {
+    pub result: KillResult,
+    pub completed: usize,
+    pub total: usize,
+}
+
+pub const KILL_PROGRESS_EVENT: &str = "kill-progress";
+
+// Outcome of nudging a parent to reap its zombie children
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReapResult {
+    pub parent_pid: String,
+    pub zombies: Vec<u32>,
+    pub signaled: bool, // Whether SIGCHLD was sent to the parent
+    pub message: String,
+}
+
+// Outcome of a smart kill (SIGTERM, wait, then SIGKILL if still running)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "message")]
+pub enum SmartKillStatus {
+    TerminatedGracefully, // Exited within the grace period after SIGTERM
+    Escalated,            // Ignored SIGTERM and had to be SIGKILLed
+    AlreadyDead,          // Was gone before we could signal it
+    Cancelled,            // A background kill was cancelled after SIGTERM, before escalating
+    Failed(String),       // Still running, or a signal could not be delivered
+}
+
+pub const SMART_KILL_GRACE_MS: u64 = 3_000;
+pub const SMART_KILL_POLL_MS: u64 = 100;
+
+// Payload of the `kill-complete` event, emitted when a background kill finishes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillCompleteEvent {
+    pub pid: String,
+    pub status: SmartKillStatus,
+}
+
+pub const KILL_COMPLETE_EVENT: &str = "kill-complete";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillHistoryEntry {
+    pub timestamp: u64, // Milliseconds since the Unix epoch
+    pub pid: String,
+    pub name: Option<String>,
+    pub signal: String,
+    pub success: bool,
+    pub message: String,
+    #[serde(default)]
+    pub detail: Option<ProcessDetail>, // The process as it was just before the kill, see HISTORY_DETAIL_FIELDS
+}
+
+pub const KILL_HISTORY_CAPACITY: usize = 200;
+
+// Caps kills per second over a sliding one-second window, so a stuck "kill all" loop can't
+// hammer the system. Without a limit (the default) every kill is let through.
+#[derive(Debug, Default)]
+pub struct KillRateLimiter {
+    max_per_second: Option<u32>,
+    recent: VecDeque<u64>, // Times of the kills let through within the last second, oldest first
+}
+
+impl KillRateLimiter {
+    // None or 0 turns the limit off
+    pub fn set_limit(&mut self, max_per_second: Option<u32>) {
+        self.max_per_second = max_per_second.filter(|max| *max > 0);
+        self.recent.clear();
+    }
+    
+    pub fn limit(&self) -> Option<u32> {
+        self.max_per_second
+    }
+    
+    // Let a kill at `now_ms` through and count it, or refuse it with the milliseconds until
+    // the oldest counted kill leaves the window
+    pub fn try_acquire(&mut self, now_ms: u64) -> Result<(), u64> {
+        let Some(max) = self.max_per_second else {
+            return Ok(());
+        };
+        
+        while self.recent.front().is_some_and(|&at| now_ms.saturating_sub(at) >= 1_000) {
+            self.recent.pop_front();
+        }
+        if self.recent.len() >= max as usize {
+            let oldest = self.recent.front().copied().unwrap_or(now_ms);
+            return Err((oldest + 1_000).saturating_sub(now_ms));
+        }
+        
+        self.recent.push_back(now_ms);
+        Ok(())
+    }
+}
+
+// What a kill captures about its target for the history, kept to the cheap fields
+pub const HISTORY_DETAIL_FIELDS: &[&str] = &["name", "command", "user", "port"];
+
+// Bounded log of kill actions; the oldest entries are dropped once the capacity is reached
+#[derive(Debug)]
+pub struct KillHistory {
+    entries: VecDeque<KillHistoryEntry>,
+    capacity: usize,
+}
+
+impl KillHistory {
+    pub fn with_capacity(capacity: usize) -> Self {
+        KillHistory {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+    
+    pub fn record(&mut self, entry: KillHistoryEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+    
+    pub fn newest_first(&self) -> Vec<KillHistoryEntry> {
+        self.entries.iter().rev().cloned().collect()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for KillHistory {
+    fn default() -> Self {
+        KillHistory::with_capacity(KILL_HISTORY_CAPACITY)
+    }
+}
+
+pub const PORT_CACHE_TTL_MS: u64 = 5_000;
+
+#[derive(Debug, Clone)]
+pub struct PortCacheEntry {
+    pub processes: Vec<ProcessInfo>,
+    pub cached_at: u64, // Milliseconds since the Unix epoch
+    pub binding: Option<String>, // IP families of the listeners, see `port_binding`
+}
+
+// Recently seen port -> listener associations, so repeated checks of the same port can
+// confirm the cached PIDs with `kill -0` instead of running a full lsof scan
+#[derive(Debug)]
+pub struct PortPidCache {
+    entries: HashMap<u16, PortCacheEntry>,
+    ttl_ms: u64,
+}
+
+impl PortPidCache {
+    pub fn with_ttl(ttl_ms: u64) -> Self {
+        PortPidCache {
+            entries: HashMap::new(),
+            ttl_ms,
+        }
+    }
+    
+    // Only occupied ports are cached: a free port has no PID to confirm
+    pub fn insert(&mut self, port: u16, processes: Vec<ProcessInfo>, now: u64) {
+        if processes.is_empty() {
+            self.entries.remove(&port);
+        } else {
+            self.entries.insert(port, PortCacheEntry { processes, cached_at: now, binding: None });
+        }
+    }
+    
+    // Remember how the cached listeners are bound, so a cache hit can report it too
+    pub fn set_binding(&mut self, port: u16, binding: Option<String>) {
+        if let Some(entry) = self.entries.get_mut(&port) {
+            entry.binding = binding;
+        }
+    }
+    
+    pub fn binding(&self, port: u16) -> Option<String> {
+        self.entries.get(&port)?.binding.clone()
+    }
+    
+    // Return the cached listeners if the entry is fresh and every cached PID is still alive.
+    // A stale entry or one whose PID has exited is removed.
+    pub fn lookup<F: Fn(&str) -> bool>(&mut self, port: u16, now: u64, is_alive: F) -> Option<Vec<ProcessInfo>> {
+        let entry = self.entries.get(&port)?;
+        
+        let fresh = now.saturating_sub(entry.cached_at) <= self.ttl_ms;
+        if fresh && entry.processes.iter().all(|p| is_alive(&p.pid)) {
+            return Some(entry.processes.clone());
+        }
+        
+        log_line!("[DEBUG] Invalidating port cache entry for port {} (fresh: {})", port, fresh);
+        self.entries.remove(&port);
+        None
+    }
+    
+    pub fn invalidate(&mut self, port: u16) {
+        self.entries.remove(&port);
+    }
+    
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for PortPidCache {
+    fn default() -> Self {
+        PortPidCache::with_ttl(PORT_CACHE_TTL_MS)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessNote {
+    pub name: String, // Process name when the note was set
+    pub note: String,
+}
+
+// Session labels for PIDs ("frontend", "db"). Each note remembers the process name it was
+// set on and is dropped once the PID turns out to run something else, so a reused PID
+// doesn't inherit another process's label.
+#[derive(Debug, Default)]
+pub struct ProcessNotes {
+    entries: HashMap<String, ProcessNote>,
+}
+
+impl ProcessNotes {
+    // Label a PID; a blank note removes the label
+    pub fn set(&mut self, pid: &str, name: &str, note: &str) {
+        if note.trim().is_empty() {
+            self.entries.remove(pid);
+        } else {
+            self.entries.insert(pid.to_string(), ProcessNote {
+                name: name.to_string(),
+                note: note.trim().to_string(),
+            });
+        }
+    }
+    
+    // The note for a PID, provided it still runs the process the note was set on. A note
+    // whose process has gone or changed name is removed.
+    pub fn get(&mut self, pid: &str, current_name: Option<&str>) -> Option<String> {
+        let entry = self.entries.get(pid)?;
+        if current_name.is_some_and(|name| same_process_name(&entry.name, name)) {
+            return Some(entry.note.clone());
+        }
+        
+        log_line!("[DEBUG] Dropping note for PID {}: it no longer runs '{}'", pid, entry.name);
+        self.entries.remove(pid);
+        None
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// Locations of the external tools we shell out to. Unset (or blank) entries fall back
+// to the bare tool name, resolved through PATH.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolPaths {
+    pub lsof: Option<String>,
+    pub ps: Option<String>,
+    pub kill: Option<String>,
+    #[serde(default)]
+    pub max_output_bytes: Option<usize>, // Cap on the output kept from a tool; unset means DEFAULT_MAX_OUTPUT_BYTES
+}
+
+// Output beyond this is dropped, so a pathological ps/lsof can't run the app out of memory
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 8 * 1024 * 1024;
+
+impl ToolPaths {
+    pub fn lsof(&self) -> Command {
+        Command::new(Self::resolve(self.lsof.as_deref(), "lsof"))
+    }
+    
+    pub fn ps(&self) -> Command {
+        Command::new(Self::resolve(self.ps.as_deref(), "ps"))
+    }
+    
+    pub fn kill(&self) -> Command {
+        Command::new(Self::resolve(self.kill.as_deref(), "kill"))
+    }
+    
+    pub fn output_limit(&self) -> usize {
+        self.max_output_bytes.filter(|limit| *limit > 0).unwrap_or(DEFAULT_MAX_OUTPUT_BYTES)
+    }
+    
+    fn resolve<'a>(configured: Option<&'a str>, default: &'a str) -> &'a str {
+        match configured.map(str::trim) {
+            Some(path) if !path.is_empty() => path,
+            _ => default,
+        }
+    }
+}
+
+// Payload of the `port-watch` event, emitted whenever a watched port changes occupancy
+// or another process takes it over
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortWatchEvent {
+    pub port: u16,
+    pub is_occupied: bool,
+    pub processes: Vec<ProcessInfo>, // Listeners now
+    #[serde(default)]
+    pub previous: Vec<ProcessInfo>, // Listeners at the last poll; empty for the first event
+}
+
+// The event to emit for a poll that found `current` on the port, or None if the same
+// PIDs were listening at the `previous` poll. The first poll (no `previous`) always reports.
+pub fn port_watch_event(port: u16, previous: Option<&[ProcessInfo]>, current: &[ProcessInfo]) -> Option<PortWatchEvent> {
+    if previous.is_some_and(|previous| listener_pids(previous) == listener_pids(current)) {
+        return None;
+    }
+    
+    Some(PortWatchEvent {
+        port,
+        is_occupied: !current.is_empty(),
+        processes: current.to_vec(),
+        previous: previous.map(<[ProcessInfo]>::to_vec).unwrap_or_default(),
+    })
+}
+
+// Sorted, deduplicated PIDs; lsof lists a process once per socket, e.g. IPv4 and IPv6
+fn listener_pids(processes: &[ProcessInfo]) -> Vec<&str> {
+    let mut pids: Vec<&str> = processes.iter().map(|p| p.pid.as_str()).collect();
+    pids.sort_unstable();
+    pids.dedup();
+    pids
+}
+
+pub const PORT_WATCH_EVENT: &str = "port-watch";
+pub const WATCH_PORT_FAST_INTERVAL_MS: u64 = 250;  // Default interval right after a change
+pub const WATCH_PORT_SLOW_INTERVAL_MS: u64 = 4_000; // Default interval a stable port backs off to
+pub const WATCH_PORT_MIN_INTERVAL_MS: u64 = 100;   // No watch polls faster than this
+
+// Poll interval of a port watch: back to `min` after a change, doubling toward `max` with
+// every poll that saw none, so the UI keeps up during churn while an idle watch costs little
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveInterval {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl AdaptiveInterval {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        AdaptiveInterval { min, max: max.max(min), current: min }
+    }
+    
+    // Interval before the next poll, given whether the last one saw a change
+    pub fn next(&mut self, changed: bool) -> Duration {
+        self.current = if changed { self.min } else { (self.current * 2).min(self.max) };
+        self.current
+    }
+    
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+}
+
+// Resolve a watch's min and max intervals. A lone `interval_ms` keeps the old fixed-rate
+// behaviour; `min_ms`/`max_ms` override either bound. Nothing polls below the floor.
+pub fn watch_intervals(interval_ms: Option<u64>, min_ms: Option<u64>, max_ms: Option<u64>) -> (Duration, Duration) {
+    let adaptive = min_ms.is_some() || max_ms.is_some();
+    let min = min_ms.or(interval_ms).unwrap_or(WATCH_PORT_FAST_INTERVAL_MS).max(WATCH_PORT_MIN_INTERVAL_MS);
+    let max = match (max_ms, interval_ms) {
+        (Some(max), _) => max,
+        (None, Some(interval)) if !adaptive => interval,
+        _ => WATCH_PORT_SLOW_INTERVAL_MS,
+    };
+    (Duration::from_millis(min), Duration::from_millis(max.max(min)))
+}
+
+// Cancellation flag shared between the registry and a background poll thread
+#[derive(Debug, Clone, Default)]
+pub struct WatchHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl WatchHandle {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+    
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+// Active port watches by port, so they can be stopped individually or all at once on exit
+#[derive(Debug, Default)]
+pub struct WatchRegistry {
+    watches: HashMap<u16, WatchHandle>,
+}
+
+impl WatchRegistry {
+    // Register a watch, cancelling any previous watch on the same port. Returns true if one was replaced.
+    pub fn add(&mut self, port: u16, handle: WatchHandle) -> bool {
+        match self.watches.insert(port, handle) {
+            Some(previous) => {
+                previous.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+    
+    // Cancel and forget the watch on a port. Stopping a port that isn't watched is a no-op returning false.
+    pub fn remove(&mut self, port: u16) -> bool {
+        match self.watches.remove(&port) {
+            Some(handle) => {
+                handle.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+    
+    // Cancel every watch, returning how many were running
+    pub fn abort_all(&mut self) -> usize {
+        let count = self.watches.len();
+        for (_, handle) in self.watches.drain() {
+            handle.cancel();
+        }
+        count
+    }
+    
+    pub fn contains(&self, port: u16) -> bool {
+        self.watches.contains_key(&port)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.watches.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.watches.is_empty()
+    }
+}
+
+// Session state managed by tauri
+#[derive(Debug, Default)]
+pub struct AppState {
+    pub kill_history: Mutex<KillHistory>,
+    pub port_cache: Mutex<PortPidCache>,
+    pub tool_paths: Mutex<ToolPaths>,
+    pub watches: Mutex<WatchRegistry>,
+    pub kill_allowlist: Mutex<Vec<String>>, // Empty means every process may be killed
+    pub services: Mutex<Option<HashMap<(u16, String), String>>>, // Parsed /etc/services, loaded on first use
+    pub notes: Mutex<ProcessNotes>,
+    pub background_kills: Mutex<HashMap<String, WatchHandle>>, // Running `kill_async` tasks by PID
+    pub kill_rate: Mutex<KillRateLimiter>,
+}
+
+impl AppState {
+    // Snapshot of the configured tool paths, so commands don't hold the lock while running tools
+    pub fn tools(&self) -> ToolPaths {
+        match self.tool_paths.lock() {
+            Ok(paths) => paths.clone(),
+            Err(e) => {
+                log_line!("[ERROR] Tool paths lock poisoned, using defaults: {}", e);
+                ToolPaths::default()
+            }
+        }
+    }
+    
+    // Snapshot of the kill allowlist; a poisoned lock fails closed with an unmatchable entry
+    pub fn allowlist(&self) -> Vec<String> {
+        match self.kill_allowlist.lock() {
+            Ok(allowlist) => allowlist.clone(),
+            Err(e) => {
+                log_line!("[ERROR] Kill allowlist lock poisoned, refusing all kills: {}", e);
+                vec![String::new()]
+            }
+        }
+    }
+    
+    // Attach session notes to processes whose PID and name still match
+    pub fn annotate(&self, processes: &mut [ProcessInfo]) {
+        match self.notes.lock() {
+            Ok(mut notes) if !notes.is_empty() => {
+                for process in processes {
+                    process.note = notes.get(&process.pid, Some(&process.name));
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log_line!("[ERROR] Process notes lock poisoned, results not annotated: {}", e),
+        }
+    }
+    
+    // Empty every in-memory cache, so the next queries go back to the system
+    pub fn clear_caches(&self) {
+        match self.port_cache.lock() {
+            Ok(mut cache) => cache.clear(),
+            Err(e) => log_line!("[ERROR] Port cache lock poisoned, not cleared: {}", e),
+        }
+        match self.services.lock() {
+            Ok(mut services) => *services = None,
+            Err(e) => log_line!("[ERROR] Services cache lock poisoned, not cleared: {}", e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ProcessError {
+    InvalidPid(String),
+    InvalidPort(String),
+    InvalidFormat(String),
+    InvalidPortPattern {
+        pattern: String,
+        reason: String,
+    },
+    CommandFailed {
+        message: String,        // Human-readable summary
+        exit_code: Option<i32>, // None if the command never ran or was killed by a signal
+        stderr: String,         // Full captured stderr, untrimmed
+    },
+    NotAllowed {
+        pid: String,
+        name: Option<String>, // None if the PID's name couldn't be resolved
+    },
+    PidReused {
+        pid: String,
+        expected: String, // Name the caller saw when it decided to kill
+        actual: String,   // Name currently running under the PID
+    },
+    InvalidSignal {
+        signal: String,
+        valid: Vec<&'static str>, // Signal names this platform supports
+    },
+    InvalidCommand {
+        tool: String,
+        reason: String,
+    },
+    InvalidPath(String),
+    InvalidPattern {
+        pattern: String,
+        reason: String,
+    },
+    TooYoung {
+        pid: String,
+        age_seconds: u64,
+        min_age_seconds: u64,
+    },
+    ConfirmationRequired {
+        pid: String,
+        name: Option<String>,
+        user: Option<String>,
+        is_critical: bool, // PID 1 or this application, as opposed to merely root-owned
+    },
+    AlreadyGone(String), // PID that exited before the signal reached it
+    InvalidField {
+        field: String,
+        valid: Vec<&'static str>, // Detail fields that can be requested
+    },
+    RateLimited {
+        max_per_second: u32,
+        retry_after_ms: u64, // When the next kill would be let through
+    },
+    InvalidUser(String),
+    UndecodableName {
+        pid: String,
+        name: String, // As decoded, with U+FFFD in place of the invalid bytes
+    },
+}
+
+impl ProcessError {
+    // A command failure with no process output to attach (e.g. the binary couldn't be spawned)
+    pub fn command_failed(message: impl Into<String>) -> Self {
+        ProcessError::CommandFailed {
+            message: message.into(),
+            exit_code: None,
+            stderr: String::new(),
+        }
+    }
+    
+    // A command failure carrying the exit code and raw stderr of the finished process
+    pub fn from_output(message: impl Into<String>, output: &std::process::Output) -> Self {
+        ProcessError::CommandFailed {
+            message: message.into(),
+            exit_code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }
+    }
+    
+    // Stable variant name sent to the frontend alongside the message
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ProcessError::InvalidPid(_) => "InvalidPid",
+            ProcessError::InvalidPort(_) => "InvalidPort",
+            ProcessError::InvalidFormat(_) => "InvalidFormat",
+            ProcessError::InvalidPortPattern { .. } => "InvalidPortPattern",
+            ProcessError::CommandFailed { .. } => "CommandFailed",
+            ProcessError::NotAllowed { .. } => "NotAllowed",
+            ProcessError::PidReused { .. } => "PidReused",
+            ProcessError::InvalidSignal { .. } => "InvalidSignal",
+            ProcessError::InvalidCommand { .. } => "InvalidCommand",
+            ProcessError::InvalidPath(_) => "InvalidPath",
+            ProcessError::InvalidPattern { .. } => "InvalidPattern",
+            ProcessError::TooYoung { .. } => "TooYoung",
+            ProcessError::ConfirmationRequired { .. } => "ConfirmationRequired",
+            ProcessError::AlreadyGone(_) => "AlreadyGone",
+            ProcessError::InvalidField { .. } => "InvalidField",
+            ProcessError::RateLimited { .. } => "RateLimited",
+            ProcessError::InvalidUser(_) => "InvalidUser",
+            ProcessError::UndecodableName { .. } => "UndecodableName",
+        }
+    }
+}
+
+impl std::fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessError::InvalidPid(message) => write!(f, "{}", message),
+            ProcessError::InvalidPort(port) => write!(f, "Invalid port number: {}", port),
+            ProcessError::InvalidFormat(format) => {
+                write!(f, "Unsupported export format '{}', expected 'json' or 'csv'", format)
+            }
+            ProcessError::InvalidPortPattern { pattern, reason } => {
+                write!(f, "Invalid port pattern '{}': {}", pattern, reason)
+            }
+            ProcessError::CommandFailed { message, .. } => write!(f, "{}", message),
+            ProcessError::NotAllowed { pid, name } => write!(
+                f,
+                "Killing PID {} ({}) is not allowed: it is not on the kill allowlist",
+                pid, name.as_deref().unwrap_or("unknown process")
+            ),
+            ProcessError::PidReused { pid, expected, actual } => write!(
+                f,
+                "Refusing to kill PID {}: it now belongs to '{}' instead of '{}'",
+                pid, actual, expected
+            ),
+            ProcessError::InvalidSignal { signal, valid } => write!(
+                f,
+                "Unsupported signal '{}', expected one of: {}",
+                signal, valid.join(", ")
+            ),
+            ProcessError::InvalidCommand { tool, reason } => {
+                write!(f, "Refusing to run '{}': {}", tool, reason)
+            }
+            ProcessError::InvalidPath(path) => {
+                write!(f, "Invalid executable path '{}', expected an absolute path to a file", path)
+            }
+            ProcessError::InvalidPattern { pattern, reason } => {
+                write!(f, "Invalid search pattern '{}': {}", pattern, reason)
+            }
+            ProcessError::TooYoung { pid, age_seconds, min_age_seconds } => write!(
+                f,
+                "Refusing to kill PID {}: it started {}s ago, under the {}s minimum age; force kill to override",
+                pid, age_seconds, min_age_seconds
+            ),
+            ProcessError::ConfirmationRequired { pid, name, user, is_critical } => write!(
+                f,
+                "Killing PID {} ({}, owned by {}) needs confirmation: it is {}",
+                pid,
+                name.as_deref().unwrap_or("unknown process"),
+                user.as_deref().unwrap_or("unknown user"),
+                if *is_critical { "a critical process" } else { "owned by root" }
+            ),
+            ProcessError::AlreadyGone(pid) => write!(f, "Process {} had already terminated", pid),
+            ProcessError::InvalidField { field, valid } => write!(
+                f,
+                "Unknown detail field '{}', expected one of: {}",
+                field, valid.join(", ")
+            ),
+            ProcessError::RateLimited { max_per_second, retry_after_ms } => write!(
+                f,
+                "Too many kills: at most {} per second are allowed, retry in {} ms",
+                max_per_second, retry_after_ms
+            ),
+            ProcessError::InvalidUser(user) => write!(f, "Invalid user '{}'", user),
+            ProcessError::UndecodableName { pid, name } => write!(
+                f,
+                "Name of PID {} is not valid UTF-8 ('{}'), so it can't be safely matched",
+                pid, name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProcessError {}
+
+// Serialized as `{ "kind": ..., "message": ... }` so the frontend can both branch on the
+// error kind and show a readable message
+impl Serialize for ProcessError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        
+        match self {
+            ProcessError::CommandFailed { exit_code, stderr, .. } => {
+                let mut state = serializer.serialize_struct("ProcessError", 4)?;
+                state.serialize_field("kind", self.kind())?;
+                state.serialize_field("message", &self.to_string())?;
+                state.serialize_field("exit_code", exit_code)?;
+                state.serialize_field("stderr", stderr)?;
+                state.end()
+            }
+            ProcessError::ConfirmationRequired { pid, name, user, is_critical } => {
+                let mut state = serializer.serialize_struct("ProcessError", 6)?;
+                state.serialize_field("kind", self.kind())?;
+                state.serialize_field("message", &self.to_string())?;
+                state.serialize_field("pid", pid)?;
+                state.serialize_field("name", name)?;
+                state.serialize_field("user", user)?;
+                state.serialize_field("is_critical", is_critical)?;
+                state.end()
+            }
+            ProcessError::RateLimited { retry_after_ms, .. } => {
+                let mut state = serializer.serialize_struct("ProcessError", 3)?;
+                state.serialize_field("kind", self.kind())?;
+                state.serialize_field("message", &self.to_string())?;
+                state.serialize_field("retry_after_ms", retry_after_ms)?;
+                state.end()
+            }
+            _ => {
+                let mut state = serializer.serialize_struct("ProcessError", 2)?;
+                state.serialize_field("kind", self.kind())?;
+                state.serialize_field("message", &self.to_string())?;
+                state.end()
+            }
+        }
+    }
+}
+
+// Search for processes by name
+// Each search option is its own optional argument so the frontend can pass only what it sets
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+fn search_processes_by_name(
+    state: State<'_, AppState>,
+    process_name: String,
+    max_results: Option<usize>,
+    terms: Option<Vec<String>>,
+    combinator: Option<TermCombinator>,
+    hide_kernel_threads: Option<bool>,
+    match_mode: Option<MatchMode>,
+    fuzzy: Option<bool>,
+    fuzzy_threshold: Option<f64>,
+    glob: Option<bool>,
+    listening_only: Option<bool>,
+    whole_word: Option<bool>,
+) -> ProcessSearchResult {
+    log_line!("[INFO] Searching for processes with name containing: {} (max results: {:?})", process_name, max_results);
+    
+    let tools = state.tools();
+    let listening_only = listening_only.unwrap_or(false);
+    
+    // Explicit terms take precedence; otherwise the single process name is the only term.
+    // When only listeners are wanted the cap applies after filtering, not before.
+    let options = SearchOptions {
+        terms: match terms {
+            Some(terms) if terms.iter().any(|t| !t.trim().is_empty()) => terms,
+            _ => vec![process_name.clone()],
+        },
+        combinator: combinator.unwrap_or_default(),
+        match_mode: match_mode.unwrap_or_default(),
+        max_results: if listening_only { None } else { max_results },
+        hide_kernel_threads: hide_kernel_threads.unwrap_or(true),
+        fuzzy: fuzzy.unwrap_or(false),
+        fuzzy_threshold: fuzzy_threshold.unwrap_or(FUZZY_THRESHOLD),
+        glob: glob.unwrap_or(false),
+        whole_word: whole_word.unwrap_or(false),
+    };
+    
+    if options.terms.iter().all(|t| t.trim().is_empty()) {
+        return ProcessSearchResult {
+            processes: vec![],
+            total_matches: 0,
+            totals: ResourceTotals::default(),
+            error: Some("Process name cannot be empty".to_string()),
+            duration_ms: None,
+            output_truncated: false,
+            schema_version: SCHEMA_VERSION,
+        };
+    }
+    
+    if options.glob {
+        if let Err(e) = compile_globs(&options.terms) {
+            return ProcessSearchResult {
+                processes: vec![],
+                total_matches: 0,
+                totals: ResourceTotals::default(),
+                error: Some(e.to_string()),
+                duration_ms: None,
+                output_truncated: false,
+                schema_version: SCHEMA_VERSION,
+            };
+        }
+    }
+    
+    let mut result = search_ps(&tools, &options, &process_name);
+    
+    // An all-numeric query may also be a PID or a port, so those matches join the name matches
+    if options.terms.len() == 1 && is_pid_query(&options.terms[0]) {
+        let query = options.terms[0].trim();
+        let port_listeners = match u16::from_str(query) {
+            Ok(port) => find_port_listeners(&tools, port).unwrap_or_else(|e| {
+                log_line!("[WARN] Port lookup for query '{}' failed: {}", query, e);
+                vec![]
+            }),
+            Err(_) => vec![],
+        };
+        let pid_matches = find_pid(&tools, query.to_string()).processes;
+        
+        let name_matches = std::mem::take(&mut result.processes);
+        let name_count = name_matches.len();
+        result.processes = merge_search_results(vec![port_listeners, pid_matches, name_matches]);
+        result.total_matches += result.processes.len() - name_count;
+        // total_matches keeps counting everything; only the returned list is capped
+        if let Some(max) = options.max_results {
+            result.processes.truncate(max);
+        }
+    }
+    
+    if listening_only {
+        match find_listening_ports(&tools) {
+            Ok(listeners) => {
+                result.processes = filter_to_listeners(std::mem::take(&mut result.processes), &listeners);
+                result.total_matches = result.processes.len();
+                if let Some(max) = max_results {
+                    result.processes.truncate(max);
+                }
+            }
+            Err(e) => {
+                log_line!("[ERROR] Listener lookup for listening-only search failed: {}", e);
+                return ProcessSearchResult {
+                    processes: vec![],
+                    total_matches: 0,
+                    totals: ResourceTotals::default(),
+                    error: Some(e.to_string()),
+                    duration_ms: None,
+                    output_truncated: false,
+                    schema_version: SCHEMA_VERSION,
+                };
+            }
+        }
+    }
+    
+    if !result.processes.is_empty() {
+        result.totals = fetch_resource_totals(&tools, &result.processes);
+    }
+    state.annotate(&mut result.processes);
+    
+    result
+}
+
+// Keep the name matches that are listening on a port, taking their ports from the lsof
+// listener rows: a process on several ports gets them all, comma-separated.
+pub fn filter_to_listeners(matches: Vec<ProcessInfo>, listeners: &[ProcessInfo]) -> Vec<ProcessInfo> {
+    matches
+        .into_iter()
+        .filter_map(|mut process| {
+            let rows: Vec<&ProcessInfo> = listeners.iter().filter(|l| l.pid == process.pid).collect();
+            let first = rows.first()?;
+            
+            let mut ports: Vec<&str> = Vec::new();
+            for row in &rows {
+                if !ports.contains(&row.port.as_str()) {
+                    ports.push(&row.port);
+                }
+            }
+            process.port = ports.join(", ");
+            process.socket_type = first.socket_type.clone();
+            Some(process)
+        })
+        .collect()
+}
+
+// Sum CPU, memory and RSS over the returned processes with a single ps call. Processes
+// that exited since the search simply drop out of the totals.
+fn fetch_resource_totals(tools: &ToolPaths, processes: &[ProcessInfo]) -> ResourceTotals {
+    let pids = processes.iter().map(|p| p.pid.as_str()).collect::<Vec<_>>().join(",");
+    let ps_args = vec!["-p", &pids, "-o", "pid=,pcpu=,pmem=,rss="];
+    log_line!("[DEBUG] Executing command: ps {}", ps_args.join(" "));
+    
+    match tools.ps().args(&ps_args).audited_output() {
+        Ok(output) => sum_resource_rows(&String::from_utf8_lossy(&output.stdout)),
+        Err(e) => {
+            log_line!("[WARN] Failed to fetch resource usage for search results: {}", e);
+            ResourceTotals::default()
+        }
+    }
+}
+
+// Sum `pid pcpu pmem rss` rows from ps. rss is reported in KiB and converted to bytes.
+// Values that don't parse are skipped; a column with no values at all stays None.
+pub fn sum_resource_rows(output: &str) -> ResourceTotals {
+    let parse = |value: &str| value.trim_end_matches('%').replace(',', ".").parse::<f64>().ok().filter(|v| v.is_finite() && *v >= 0.0);
+    let add = |total: Option<f64>, value: Option<f64>| match (total, value) {
+        (Some(total), Some(value)) => Some(total + value),
+        (total, value) => total.or(value),
+    };
+    
+    let mut totals = ResourceTotals::default();
+    
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 4 {
+            continue;
+        }
+        
+        totals.total_cpu = add(totals.total_cpu, parse(parts[1]));
+        totals.total_mem = add(totals.total_mem, parse(parts[2]));
+        if let Ok(rss_kib) = parts[3].parse::<u64>() {
+            totals.total_rss_bytes = Some(totals.total_rss_bytes.unwrap_or(0) + rss_kib * 1024);
+        }
+    }
+    
+    totals
+}
+
+// Histogram of running process names, most frequent first, e.g. [("chrome", 23), ("node", 4)]
+#[tauri::command]
+fn process_name_counts(state: State<'_, AppState>) -> Result<Vec<(String, usize)>, ProcessError> {
+    log_line!("[INFO] Counting processes by name");
+    
+    let ps_args = vec!["-A", "-o", "comm="];
+    log_line!("[DEBUG] Executing command: ps {}", ps_args.join(" "));
+    
+    let output = state.tools().ps().args(&ps_args).audited_output().map_err(|e| {
+        log_line!("[ERROR] Failed to execute ps command: {}", e);
+        ProcessError::command_failed(format!("Failed to execute ps command: {}", e))
+    })?;
+    if !output.status.success() {
+        let error_str = String::from_utf8_lossy(&output.stderr);
+        log_line!("[ERROR] ps command failed with status: {}, stderr: {}", output.status, error_str);
+        return Err(ProcessError::from_output(format!("Failed to execute ps command: {}", error_str.trim()), &output));
+    }
+    
+    let counts = tally_process_names(&String::from_utf8_lossy(&output.stdout));
+    log_line!("[INFO] Counted {} distinct process name(s)", counts.len());
+    Ok(counts)
+}
+
+// Tally `ps -o comm=` lines by name, sorted by count descending and then by name. Each
+// line is one whole name, spaces included; macOS prints comm as the executable's full
+// path, so only the basename is counted.
+pub fn tally_process_names(output: &str) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    
+    for line in output.lines() {
+        let comm = line.trim();
+        let name = normalize_process_name(process_basename(comm));
+        if !name.is_empty() {
+            *counts.entry(name).or_insert(0) += 1;
+        }
+    }
+    
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+// Merge process lists from several sources, keeping the first entry per PID. Sources are
+// given in priority order, so lists with accurate ports should come first; a highlight
+// range from a later name match is still carried over together with that name.
+pub fn merge_search_results(sources: Vec<Vec<ProcessInfo>>) -> Vec<ProcessInfo> {
+    let mut merged: Vec<ProcessInfo> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    
+    for process in sources.into_iter().flatten() {
+        match index.get(&process.pid) {
+            Some(&slot) => {
+                // The range indexes the name it was found in, so both move together
+                if merged[slot].match_range.is_none() && process.match_range.is_some() {
+                    merged[slot].name = process.name;
+                    merged[slot].match_range = process.match_range;
+                }
+            }
+            None => {
+                index.insert(process.pid.clone(), merged.len());
+                merged.push(process);
+            }
+        }
+    }
+    
+    merged
+}
+
+// Stream `ps` and match every process against the search options
+fn search_ps(tools: &ToolPaths, options: &SearchOptions, process_name: &str) -> ProcessSearchResult {
+    // Use ps command to search for processes by name
+    // -A: show all processes, -o: specify output format
+    // args is included so names truncated in comm can be recovered from the command line
+    let ps_args = vec!["-A", "-o", "pid=,comm=,args="];
+    log_line!("[DEBUG] Executing command: ps {}", ps_args.join(" "));
+    
+    // Stream stdout instead of collecting it, so large process tables are matched incrementally
+    let started = Instant::now();
+    let mut command = tools.ps();
+    let child = command
+        .args(&ps_args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            record_command(&command, Err(&e));
+            log_line!("[ERROR] Failed to execute ps command: {}", e);
+            return ProcessSearchResult {
+                processes: vec![],
+                total_matches: 0,
+                totals: ResourceTotals::default(),
+                error: Some(format!("Failed to execute ps command: {}", e)),
+                duration_ms: None,
+                output_truncated: false,
+                schema_version: SCHEMA_VERSION,
+            };
+        }
+    };
+    
+    // The whole table is read so matches beyond the cap are still counted, up to the
+    // output limit; past it ps is stopped and the matches so far are returned
+    let limit = tools.output_limit();
+    let (processes, total_matches, output_truncated) = match child.stdout.take() {
+        Some(stdout) => {
+            let mut reader = BufReader::new(stdout.take(limit as u64));
+            let (processes, total_matches) = count_ps_stream(&mut reader, options);
+            let mut rest = reader.into_inner().into_inner();
+            let truncated = read_capped(&mut rest, 0).is_ok_and(|(_, more)| more);
+            (processes, total_matches, truncated)
+        }
+        None => (vec![], 0, false),
+    };
+    if output_truncated {
+        log_line!("[WARN] ps output exceeded {} bytes, search results are incomplete", limit);
+        let _ = child.kill();
+    }
+    
+    let wait = child.wait();
+    let duration_ms = elapsed_ms(started);
+    record_command(&command, wait.as_ref().map(|status| status.code()));
+    
+    match wait {
+        // A ps stopped for going over the limit exits by signal, yet its matches are valid
+        Ok(status) if status.success() || output_truncated => {
+            log_line!("[INFO] Found {} process(es) matching name '{}', returning {}", 
+                     total_matches, process_name, processes.len());
+            ProcessSearchResult {
+                processes,
+                total_matches,
+                totals: ResourceTotals::default(),
+                error: None,
+                duration_ms: Some(duration_ms),
+                output_truncated,
+                schema_version: SCHEMA_VERSION,
+            }
+        }
+        Ok(status) => {
+            let mut error_str = String::new();
+            if let Some(mut stderr) = child.stderr.take() {
+                let _ = stderr.read_to_string(&mut error_str);
+            }
+            log_line!("[ERROR] ps command failed with status: {}, stderr: {}", status, error_str);
+            
+            ProcessSearchResult {
+                processes: vec![],
+                total_matches: 0,
+                totals: ResourceTotals::default(),
+                error: Some(format!("Failed to execute ps command: {}", error_str)),
+                duration_ms: Some(duration_ms),
+                output_truncated: false,
+                schema_version: SCHEMA_VERSION,
+            }
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to wait for ps command: {}", e);
+            ProcessSearchResult {
+                processes: vec![],
+                total_matches: 0,
+                totals: ResourceTotals::default(),
+                error: Some(format!("Failed to execute ps command: {}", e)),
+                duration_ms: Some(duration_ms),
+                output_truncated: false,
+                schema_version: SCHEMA_VERSION,
+            }
+        }
+    }
+}
+
+
+
+// Look up a single process by its exact PID
+#[tauri::command]
+fn lookup_pid(state: State<'_, AppState>, pid: String) -> ProcessSearchResult {
+    find_pid(&state.tools(), pid)
+}
+
+fn find_pid(tools: &ToolPaths, pid: String) -> ProcessSearchResult {
+    log_line!("[INFO] Looking up process with PID: {}", pid);
+    
+    if !is_pid_query(&pid) {
+        return ProcessSearchResult {
+            processes: vec![],
+            total_matches: 0,
+            totals: ResourceTotals::default(),
+            error: Some(format!("Invalid PID format: {}", pid)),
+            duration_ms: None,
+            output_truncated: false,
+            schema_version: SCHEMA_VERSION,
+        };
+    }
+    
+    let ps_args = vec!["-p", pid.trim(), "-o", "pid=,comm="];
+    log_line!("[DEBUG] Executing command: ps {}", ps_args.join(" "));
+    
+    let (output, duration_ms) = timed_output(tools.ps().args(&ps_args), tools.output_limit());
+    match output {
+        Ok(output) => {
+            // ps exits with a failure status when the PID doesn't exist, which is simply "no match"
+            let output_str = String::from_utf8_lossy(&output.stdout);
+            let processes = parse_pid_lookup_output(&output_str, pid.trim());
+            log_line!("[INFO] PID lookup for {} found {} process(es)", pid, processes.len());
+            
+            ProcessSearchResult {
+                total_matches: processes.len(),
+                totals: ResourceTotals::default(),
+                processes,
+                error: None,
+                duration_ms: Some(duration_ms),
+                output_truncated: output.truncated,
+                schema_version: SCHEMA_VERSION,
+            }
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute ps command: {}", e);
+            ProcessSearchResult {
+                processes: vec![],
+                total_matches: 0,
+                totals: ResourceTotals::default(),
+                error: Some(format!("Failed to execute ps command: {}", e)),
+                duration_ms: Some(duration_ms),
+                output_truncated: false,
+                schema_version: SCHEMA_VERSION,
+            }
+        }
+    }
+}
+
+// Check whether a search query should be treated as a PID (a plain positive decimal number)
+pub fn is_pid_query(query: &str) -> bool {
+    let query = query.trim();
+    !query.is_empty() && query.bytes().all(|b| b.is_ascii_digit()) && query.parse::<u32>().is_ok()
+}
+
+// Parse `ps -p <pid> -o pid=,comm=` output, keeping only the row for the requested PID
+pub fn parse_pid_lookup_output(output: &str, pid: &str) -> Vec<ProcessInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (row_pid, name) = line.trim().split_once(char::is_whitespace)?;
+            if row_pid != pid {
+                return None;
+            }
+            Some(ProcessInfo {
+                pid: row_pid.to_string(),
+                name: normalize_process_name(name),
+                port: "Unknown".to_string(), // Port is unknown for PID lookup
+                match_range: None,
+                score: None,
+                socket_type: None,
+                fd: None,
+                fd_type: None,
+                note: None,
+                depth: None,
+                is_privileged_port: false,
+            })
+        })
+        .collect()
+}
+
+// Check if a port is occupied and return process information. `ip_family` narrows the
+// lsof scan to IPv4 or IPv6 listeners; such a check bypasses the port cache.
+#[tauri::command]
+fn check_port(state: State<'_, AppState>, port: String, fresh: Option<bool>, ip_family: Option<IpFamily>) -> PortCheckResult {
+    let mut result = lookup_port(&state, port, fresh, ip_family.unwrap_or_default());
+    state.annotate(&mut result.processes);
+    result
+}
+
+fn lookup_port(state: &AppState, port: String, fresh: Option<bool>, family: IpFamily) -> PortCheckResult {
+    log_line!("[INFO] Starting port check for port: {} (family: {:?})", port, family);
+    // The cache holds listeners of both families, so a narrowed check can't use it
+    let use_cache = family == IpFamily::Any;
+    let fresh = fresh.unwrap_or(false) || !use_cache;
+    
+    let port_num = match u16::from_str(&port) {
+        Ok(p) => {
+            log_line!("[DEBUG] Port number parsed successfully: {}", p);
+            p
+        },
+        Err(_) if is_port_pattern(&port) => {
+            return check_port_pattern(&state.tools(), &port, family);
+        }
+        Err(e) => {
+            log_line!("[ERROR] Invalid port number '{}': {}", port, e);
+            return PortCheckResult {
+                is_occupied: false,
+                processes: vec![],
+                binding: None,
+                error: Some("Invalid port number".to_string()),
+                duration_ms: None,
+                output_truncated: false,
+                schema_version: SCHEMA_VERSION,
+            };
+        }
+    };
+
+    let tools = state.tools();
+    
+    // A cached listener that is still alive answers the check without a full lsof scan,
+    // unless the caller asked for a fresh view
+    if !fresh {
+        if let Ok(mut cache) = state.port_cache.lock() {
+            if let Some(processes) = cache.lookup(port_num, now_millis(), |pid| process_alive(&tools, pid)) {
+                log_line!("[INFO] Port {} confirmed from cache: {} process(es)", port_num, processes.len());
+                return PortCheckResult {
+                    is_occupied: true,
+                    processes,
+                    binding: cache.binding(port_num),
+                    error: None,
+                    duration_ms: None,
+                    output_truncated: false,
+                    schema_version: SCHEMA_VERSION,
+                };
+            }
+        }
+    }
+
+    // Use lsof to check port usage - works on macOS and Linux
+    // -sTCP:LISTEN only shows processes in LISTEN state to avoid duplicates
+    let port_arg = family.lsof_address(port_num);
+    let lsof_args = vec!["-i", &port_arg, "-P", "-n", "-sTCP:LISTEN"];
+    log_line!("[DEBUG] Executing command: lsof {}", lsof_args.join(" "));
+    
+    let (output, duration_ms) = timed_output(tools.lsof().args(&lsof_args), tools.output_limit());
+    log_line!("[DEBUG] lsof finished in {} ms", duration_ms);
+
+    match output {
+        Ok(output) => {
+            if output.status.success() {
+                let output_str = String::from_utf8_lossy(&output.stdout);
+                log_line!("[DEBUG] lsof command successful, output length: {} characters", output_str.len());
+                log_line!("[DEBUG] lsof raw output:\n{}", output_str);
+                
+                let processes = parse_lsof_output(&output_str, &port);
+                let binding = port_binding(&output_str);
+                log_line!("[INFO] Found {} LISTEN processes using port {} (binding: {:?})", processes.len(), port, binding);
+                
+                for process in &processes {
+                    log_line!("[DEBUG] Process found - PID: {}, Name: {}, Port: {}", 
+                             process.pid, process.name, process.port);
+                }
+                
+                if use_cache {
+                    if let Ok(mut cache) = state.port_cache.lock() {
+                        cache.insert(port_num, processes.clone(), now_millis());
+                        cache.set_binding(port_num, binding.clone());
+                    }
+                }
+                
+                PortCheckResult {
+                    is_occupied: !processes.is_empty(),
+                    processes,
+                    binding,
+                    error: None,
+                    duration_ms: Some(duration_ms),
+                    output_truncated: output.truncated,
+                    schema_version: SCHEMA_VERSION,
+                }
+            } else {
+                let error_str = String::from_utf8_lossy(&output.stderr);
+                log_line!("[DEBUG] lsof command failed with status: {}, stderr: {}", 
+                         output.status, error_str);
+                log_line!("[INFO] Port {} appears to be available (no processes found)", port);
+                
+                if let Ok(mut cache) = state.port_cache.lock() {
+                    cache.invalidate(port_num);
+                }
+                
+                PortCheckResult {
+                    is_occupied: false,
+                    processes: vec![],
+                    binding: None,
+                    error: None,
+                    duration_ms: Some(duration_ms),
+                    output_truncated: output.truncated,
+                    schema_version: SCHEMA_VERSION,
+                }
+            }
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute lsof command: {}", e);
+            PortCheckResult {
+                is_occupied: false,
+                processes: vec![],
+                binding: None,
+                error: Some(format!("Failed to execute lsof: {}", e)),
+                duration_ms: Some(duration_ms),
+                output_truncated: false,
+                schema_version: SCHEMA_VERSION,
+            }
+        },
+    }
+}
+
+// Check which processes have a Unix domain socket bound at `path` open, e.g. `/tmp/app.sock`.
+// The socket path takes the place of the port in the returned processes.
+#[tauri::command]
+fn check_unix_socket(state: State<'_, AppState>, path: String) -> PortCheckResult {
+    log_line!("[INFO] Checking Unix socket: {}", path);
+    
+    let path = path.trim();
+    if !path.starts_with('/') {
+        return PortCheckResult {
+            is_occupied: false,
+            processes: vec![],
+            binding: None,
+            error: Some("Socket path must be absolute".to_string()),
+            duration_ms: None,
+            output_truncated: false,
+            schema_version: SCHEMA_VERSION,
+        };
+    }
+    
+    // lsof can't reliably select a socket by its path, so list Unix sockets and filter
+    let lsof_args = vec!["-U", "-P", "-n"];
+    log_line!("[DEBUG] Executing command: lsof {}", lsof_args.join(" "));
+    
+    let tools = state.tools();
+    let (output, duration_ms) = timed_output(tools.lsof().args(&lsof_args), tools.output_limit());
+    match output {
+        Ok(output) => {
+            let processes = parse_unix_socket_output(&String::from_utf8_lossy(&output.stdout), path);
+            log_line!("[INFO] Found {} process(es) using Unix socket {}", processes.len(), path);
+            
+            PortCheckResult {
+                is_occupied: !processes.is_empty(),
+                processes,
+                binding: None,
+                error: None,
+                duration_ms: Some(duration_ms),
+                output_truncated: output.truncated,
+                schema_version: SCHEMA_VERSION,
+            }
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute lsof command: {}", e);
+            PortCheckResult {
+                is_occupied: false,
+                processes: vec![],
+                binding: None,
+                error: Some(format!("Failed to execute lsof: {}", e)),
+                duration_ms: Some(duration_ms),
+                output_truncated: false,
+                schema_version: SCHEMA_VERSION,
+            }
+        }
+    }
+}
+
+// Find the processes holding a file open, to debug "file in use" errors. For a directory,
+// processes with any file directly inside it open are included (lsof `+d`; not recursive,
+// as `+D` can take very long on large trees). Each process appears once, with the file
+// path in place of the port.
+#[tauri::command]
+fn processes_using_file(state: State<'_, AppState>, path: String) -> Vec<ProcessInfo> {
+    log_line!("[INFO] Finding processes using file: {}", path);
+    
+    let path = path.trim();
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            log_line!("[WARN] Cannot inspect '{}': {}", path, e);
+            return vec![];
+        }
+    };
+    
+    let lsof_args = if metadata.is_dir() {
+        vec!["-n", "-P", "+d", path]
+    } else {
+        vec!["-n", "-P", "--", path]
+    };
+    log_line!("[DEBUG] Executing command: lsof {}", lsof_args.join(" "));
+    
+    // lsof exits with status 1 when no process has the file open
+    match state.tools().lsof().args(&lsof_args).audited_output() {
+        Ok(output) => {
+            let processes = parse_lsof_file_output(&String::from_utf8_lossy(&output.stdout));
+            log_line!("[INFO] Found {} process(es) using {}", processes.len(), path);
+            processes
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute lsof command: {}", e);
+            vec![]
+        }
+    }
+}
+
+// Parse lsof rows for open files into one `ProcessInfo` per PID. The NAME column is
+// everything after the eighth column, so paths containing spaces stay whole. FD and TYPE
+// tell the executable image (`txt`) apart from the working directory, mapped libraries
+// (`mem`) and ordinary open files.
+pub fn parse_lsof_file_output(output: &str) -> Vec<ProcessInfo> {
+    let mut processes: Vec<ProcessInfo> = Vec::new();
+    
+    for (line_num, line) in output.lines().skip(1).enumerate() {
+        let Some((fields, name)) = split_leading_fields(line, 8) else {
+            if !line.trim().is_empty() {
+                log_line!("[WARN] Skipping malformed line {}: {}", line_num + 1, line);
+            }
+            continue;
+        };
+        
+        if name.is_empty() || processes.iter().any(|p| p.pid == fields[1]) {
+            continue;
+        }
+        processes.push(ProcessInfo {
+            pid: fields[1].to_string(),
+            name: normalize_process_name(fields[0]),
+            port: name.to_string(),
+            fd: Some(fields[3].to_string()),
+            fd_type: Some(fields[4].to_string()),
+            ..Default::default()
+        });
+    }
+    
+    processes
+}
+
+// Parse `lsof -U` output, keeping the processes with the socket at `path` open. Linux adds
+// a NODE column and a trailing `type=STREAM`, macOS has neither, so the path is matched
+// against any column after TYPE rather than a fixed position. One entry per PID.
+pub fn parse_unix_socket_output(output: &str, path: &str) -> Vec<ProcessInfo> {
+    let mut processes: Vec<ProcessInfo> = Vec::new();
+    
+    for line in output.lines().skip(1) {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 6 || parts[4] != "unix" || !parts[5..].contains(&path) {
+            continue;
+        }
+        
+        if processes.iter().any(|p| p.pid == parts[1]) {
+            continue;
+        }
+        processes.push(ProcessInfo {
+            pid: parts[1].to_string(),
+            name: normalize_process_name(parts[0]),
+            port: path.to_string(),
+            socket_type: Some("unix".to_string()),
+            fd: Some(parts[3].to_string()),
+            fd_type: Some(parts[4].to_string()),
+            ..Default::default()
+        });
+    }
+    
+    processes
+}
+
+// Check a port pattern such as `8*` or `>1024` against every listening port
+fn check_port_pattern(tools: &ToolPaths, pattern: &str, family: IpFamily) -> PortCheckResult {
+    let range = match parse_port_pattern(pattern) {
+        Ok(range) => range,
+        Err(e) => {
+            log_line!("[ERROR] {}", e);
+            return PortCheckResult {
+                is_occupied: false,
+                processes: vec![],
+                binding: None,
+                error: Some(e.to_string()),
+                duration_ms: None,
+                output_truncated: false,
+                schema_version: SCHEMA_VERSION,
+            };
+        }
+    };
+    log_line!("[INFO] Checking port pattern '{}' as ports {}-{}", pattern, range.start(), range.end());
+    
+    let started = Instant::now();
+    let listeners = find_family_listeners(tools, family);
+    let duration_ms = elapsed_ms(started);
+    
+    match listeners {
+        Ok(listeners) => {
+            let processes: Vec<ProcessInfo> = listeners
+                .into_iter()
+                .filter(|p| p.port.parse::<u16>().is_ok_and(|port| range.contains(&port)))
+                .collect();
+            log_line!("[INFO] Found {} listener(s) matching pattern '{}'", processes.len(), pattern);
+            
+            PortCheckResult {
+                is_occupied: !processes.is_empty(),
+                processes,
+                binding: None,
+                error: None,
+                duration_ms: Some(duration_ms),
+                output_truncated: false,
+                schema_version: SCHEMA_VERSION,
+            }
+        }
+        Err(e) => PortCheckResult {
+            is_occupied: false,
+            processes: vec![],
+            binding: None,
+            error: Some(e.to_string()),
+            duration_ms: Some(duration_ms),
+            output_truncated: false,
+            schema_version: SCHEMA_VERSION,
+        },
+    }
+}
+
+// One external command the app ran, for the audit trail behind `get_command_audit`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandAuditEntry {
+    pub timestamp: u64, // Milliseconds since the Unix epoch, when the command finished or was started
+    pub program: String,
+    pub args: Vec<String>,
+    pub exit_code: Option<i32>, // None if it failed to start, was ended by a signal, or was left running
+    pub error: Option<String>,  // Why it could not be started or waited for
+}
+
+pub const COMMAND_AUDIT_CAPACITY: usize = 500;
+
+// The most recent commands, oldest dropped first. It lives in a static rather than AppState
+// because commands are run from helpers that only get ToolPaths, often on background threads.
+static COMMAND_AUDIT: Mutex<VecDeque<CommandAuditEntry>> = Mutex::new(VecDeque::new());
+
+// Append a command and its outcome to the audit trail
+pub fn record_command(command: &Command, outcome: Result<Option<i32>, &std::io::Error>) {
+    let entry = CommandAuditEntry {
+        timestamp: now_millis(),
+        program: command.get_program().to_string_lossy().into_owned(),
+        args: command.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect(),
+        exit_code: outcome.as_ref().ok().copied().flatten(),
+        error: outcome.err().map(|e| e.to_string()),
+    };
+    
+    match COMMAND_AUDIT.lock() {
+        Ok(mut audit) => {
+            while audit.len() >= COMMAND_AUDIT_CAPACITY {
+                audit.pop_front();
+            }
+            audit.push_back(entry);
+        }
+        Err(e) => log_line!("[ERROR] Command audit lock poisoned: {}", e),
+    }
+}
+
+// The audit trail, newest entries first
+pub fn command_audit() -> Vec<CommandAuditEntry> {
+    match COMMAND_AUDIT.lock() {
+        Ok(audit) => audit.iter().rev().cloned().collect(),
+        Err(e) => {
+            log_line!("[ERROR] Command audit lock poisoned: {}", e);
+            vec![]
+        }
+    }
+}
+
+// `Command::output` that also records the command in the audit trail. Every external
+// command goes through this, `capped_output` or an explicit `record_command`.
+pub trait AuditedOutput {
+    fn audited_output(&mut self) -> std::io::Result<std::process::Output>;
+}
+
+impl AuditedOutput for Command {
+    fn audited_output(&mut self) -> std::io::Result<std::process::Output> {
+        let output = self.output();
+        record_command(self, output.as_ref().map(|output| output.status.code()));
+        output
+    }
+}
+
+// Every external command this session ran (lsof, ps, kill, ss, ...), newest first
+#[tauri::command]
+fn get_command_audit() -> Vec<CommandAuditEntry> {
+    command_audit()
+}
+
+// Run a command to completion, also returning how long it took in milliseconds
+pub fn timed_output(command: &mut Command, limit: usize) -> (std::io::Result<CappedOutput>, u64) {
+    let started = Instant::now();
+    let output = capped_output(command, limit);
+    (output, elapsed_ms(started))
+}
+
+// Output of a command run with `capped_output`
+#[derive(Debug)]
+pub struct CappedOutput {
+    pub status: ExitStatus,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub truncated: bool, // stdout or stderr went over the limit
+}
+
+// Like `Command::output`, but keeping at most `limit` bytes of each stream. A command whose
+// stdout goes over is killed; stderr past the limit is read and discarded.
+pub fn capped_output(command: &mut Command, limit: usize) -> std::io::Result<CappedOutput> {
+    let output = run_capped(command, limit);
+    record_command(command, output.as_ref().map(|output| output.status.code()));
+    output
+}
+
+fn run_capped(command: &mut Command, limit: usize) -> std::io::Result<CappedOutput> {
+    let mut child = command.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    
+    // stderr is read on its own thread so neither pipe can fill up and stall the command
+    let stderr = child.stderr.take();
+    let stderr_reader = std::thread::spawn(move || -> std::io::Result<(Vec<u8>, bool)> {
+        let Some(mut stderr) = stderr else {
+            return Ok((Vec::new(), false));
+        };
+        let (data, truncated) = read_capped(&mut stderr, limit)?;
+        if truncated {
+            std::io::copy(&mut stderr, &mut std::io::sink())?;
+        }
+        Ok((data, truncated))
+    });
+    
+    let (stdout, stdout_truncated) = match child.stdout.take() {
+        Some(mut stdout) => read_capped(&mut stdout, limit)?,
+        None => (Vec::new(), false),
+    };
+    if stdout_truncated {
+        log_line!("[WARN] Command output exceeded {} bytes, truncating and stopping the command", limit);
+        let _ = child.kill();
+    }
+    
+    let (stderr, stderr_truncated) = stderr_reader
+        .join()
+        .unwrap_or_else(|_| Err(std::io::Error::other("stderr reader panicked")))?;
+    let status = child.wait()?;
+    
+    Ok(CappedOutput { status, stdout, stderr, truncated: stdout_truncated || stderr_truncated })
+}
+
+// Read up to `limit` bytes, reporting whether the reader had more to give
+pub fn read_capped<R: Read>(reader: &mut R, limit: usize) -> std::io::Result<(Vec<u8>, bool)> {
+    let mut data = Vec::new();
+    reader.take(limit as u64).read_to_end(&mut data)?;
+    
+    let mut probe = [0u8; 1];
+    let truncated = data.len() == limit && reader.read(&mut probe)? > 0;
+    Ok((data, truncated))
+}
+
+// Milliseconds since `started`, rounded up so a command that ran never reports 0
+pub fn elapsed_ms(started: Instant) -> u64 {
+    let micros = started.elapsed().as_micros();
+    u64::try_from(micros.div_ceil(1000)).unwrap_or(u64::MAX)
+}
+
+// Whether a port query uses pattern syntax rather than being a plain port number
+pub fn is_port_pattern(query: &str) -> bool {
+    let query = query.trim();
+    query.contains('*') || query.starts_with('>') || query.contains('-')
+}
+
+// Expand a port pattern into the inclusive range of ports it covers:
+// - `3000` is just that port, `3000-3010` an explicit range
+// - `>1024` is every port above 1024
+// - `8*` fills the wildcard up to a 4-digit port (8000-8999, `80*` is 8000-8099);
+//   prefixes of 4+ digits grow to 5 digits (`6553*` is 65530-65535)
+pub fn parse_port_pattern(pattern: &str) -> Result<RangeInclusive<u16>, ProcessError> {
+    let trimmed = pattern.trim();
+    let invalid = |reason: &str| ProcessError::InvalidPortPattern {
+        pattern: pattern.to_string(),
+        reason: reason.to_string(),
+    };
+    let parse_port = |value: &str| -> Result<u16, ProcessError> {
+        if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid("expected a port number"));
+        }
+        value.parse::<u16>().map_err(|_| invalid("port must be between 0 and 65535"))
+    };
+    
+    if let Some(bound) = trimmed.strip_prefix('>') {
+        let bound = parse_port(bound.trim())?;
+        if bound == u16::MAX {
+            return Err(invalid("no ports above 65535"));
+        }
+        return Ok(bound + 1..=u16::MAX);
+    }
+    
+    if let Some(prefix) = trimmed.strip_suffix('*') {
+        if prefix.is_empty() {
+            return Err(invalid("a bare '*' matches every port, use '>0' instead"));
+        }
+        if prefix.starts_with('0') {
+            return Err(invalid("a wildcard prefix can't start with 0"));
+        }
+        if !prefix.bytes().all(|b| b.is_ascii_digit()) || prefix.len() > 4 {
+            return Err(invalid("use digits followed by a single trailing '*'"));
+        }
+        
+        let width = (prefix.len() + 1).max(4);
+        let scale = 10u32.pow((width - prefix.len()) as u32);
+        let start = prefix.parse::<u32>().unwrap_or(0) * scale;
+        let end = (start + scale - 1).min(u16::MAX as u32);
+        if start > u16::MAX as u32 {
+            return Err(invalid("pattern only covers ports above 65535"));
+        }
+        return Ok(start as u16..=end as u16);
+    }
+    
+    if let Some((start, end)) = trimmed.split_once('-') {
+        let (start, end) = (parse_port(start.trim())?, parse_port(end.trim())?);
+        if start > end {
+            return Err(invalid("range start is greater than its end"));
+        }
+        return Ok(start..=end);
+    }
+    
+    let port = parse_port(trimmed)?;
+    Ok(port..=port)
+}
+
+// Kill a process by PID using SIGKILL signal (force kill). Critical and root-owned
+// processes are refused with ConfirmationRequired until re-invoked with `confirmed`.
+#[tauri::command]
+fn kill_process(
+    state: State<'_, AppState>,
+    pid: String,
+    expected_name: Option<String>,
+    confirmed: Option<bool>,
+) -> Result<String, ProcessError> {
+    kill_and_record(&state, pid, true, expected_name.as_deref(), None, confirmed.unwrap_or(false))
+}
+
+// Kill a process by PID using SIGTERM signal (graceful kill). With `min_age_seconds`, a
+// process younger than that is refused, so a service still starting up isn't cut short.
+#[tauri::command]
+fn graceful_kill_process(
+    state: State<'_, AppState>,
+    pid: String,
+    expected_name: Option<String>,
+    min_age_seconds: Option<u64>,
+    confirmed: Option<bool>,
+) -> Result<String, ProcessError> {
+    kill_and_record(&state, pid, false, expected_name.as_deref(), min_age_seconds, confirmed.unwrap_or(false))
+}
+
+// Send an arbitrary signal (e.g. "HUP" to reload a daemon, "USR1", or a number like "10"),
+// validated against the signals this platform actually has
+#[tauri::command]
+fn send_signal(
+    state: State<'_, AppState>,
+    pid: String,
+    signal: String,
+    expected_name: Option<String>,
+    confirmed: Option<bool>,
+) -> Result<String, ProcessError> {
+    let name = parse_signal(&signal)?;
+    log_line!("[INFO] Sending SIG{} to PID: {}", name, pid);
+    // Most signals end a process that doesn't handle them, so each one counts as a kill
+    check_kill_rate(&state, now_millis())?;
+    
+    let tools = state.tools();
+    let process_name = get_process_name(&tools, &pid);
+    let result = confirm_kill(&tools, &pid, confirmed.unwrap_or(false))
+        .and_then(|_| deliver_signal(&tools, &pid, name, expected_name.as_deref(), &state.allowlist()));
+    
+    record_history(&state, KillHistoryEntry {
+        timestamp: now_millis(),
+        pid,
+        name: process_name,
+        signal: format!("SIG{}", name),
+        success: result.is_ok(),
+        message: match &result {
+            Ok(message) => message.clone(),
+            Err(e) => e.to_string(),
+        },
+        detail: None,
+    });
+    
+    result
+}
+
+// Signal every process in a group at once (a shell and its jobs, say) with SIGTERM, or
+// SIGKILL when forced. The group leader must pass the allowlist, and a critical or
+// root-owned leader needs `confirmed`.
+#[tauri::command]
+fn kill_process_group(state: State<'_, AppState>, pgid: String, force: bool, confirmed: Option<bool>) -> Result<String, ProcessError> {
+    log_line!("[INFO] Attempting to {} process group {}", if force { "force kill" } else { "terminate" }, pgid);
+    
+    let args = process_group_kill_args(&pgid, force, std::process::id())?;
+    // The whole group counts as one kill against the rate limit
+    check_kill_rate(&state, now_millis())?;
+    let tools = state.tools();
+    let leader_name = get_process_name(&tools, &pgid);
+    check_kill_target(&tools, &pgid, None, &state.allowlist())?;
+    confirm_kill(&tools, &pgid, confirmed.unwrap_or(false))?;
+    
+    log_line!("[DEBUG] Executing command: kill {}", args.join(" "));
+    let result = match tools.kill().args(&args).audited_output() {
+        Ok(output) if output.status.success() => {
+            log_line!("[INFO] Signalled process group {}", pgid);
+            Ok(format!("Process group {} {}", pgid, if force { "force killed" } else { "terminated" }))
+        }
+        Ok(output) => {
+            let error_msg = String::from_utf8_lossy(&output.stderr);
+            log_line!("[ERROR] Failed to signal process group {}: status={}, stderr='{}'", pgid, output.status, error_msg);
+            Err(already_gone(&pgid, &error_msg).unwrap_or_else(|| ProcessError::from_output(
+                format!("Failed to signal process group {}: {}", pgid, error_msg.trim()),
+                &output,
+            )))
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute kill command for process group {}: {}", pgid, e);
+            Err(ProcessError::command_failed(format!("Failed to execute kill command: {}", e)))
+        }
+    };
+    
+    record_history(&state, KillHistoryEntry {
+        timestamp: now_millis(),
+        pid: format!("-{}", pgid),
+        name: leader_name,
+        signal: if force { "SIGKILL" } else { "SIGTERM" }.to_string(),
+        success: result.is_ok(),
+        message: match &result {
+            Ok(message) => message.clone(),
+            Err(e) => e.to_string(),
+        },
+        detail: None,
+    });
+    
+    result
+}
+
+// Build the `kill` arguments that signal a whole process group: the signal, then the
+// negated PGID, e.g. `-TERM -4242`. Group 0 would be our own group and group 1 is read
+// as -1, every process we may signal, so both are refused, as is our own PID's group.
+pub fn process_group_kill_args(pgid: &str, force: bool, own_pid: u32) -> Result<Vec<String>, ProcessError> {
+    let invalid = |reason: &str| {
+        log_line!("[ERROR] Invalid process group '{}': {}", pgid, reason);
+        Err(ProcessError::InvalidPid(format!("Invalid process group {}: {}", pgid, reason)))
+    };
+    
+    if pgid.is_empty() || !pgid.bytes().all(|b| b.is_ascii_digit()) {
+        return invalid("expected a positive number");
+    }
+    match pgid.parse::<u32>() {
+        Ok(0) => return invalid("group 0 is this application's own process group"),
+        Ok(1) => return invalid("signalling group 1 would signal every process"),
+        Ok(_) => {}
+        Err(_) => return invalid("expected a positive number"),
+    }
+    if let Some(reason) = protected_pid_reason(pgid, own_pid) {
+        return invalid(reason);
+    }
+    
+    let signal = if force { "-KILL" } else { "-TERM" };
+    Ok(vec![signal.to_string(), format!("-{}", pgid.trim_start_matches('0'))])
+}
+
+// Run `kill -s <name>` after the same PID, allowlist and PID-reuse checks a kill gets
+fn deliver_signal(
+    tools: &ToolPaths,
+    pid: &str,
+    signal: &str,
+    expected_name: Option<&str>,
+    allowlist: &[String],
+) -> Result<String, ProcessError> {
+    validate_kill_pid(pid)?;
+    check_kill_target(tools, pid, expected_name, allowlist)?;
+    
+    log_line!("[DEBUG] Executing kill -s {} command for PID: {}", signal, pid);
+    let output = tools.kill()
+        .args(["-s", signal, pid])
+        .audited_output()
+        .map_err(|e| {
+            log_line!("[ERROR] Failed to execute kill command for PID {}: {}", pid, e);
+            ProcessError::command_failed(format!("Failed to execute kill command: {}", e))
+        })?;
+    
+    if output.status.success() {
+        log_line!("[INFO] Sent SIG{} to process with PID: {}", signal, pid);
+        Ok(format!("Sent SIG{} to process {}", signal, pid))
+    } else {
+        let error_msg = String::from_utf8_lossy(&output.stderr);
+        if let Some(gone) = already_gone(pid, &error_msg) {
+            return Err(gone);
+        }
+        log_line!("[ERROR] Failed to send SIG{} to process {}: status={}, stderr='{}'", 
+                 signal, pid, output.status, error_msg);
+        Err(ProcessError::from_output(format!("Failed to send SIG{} to process {}: {}", 
+                   signal, pid, error_msg.trim()), &output))
+    }
+}
+
+// Signal names and numbers per platform; the numbering differs between Linux and macOS
+// (e.g. USR1 is 10 on Linux, 30 on macOS) and each has signals the other lacks.
+#[cfg(target_os = "linux")]
+pub const SIGNALS: &[(&str, i32)] = &[
+    ("HUP", 1), ("INT", 2), ("QUIT", 3), ("ILL", 4), ("TRAP", 5), ("ABRT", 6), ("BUS", 7),
+    ("FPE", 8), ("KILL", 9), ("USR1", 10), ("SEGV", 11), ("USR2", 12), ("PIPE", 13),
+    ("ALRM", 14), ("TERM", 15), ("STKFLT", 16), ("CHLD", 17), ("CONT", 18), ("STOP", 19),
+    ("TSTP", 20), ("TTIN", 21), ("TTOU", 22), ("URG", 23), ("XCPU", 24), ("XFSZ", 25),
+    ("VTALRM", 26), ("PROF", 27), ("WINCH", 28), ("IO", 29), ("PWR", 30), ("SYS", 31),
+];
+
+#[cfg(target_os = "macos")]
+pub const SIGNALS: &[(&str, i32)] = &[
+    ("HUP", 1), ("INT", 2), ("QUIT", 3), ("ILL", 4), ("TRAP", 5), ("ABRT", 6), ("EMT", 7),
+    ("FPE", 8), ("KILL", 9), ("BUS", 10), ("SEGV", 11), ("SYS", 12), ("PIPE", 13),
+    ("ALRM", 14), ("TERM", 15), ("URG", 16), ("STOP", 17), ("TSTP", 18), ("CONT", 19),
+    ("CHLD", 20), ("TTIN", 21), ("TTOU", 22), ("IO", 23), ("XCPU", 24), ("XFSZ", 25),
+    ("VTALRM", 26), ("PROF", 27), ("WINCH", 28), ("INFO", 29), ("USR1", 30), ("USR2", 31),
+];
+
+// Elsewhere only the signals whose numbers POSIX fixes are offered
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub const SIGNALS: &[(&str, i32)] = &[
+    ("HUP", 1), ("INT", 2), ("QUIT", 3), ("ABRT", 6), ("KILL", 9), ("ALRM", 14), ("TERM", 15),
+];
+
+// Resolve a signal given by name ("TERM", "sigterm") or number ("15") to its canonical
+// name in `SIGNALS`, rejecting anything this platform doesn't have
+pub fn parse_signal(signal: &str) -> Result<&'static str, ProcessError> {
+    let trimmed = signal.trim();
+    let upper = trimmed.to_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    let number = trimmed.parse::<i32>().ok();
+    
+    SIGNALS
+        .iter()
+        .find(|(known, value)| *known == name || Some(*value) == number)
+        .map(|(known, _)| *known)
+        .ok_or_else(|| {
+            log_line!("[ERROR] Unsupported signal: {}", signal);
+            ProcessError::InvalidSignal {
+                signal: trimmed.to_string(),
+                valid: SIGNALS.iter().map(|(known, _)| *known).collect(),
+            }
+        })
+}
+
+// Configure where lsof/ps/kill live for systems where they aren't on the app's PATH
+#[tauri::command]
+fn set_tool_paths(state: State<'_, AppState>, paths: ToolPaths) {
+    log_line!("[INFO] Using tool paths: {:?}", paths);
+    match state.tool_paths.lock() {
+        Ok(mut current) => *current = paths,
+        Err(e) => log_line!("[ERROR] Tool paths lock poisoned, paths not updated: {}", e),
+    }
+}
+
+#[tauri::command]
+fn get_tool_paths(state: State<'_, AppState>) -> ToolPaths {
+    state.tools()
+}
+
+// Restrict kills to the given process names; an empty list lifts the restriction
+#[tauri::command]
+fn set_kill_allowlist(state: State<'_, AppState>, names: Vec<String>) {
+    let names: Vec<String> = names.into_iter().map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect();
+    log_line!("[INFO] Kill allowlist set to: {:?}", names);
+    match state.kill_allowlist.lock() {
+        Ok(mut allowlist) => *allowlist = names,
+        Err(e) => log_line!("[ERROR] Kill allowlist lock poisoned, allowlist not updated: {}", e),
+    }
+}
+
+#[tauri::command]
+fn get_kill_allowlist(state: State<'_, AppState>) -> Vec<String> {
+    state.allowlist()
+}
+
+// Allow at most `max_per_second` kills per second; None (or 0) removes the limit
+#[tauri::command]
+fn set_kill_rate_limit(state: State<'_, AppState>, max_per_second: Option<u32>) {
+    log_line!("[INFO] Kill rate limit set to: {:?} per second", max_per_second);
+    match state.kill_rate.lock() {
+        Ok(mut limiter) => limiter.set_limit(max_per_second),
+        Err(e) => log_line!("[ERROR] Kill rate limiter lock poisoned, limit not updated: {}", e),
+    }
+}
+
+#[tauri::command]
+fn get_kill_rate_limit(state: State<'_, AppState>) -> Option<u32> {
+    match state.kill_rate.lock() {
+        Ok(limiter) => limiter.limit(),
+        Err(e) => {
+            log_line!("[ERROR] Kill rate limiter lock poisoned: {}", e);
+            None
+        }
+    }
+}
+
+// Get the kill history of this session, newest entries first
+#[tauri::command]
+fn get_kill_history(state: State<'_, AppState>) -> Vec<KillHistoryEntry> {
+    match state.kill_history.lock() {
+        Ok(history) => history.newest_first(),
+        Err(e) => {
+            log_line!("[ERROR] Kill history lock poisoned: {}", e);
+            vec![]
+        }
+    }
+}
+
+// Kill a process and append the outcome to the session's kill history. Critical and
+// root-owned targets are refused with ConfirmationRequired unless `confirmed` is passed.
+fn kill_and_record(
+    state: &AppState,
+    pid: String,
+    force: bool,
+    expected_name: Option<&str>,
+    min_age_seconds: Option<u64>,
+    confirmed: bool,
+) -> Result<String, ProcessError> {
+    check_kill_rate(state, now_millis())?;
+    
+    // Capture the process before killing, afterwards it is gone
+    let tools = state.tools();
+    let detail = capture_kill_detail(&tools, &pid);
+    let result = send_kill_signal(&tools, pid.clone(), force, expected_name, min_age_seconds, confirmed, &state.allowlist());
+    
+    let signal = if force { "SIGKILL" } else { "SIGTERM" };
+    record_history(state, kill_history_entry(pid, signal, detail, &result, now_millis()));
+    result
+}
+
+// Count a kill against the rate limit. A poisoned limiter lets kills through: it guards
+// against runaway loops, not against unauthorized kills.
+fn check_kill_rate(state: &AppState, now_ms: u64) -> Result<(), ProcessError> {
+    let mut limiter = match state.kill_rate.lock() {
+        Ok(limiter) => limiter,
+        Err(e) => {
+            log_line!("[ERROR] Kill rate limiter lock poisoned, not limiting: {}", e);
+            return Ok(());
+        }
+    };
+    
+    limiter.try_acquire(now_ms).map_err(|retry_after_ms| {
+        let max_per_second = limiter.limit().unwrap_or_default();
+        log_line!("[WARN] Kill refused by the rate limit of {} per second, retry in {} ms", max_per_second, retry_after_ms);
+        ProcessError::RateLimited { max_per_second, retry_after_ms }
+    })
+}
+
+// Snapshot a process's HISTORY_DETAIL_FIELDS for the kill history; None if it isn't running
+fn capture_kill_detail(tools: &ToolPaths, pid: &str) -> Option<ProcessDetail> {
+    let fields: Vec<String> = HISTORY_DETAIL_FIELDS.iter().map(|field| field.to_string()).collect();
+    let plan = plan_detail_fields(&fields).ok()?;
+    fetch_selected_detail(tools, pid, &plan).ok()
+}
+
+// Build the history entry of a kill from the detail captured before it
+pub fn kill_history_entry(
+    pid: String,
+    signal: &str,
+    detail: Option<ProcessDetail>,
+    result: &Result<String, ProcessError>,
+    timestamp: u64,
+) -> KillHistoryEntry {
+    KillHistoryEntry {
+        timestamp,
+        pid,
+        name: detail.as_ref().map(|detail| detail.name.clone()).filter(|name| !name.is_empty()),
+        signal: signal.to_string(),
+        success: result.is_ok(),
+        message: match result {
+            Ok(message) => message.clone(),
+            Err(e) => e.to_string(),
+        },
+        detail,
+    }
+}
+
+// Append an entry to the session's kill history
+fn record_history(state: &AppState, entry: KillHistoryEntry) {
+    match state.kill_history.lock() {
+        Ok(mut history) => history.record(entry),
+        Err(e) => log_line!("[ERROR] Kill history lock poisoned, entry not recorded: {}", e),
+    }
+}
+
+// Look up the command name of a PID, None if it doesn't exist or ps fails
+fn get_process_name(tools: &ToolPaths, pid: &str) -> Option<String> {
+    read_process_name(tools, pid).map(|(name, _)| name)
+}
+
+// The process name along with whether invalid UTF-8 in it had to be replaced
+fn read_process_name(tools: &ToolPaths, pid: &str) -> Option<(String, bool)> {
+    pid.parse::<u32>().ok()?;
+    
+    let output = tools.ps()
+        .args(["-p", pid, "-o", "comm="])
+        .audited_output()
+        .ok()?;
+    
+    let (name, replaced) = decode_name(output.stdout.trim_ascii());
+    if output.status.success() && !name.is_empty() {
+        Some((name, replaced))
+    } else {
+        None
+    }
+}
+
+// Decode command output strictly, reporting whether invalid UTF-8 was replaced with
+// U+FFFD. The replaced text is fine to display but not to match a kill target against.
+pub fn decode_name(bytes: &[u8]) -> (String, bool) {
+    match String::from_utf8_lossy(bytes) {
+        std::borrow::Cow::Borrowed(text) => (text.to_string(), false),
+        std::borrow::Cow::Owned(text) => (text, true),
+    }
+}
+
+// Only a name that decoded cleanly may be matched against; a replaced one is refused
+pub fn strict_name(pid: &str, decoded: Option<(String, bool)>) -> Result<Option<String>, ProcessError> {
+    match decoded {
+        Some((name, true)) => {
+            log_line!("[ERROR] Name of PID {} is not valid UTF-8: '{}'", pid, name);
+            Err(ProcessError::UndecodableName { pid: pid.to_string(), name })
+        }
+        decoded => Ok(decoded.map(|(name, _)| name)),
+    }
+}
+
+// Current time in milliseconds since the Unix epoch
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// Look up what `check_confirmation` needs about a PID and apply it; nothing to look up
+// once the caller has confirmed
+fn confirm_kill(tools: &ToolPaths, pid: &str, confirmed: bool) -> Result<(), ProcessError> {
+    if confirmed {
+        return Ok(());
+    }
+    let owner = get_process_owner(tools, pid);
+    check_confirmation(
+        pid,
+        get_process_name(tools, pid).as_deref(),
+        owner.as_ref().map(|(_, user)| user.as_str()),
+        owner.as_ref().map(|(euid, _)| *euid),
+        std::process::id(),
+        confirmed,
+    )
+}
+
+// Internal function to kill process with specified signal. When `expected_name` is given
+// the process at the PID must still carry that name, which guards against PID reuse.
+// When `min_age_seconds` is given, a graceful kill of a younger process is refused; a
+// force kill overrides the guard. Critical and root-owned targets need `confirmed`,
+// otherwise ConfirmationRequired comes back carrying what the UI needs to ask the user.
+// A non-empty `allowlist` refuses any process whose name isn't on it.
+pub fn kill_process_with_signal(
+    pid: String,
+    force: bool,
+    expected_name: Option<String>,
+    min_age_seconds: Option<u64>,
+    confirmed: bool,
+    allowlist: &[String],
+) -> Result<String, ProcessError> {
+    send_kill_signal(&ToolPaths::default(), pid, force, expected_name.as_deref(), min_age_seconds, confirmed, allowlist)
+}
+
+fn send_kill_signal(
+    tools: &ToolPaths,
+    pid: String,
+    force: bool,
+    expected_name: Option<&str>,
+    min_age_seconds: Option<u64>,
+    confirmed: bool,
+    allowlist: &[String],
+) -> Result<String, ProcessError> {
+    let signal_type = if force { "SIGKILL (-9)" } else { "SIGTERM (-15)" };
+    log_line!("[INFO] Attempting to {} process with PID: {} using {}", 
+             if force { "force kill" } else { "gracefully terminate" }, pid, signal_type);
+    
+    validate_kill_pid(&pid)?;
+    check_kill_target(tools, &pid, expected_name, allowlist)?;
+    if min_age_seconds.is_some() && !force {
+        check_min_age(&pid, get_process_age(tools, &pid), min_age_seconds, force)?;
+    }
+    confirm_kill(tools, &pid, confirmed)?;
+    
+    let signal_arg = if force { "-9" } else { "-15" };
+    log_line!("[DEBUG] Executing kill {} command for PID: {}", signal_arg, pid);
+    
+    let output = tools.kill()
+        .arg(signal_arg)
+        .arg(&pid)
+        .audited_output();
+
+    match output {
+        Ok(output) => {
+            if output.status.success() {
+                let action = if force { "force killed" } else { "gracefully terminated" };
+                log_line!("[INFO] Successfully {} process with PID: {}", action, pid);
+                Ok(format!("Process {} {} successfully", pid, action))
+            } else {
+                let error_msg = String::from_utf8_lossy(&output.stderr);
+                if let Some(gone) = already_gone(&pid, &error_msg) {
+                    return Err(gone);
+                }
+                log_line!("[ERROR] Failed to {} process {}: status={}, stderr='{}'", 
+                         if force { "force kill" } else { "gracefully terminate" }, 
+                         pid, output.status, error_msg);
+                Err(ProcessError::from_output(format!("Failed to {} process {}: {}", 
+                           if force { "force kill" } else { "gracefully terminate" }, 
+                           pid, error_msg.trim()), &output))
+            }
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute kill command for PID {}: {}", pid, e);
+            Err(ProcessError::command_failed(format!("Failed to execute kill command: {}", e)))
+        },
+    }
+}
+
+// How long a process has been running in seconds, None if it doesn't exist or ps fails
+fn get_process_age(tools: &ToolPaths, pid: &str) -> Option<u64> {
+    let output = tools.ps()
+        .args(["-p", pid, "-o", "etime="])
+        .audited_output()
+        .ok()?;
+    
+    if output.status.success() {
+        parse_etime(&String::from_utf8_lossy(&output.stdout))
+    } else {
+        None
+    }
+}
+
+// Whether a kill of a process would go through, so the UI can disable the kill button
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KillPrecheck {
+    pub killable: bool,
+    pub reason: Option<String>, // Why not, when not killable
+}
+
+// What a kill precheck needs to know about the target process
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KillTargetState {
+    pub name: String,
+    pub uid: Option<u32>,
+    pub stat: String, // ps `stat`, e.g. "S", "Z+", "D"
+}
+
+// Check up front whether killing a PID would work: it must exist, not be guarded, pass the
+// allowlist, be able to receive signals and, unless we run as root, belong to our user
+#[tauri::command]
+fn can_kill(state: State<'_, AppState>, pid: String) -> Result<KillPrecheck, ProcessError> {
+    let pid = pid.trim().to_string();
+    validate_kill_pid(&pid)?;
+    
+    let tools = state.tools();
+    let own_pid = std::process::id();
+    let own_uid = get_process_owner(&tools, &own_pid.to_string()).map(|(uid, _)| uid);
+    let target = get_kill_target_state(&tools, &pid);
+    
+    let precheck = kill_precheck(&pid, target.as_ref(), own_uid, own_pid, &state.allowlist());
+    log_line!("[DEBUG] Kill precheck for PID {}: {:?}", pid, precheck);
+    Ok(precheck)
+}
+
+// Name, owner and state of a process in one ps call; None if it doesn't exist
+fn get_kill_target_state(tools: &ToolPaths, pid: &str) -> Option<KillTargetState> {
+    let output = tools.ps()
+        .args(["-p", pid, "-o", "uid=,stat=,comm="])
+        .audited_output()
+        .ok()?;
+    
+    if !output.status.success() {
+        return None;
+    }
+    let output_str = String::from_utf8_lossy(&output.stdout);
+    let (fields, comm) = split_leading_fields(&output_str, 2)?;
+    Some(KillTargetState {
+        name: comm.to_string(),
+        uid: fields[0].parse().ok(),
+        stat: fields[1].to_string(),
+    })
+}
+
+// The kill precheck itself. Reasons are checked in order of how fundamental they are, so
+// a zombie init reports being critical rather than being a zombie.
+pub fn kill_precheck(
+    pid: &str,
+    target: Option<&KillTargetState>,
+    own_uid: Option<u32>,
+    own_pid: u32,
+    allowlist: &[String],
+) -> KillPrecheck {
+    let refuse = |reason: String| KillPrecheck { killable: false, reason: Some(reason) };
+    
+    let Some(target) = target else {
+        return refuse(format!("Process {} is not running", pid));
+    };
+    if let Some(reason) = protected_pid_reason(pid, own_pid) {
+        return refuse(format!("PID {} is protected: {}", pid, reason));
+    }
+    if let Err(e) = check_allowlist(pid, Some(&target.name), allowlist) {
+        return refuse(e.to_string());
+    }
+    match target.stat.trim().chars().next() {
+        Some('Z') => return refuse(format!("PID {} is a zombie: it has already exited and waits for its parent to reap it", pid)),
+        Some('D') => return refuse(format!("PID {} is in uninterruptible sleep and ignores signals until its I/O completes", pid)),
+        _ => {}
+    }
+    // Root may signal anything; an unknown owner is left for the kill itself to judge
+    if let (Some(own_uid), Some(uid)) = (own_uid, target.uid) {
+        if own_uid != 0 && uid != own_uid {
+            return refuse(format!("PID {} belongs to another user (uid {})", pid, uid));
+        }
+    }
+    
+    KillPrecheck { killable: true, reason: None }
+}
+
+// Effective uid and user name of a process, None if it doesn't exist or ps fails
+fn get_process_owner(tools: &ToolPaths, pid: &str) -> Option<(u32, String)> {
+    let output = tools.ps()
+        .args(["-p", pid, "-o", "uid=,user="])
+        .audited_output()
+        .ok()?;
+    
+    if !output.status.success() {
+        return None;
+    }
+    let output_str = String::from_utf8_lossy(&output.stdout);
+    let (fields, user) = split_leading_fields(&output_str, 1)?;
+    Some((fields[0].parse().ok()?, user.to_string()))
+}
+
+// Decide whether a kill must be confirmed first: critical processes (PID 1, this
+// application) and root-owned ones are refused until the caller passes `confirmed`.
+pub fn check_confirmation(
+    pid: &str,
+    name: Option<&str>,
+    user: Option<&str>,
+    euid: Option<u32>,
+    own_pid: u32,
+    confirmed: bool,
+) -> Result<(), ProcessError> {
+    let is_critical = protected_pid_reason(pid, own_pid).is_some();
+    if confirmed || !(is_critical || euid == Some(0)) {
+        return Ok(());
+    }
+    
+    log_line!("[WARN] Kill of PID {} ({:?}) needs confirmation", pid, name);
+    Err(ProcessError::ConfirmationRequired {
+        pid: pid.to_string(),
+        name: name.map(str::to_string),
+        user: user.map(str::to_string),
+        is_critical,
+    })
+}
+
+// Decide whether a process is old enough to kill. A force kill always goes ahead, and so
+// does a process of unknown age: if it's gone, the kill reports that itself.
+pub fn check_min_age(pid: &str, age_seconds: Option<u64>, min_age_seconds: Option<u64>, force: bool) -> Result<(), ProcessError> {
+    match (age_seconds, min_age_seconds) {
+        (Some(age_seconds), Some(min_age_seconds)) if !force && age_seconds < min_age_seconds => {
+            log_line!("[WARN] PID {} is only {}s old, under the {}s minimum age", pid, age_seconds, min_age_seconds);
+            Err(ProcessError::TooYoung {
+                pid: pid.to_string(),
+                age_seconds,
+                min_age_seconds,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+// Recognize kill's ESRCH failure, the PID having exited between listing and killing.
+// procps prints `kill: (1234) - No such process`, util-linux `kill: (1234): No such
+// process`, macOS and the shell builtins `kill: 1234: No such process`, and BusyBox
+// `kill: can't kill pid 1234: No such process`; the shared phrase is matched.
+pub fn already_gone(pid: &str, stderr: &str) -> Option<ProcessError> {
+    if stderr.to_lowercase().contains("no such process") {
+        log_line!("[INFO] Process {} had already terminated", pid);
+        Some(ProcessError::AlreadyGone(pid.to_string()))
+    } else {
+        None
+    }
+}
+
+// Apply the allowlist and PID-reuse checks, resolving the name only when one is needed
+fn check_kill_target(tools: &ToolPaths, pid: &str, expected_name: Option<&str>, allowlist: &[String]) -> Result<(), ProcessError> {
+    if expected_name.is_some() || !allowlist.is_empty() {
+        let current_name = strict_name(pid, read_process_name(tools, pid))?;
+        check_allowlist(pid, current_name.as_deref(), allowlist)?;
+        if let Some(expected) = expected_name {
+            verify_expected_name(pid, expected, current_name.as_deref())?;
+        }
+    }
+    Ok(())
+}
+
+// Check a process name against the kill allowlist. An empty allowlist allows everything;
+// otherwise the executable's basename must match an entry exactly (case-insensitive),
+// and a PID whose name can't be resolved is refused.
+pub fn check_allowlist(pid: &str, name: Option<&str>, allowlist: &[String]) -> Result<(), ProcessError> {
+    if allowlist.is_empty() {
+        return Ok(());
+    }
+    
+    let allowed = name.is_some_and(|name| {
+        let basename = normalize_process_name(process_basename(name));
+        allowlist.iter().any(|entry| !entry.trim().is_empty() && entry.trim().eq_ignore_ascii_case(&basename))
+    });
+    
+    if allowed {
+        Ok(())
+    } else {
+        log_line!("[ERROR] PID {} ({:?}) is not on the kill allowlist", pid, name);
+        Err(ProcessError::NotAllowed {
+            pid: pid.to_string(),
+            name: name.map(str::to_string),
+        })
+    }
+}
+
+// Check that the process currently at `pid` is the one the caller meant to kill.
+// Names are compared by executable basename and a prefix match is accepted either way,
+// since lsof (9 chars) and Linux comm (15 chars) both truncate. If the process is gone
+// there is nothing to protect, so the kill goes ahead and reports that itself. An expected
+// name carrying U+FFFD came from lossily decoded output and is never matched.
+pub fn verify_expected_name(pid: &str, expected: &str, current: Option<&str>) -> Result<(), ProcessError> {
+    let Some(current) = current else {
+        return Ok(());
+    };
+    if expected.contains(char::REPLACEMENT_CHARACTER) {
+        log_line!("[ERROR] Expected name '{}' for PID {} was decoded lossily", expected, pid);
+        return Err(ProcessError::UndecodableName { pid: pid.to_string(), name: expected.to_string() });
+    }
+    
+    if same_process_name(expected, current) {
+        return Ok(());
+    }
+    
+    log_line!("[ERROR] PID {} was reused: expected '{}', found '{}'", pid, expected, current);
+    Err(ProcessError::PidReused {
+        pid: pid.to_string(),
+        expected: expected.to_string(),
+        actual: current.to_string(),
+    })
+}
+
+// Whether two names refer to the same executable: basenames are compared and a prefix
+// match is accepted either way, since lsof and ps truncate names differently
+pub fn same_process_name(a: &str, b: &str) -> bool {
+    let basename = |name: &str| normalize_process_name(process_basename(name));
+    let (a, b) = (basename(a), basename(b));
+    a.starts_with(&b) || b.starts_with(&a)
+}
+
+// Validate a PID before it is handed to `kill`. Only plain positive decimal numbers are
+// accepted: PID 0 would signal our own process group and negative values (`-1`, `-<pgid>`)
+// use kill's group syntax, which could hit far more than the intended process.
+pub fn validate_kill_pid(pid: &str) -> Result<u32, ProcessError> {
+    if pid.is_empty() || !pid.bytes().all(|b| b.is_ascii_digit()) {
+        log_line!("[ERROR] Invalid PID format '{}'", pid);
+        return Err(ProcessError::InvalidPid(format!("Invalid PID format: {}", pid)));
+    }
+    
+    match pid.parse::<u32>() {
+        Ok(0) => {
+            log_line!("[ERROR] Refusing to signal PID 0 (the caller's process group)");
+            Err(ProcessError::InvalidPid(
+                "Invalid PID 0: it refers to the whole process group and cannot be killed".to_string()
+            ))
+        }
+        Ok(p) => Ok(p),
+        Err(e) => {
+            log_line!("[ERROR] Invalid PID format '{}': {}", pid, e);
+            Err(ProcessError::InvalidPid(format!("Invalid PID format: {}", pid)))
+        }
+    }
+}
+
+// Check whether a process exists using `kill -0`, which delivers no signal.
+// A permission error still means the process exists, it just belongs to another user.
+pub fn is_process_alive(pid: &str) -> bool {
+    process_alive(&ToolPaths::default(), pid)
+}
+
+fn process_alive(tools: &ToolPaths, pid: &str) -> bool {
+    if validate_kill_pid(pid).is_err() {
+        return false;
+    }
+    
+    match tools.kill().args(["-0", pid]).audited_output() {
+        Ok(output) => {
+            output.status.success()
+                || String::from_utf8_lossy(&output.stderr).contains("not permitted")
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute kill -0 for PID {}: {}", pid, e);
+            false
+        }
+    }
+}
+
+// Check many PIDs at once with a single `ps -p p1,p2,...` instead of one `kill -0` each.
+// Results follow the input order; invalid PIDs are reported as not alive.
+#[tauri::command]
+fn which_alive(state: State<'_, AppState>, pids: Vec<String>) -> Vec<(String, bool)> {
+    log_line!("[INFO] Checking liveness of {} PID(s)", pids.len());
+    
+    let valid: Vec<&str> = pids
+        .iter()
+        .map(|pid| pid.trim())
+        .filter(|pid| validate_kill_pid(pid).is_ok())
+        .collect();
+    if valid.is_empty() {
+        return parse_alive_pids("", &pids);
+    }
+    
+    let pid_list = valid.join(",");
+    let ps_args = vec!["-p", &pid_list, "-o", "pid="];
+    log_line!("[DEBUG] Executing command: ps {}", ps_args.join(" "));
+    
+    // ps exits with a failure status when some of the PIDs don't exist, which is expected
+    match state.tools().ps().args(&ps_args).audited_output() {
+        Ok(output) => parse_alive_pids(&String::from_utf8_lossy(&output.stdout), &pids),
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute ps command: {}", e);
+            parse_alive_pids("", &pids)
+        }
+    }
+}
+
+// Match `ps -o pid=` output against the requested PIDs, in request order
+pub fn parse_alive_pids(output: &str, requested: &[String]) -> Vec<(String, bool)> {
+    let alive: HashSet<u32> = output
+        .split_whitespace()
+        .filter_map(|pid| pid.parse::<u32>().ok())
+        .collect();
+    
+    requested
+        .iter()
+        .map(|pid| {
+            let is_alive = validate_kill_pid(pid.trim()).is_ok_and(|pid| alive.contains(&pid));
+            (pid.clone(), is_alive)
+        })
+        .collect()
+}
+
+// Terminate a process gracefully, escalating to SIGKILL if it outlives the grace period
+#[tauri::command]
+fn smart_kill(state: State<'_, AppState>, pid: String, confirmed: Option<bool>) -> Result<SmartKillStatus, ProcessError> {
+    log_line!("[INFO] Smart kill for PID: {}", pid);
+    
+    validate_kill_pid(&pid)?;
+    let tools = state.tools();
+    // Asked once up front, so the SIGTERM and a later SIGKILL don't each need it
+    confirm_kill(&tools, &pid, confirmed.unwrap_or(false))?;
+    
+    let status = smart_kill_with(
+        &pid,
+        Duration::from_millis(SMART_KILL_GRACE_MS),
+        |target, force| kill_and_record(&state, target.to_string(), force, None, None, true),
+        |target| process_alive(&tools, target),
+        std::thread::sleep,
+    );
+    
+    log_line!("[INFO] Smart kill for PID {} finished: {:?}", pid, status);
+    Ok(status)
+}
+
+// The smart kill sequence with the killer, liveness check and sleep injected so each
+// branch can be tested without real processes.
+pub fn smart_kill_with<K, A, S>(pid: &str, grace: Duration, killer: K, is_alive: A, sleep: S) -> SmartKillStatus
+where
+    K: Fn(&str, bool) -> Result<String, ProcessError>,
+    A: Fn(&str) -> bool,
+    S: FnMut(Duration),
+{
+    kill_async_with(pid, grace, || false, killer, is_alive, sleep)
+}
+
+// Start a smart kill in the background and return at once. The outcome arrives as a
+// `kill-complete` event; `cancel_kill_async` stops the task before it escalates.
+#[tauri::command]
+fn kill_async(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    pid: String,
+    grace_ms: Option<u64>,
+    confirmed: Option<bool>,
+) -> Result<(), ProcessError> {
+    validate_kill_pid(&pid)?;
+    // Asked before the task starts, so the caller gets ConfirmationRequired back directly
+    confirm_kill(&state.tools(), &pid, confirmed.unwrap_or(false))?;
+    let grace = Duration::from_millis(grace_ms.unwrap_or(SMART_KILL_GRACE_MS));
+    log_line!("[INFO] Background kill for PID {} with a {:?} grace period", pid, grace);
+    
+    let handle = WatchHandle::default();
+    match state.background_kills.lock() {
+        Ok(mut kills) => {
+            if kills.contains_key(&pid) {
+                return Err(ProcessError::command_failed(format!("A background kill of PID {} is already running", pid)));
+            }
+            kills.insert(pid.clone(), handle.clone());
+        }
+        Err(e) => {
+            log_line!("[ERROR] Background kill registry lock poisoned: {}", e);
+            return Err(ProcessError::command_failed("Background kill registry is unavailable"));
+        }
+    }
+    
+    std::thread::spawn(move || {
+        let state = app.state::<AppState>();
+        let tools = state.tools();
+        let status = kill_async_with(
+            &pid,
+            grace,
+            || handle.is_cancelled(),
+            |target, force| kill_and_record(&state, target.to_string(), force, None, None, true),
+            |target| process_alive(&tools, target),
+            std::thread::sleep,
+        );
+        
+        log_line!("[INFO] Background kill for PID {} finished: {:?}", pid, status);
+        if let Ok(mut kills) = state.background_kills.lock() {
+            kills.remove(&pid);
+        }
+        if let Err(e) = app.emit(KILL_COMPLETE_EVENT, KillCompleteEvent { pid, status }) {
+            log_line!("[WARN] Failed to emit {} event: {}", KILL_COMPLETE_EVENT, e);
+        }
+    });
+    
+    Ok(())
+}
+
+// Cancel a running background kill so it won't escalate to SIGKILL. Returns false if no
+// background kill of the PID was running.
+#[tauri::command]
+fn cancel_kill_async(state: State<'_, AppState>, pid: String) -> Result<bool, ProcessError> {
+    match state.background_kills.lock() {
+        Ok(kills) => Ok(kills.get(pid.trim()).map(WatchHandle::cancel).is_some()),
+        Err(e) => {
+            log_line!("[ERROR] Background kill registry lock poisoned: {}", e);
+            Err(ProcessError::command_failed("Background kill registry is unavailable"))
+        }
+    }
+}
+
+// The smart kill sequence, checking `is_cancelled` while waiting out the grace period:
+// once cancelled it stops waiting and never sends SIGKILL.
+pub fn kill_async_with<C, K, A, S>(pid: &str, grace: Duration, is_cancelled: C, killer: K, is_alive: A, mut sleep: S) -> SmartKillStatus
+where
+    C: Fn() -> bool,
+    K: Fn(&str, bool) -> Result<String, ProcessError>,
+    A: Fn(&str) -> bool,
+    S: FnMut(Duration),
+{
+    if !is_alive(pid) {
+        return SmartKillStatus::AlreadyDead;
+    }
+    
+    if let Err(e) = killer(pid, false) {
+        // The process may have exited between the check and the signal
+        return if is_alive(pid) {
+            SmartKillStatus::Failed(e.to_string())
+        } else {
+            SmartKillStatus::AlreadyDead
+        };
+    }
+    
+    // A cancellation ends the wait the same way an exit does
+    let exited = poll_for_exit(pid, grace, |target| !is_cancelled() && is_alive(target), &mut sleep);
+    if is_cancelled() {
+        log_line!("[INFO] Kill of PID {} cancelled before escalation", pid);
+        return SmartKillStatus::Cancelled;
+    }
+    if exited {
+        return SmartKillStatus::TerminatedGracefully;
+    }
+    
+    let poll = Duration::from_millis(SMART_KILL_POLL_MS);
+    log_line!("[WARN] PID {} still running after {:?}, escalating to SIGKILL", pid, grace);
+    match killer(pid, true) {
+        Ok(_) => {
+            sleep(poll);
+            if is_alive(pid) {
+                SmartKillStatus::Failed(format!(
+                    "Process {} is still running after SIGKILL; it may be stuck in an uninterruptible syscall (D state) and will exit once its I/O completes",
+                    pid
+                ))
+            } else {
+                SmartKillStatus::Escalated
+            }
+        }
+        // Exited on its own right as the grace period ran out
+        Err(_) if !is_alive(pid) => SmartKillStatus::TerminatedGracefully,
+        Err(e) => SmartKillStatus::Failed(e.to_string()),
+    }
+}
+
+pub const WAIT_FOR_EXIT_MAX_MS: u64 = 60_000;
+
+// Block until a process has exited, e.g. after a graceful kill, checking every
+// SMART_KILL_POLL_MS for up to `timeout_ms` (capped at WAIT_FOR_EXIT_MAX_MS). Returns
+// whether it exited in time; a PID that is already gone returns true immediately.
+#[tauri::command]
+fn wait_for_exit(state: State<'_, AppState>, pid: String, timeout_ms: u64) -> Result<bool, ProcessError> {
+    validate_kill_pid(&pid)?;
+    let tools = state.tools();
+    let timeout = Duration::from_millis(timeout_ms.min(WAIT_FOR_EXIT_MAX_MS));
+    log_line!("[INFO] Waiting up to {:?} for PID {} to exit", timeout, pid);
+    
+    let exited = !process_alive(&tools, &pid)
+        || poll_for_exit(&pid, timeout, |target| process_alive(&tools, target), std::thread::sleep);
+    log_line!("[INFO] PID {} {}", pid, if exited { "has exited" } else { "is still running" });
+    Ok(exited)
+}
+
+// Poll until the process is gone or `timeout` has passed. Returns whether it exited.
+pub fn poll_for_exit<A, S>(pid: &str, timeout: Duration, is_alive: A, mut sleep: S) -> bool
+where
+    A: Fn(&str) -> bool,
+    S: FnMut(Duration),
+{
+    let poll = Duration::from_millis(SMART_KILL_POLL_MS);
+    let mut waited = Duration::ZERO;
+    while waited < timeout {
+        sleep(poll);
+        waited += poll;
+        if !is_alive(pid) {
+            return true;
+        }
+    }
+    false
+}
+
+// A process relaunched by `restart_process`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartResult {
+    pub old_pid: String,
+    pub new_pid: u32,
+    pub argv: Vec<String>, // Command line the new process was started with
+}
+
+// Restart a process for dev workflows: capture its command line, SIGTERM it, wait for it
+// to exit and launch the same command again, detached from this app. A process that
+// doesn't exit within the grace period is left alone rather than run twice. Only the
+// command line (and on Linux the working directory) carries over, not the environment.
+#[tauri::command]
+fn restart_process(state: State<'_, AppState>, pid: String, confirmed: Option<bool>) -> Result<RestartResult, ProcessError> {
+    log_line!("[INFO] Restarting process PID: {}", pid);
+    
+    validate_kill_pid(&pid)?;
+    if let Some(reason) = protected_pid_reason(&pid, std::process::id()) {
+        return Err(ProcessError::command_failed(format!("Refusing to restart PID {}: {}", pid, reason)));
+    }
+    
+    let tools = state.tools();
+    let detail = fetch_process_detail(&tools, pid.clone()).map_err(ProcessError::command_failed)?;
+    let argv = split_command_line(detail.command.as_deref().unwrap_or(""));
+    if argv.is_empty() {
+        return Err(ProcessError::command_failed(format!("Could not read the command line of PID {}", pid)));
+    }
+    // A command line that wasn't valid UTF-8 can't be relaunched faithfully
+    if argv.iter().any(|arg| arg.contains(char::REPLACEMENT_CHARACTER)) {
+        return Err(ProcessError::command_failed(format!("The command line of PID {} is not valid UTF-8", pid)));
+    }
+    let cwd = read_cwd(&pid);
+    
+    // The name guards against the PID having been reused since the detail was read
+    kill_and_record(&state, pid.clone(), false, Some(&detail.name), None, confirmed.unwrap_or(false))?;
+    
+    let grace = Duration::from_millis(SMART_KILL_GRACE_MS);
+    if !poll_for_exit(&pid, grace, |target| process_alive(&tools, target), std::thread::sleep) {
+        log_line!("[WARN] PID {} still running after {:?}, not relaunching", pid, grace);
+        return Err(ProcessError::command_failed(format!(
+            "Process {} did not exit within {} ms of SIGTERM, so it was not relaunched", pid, SMART_KILL_GRACE_MS
+        )));
+    }
+    
+    log_line!("[DEBUG] Relaunching: {:?} (cwd: {:?})", argv, cwd);
+    let mut command = Command::new(&argv[0]);
+    command
+        .args(&argv[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(cwd) = &cwd {
+        command.current_dir(cwd);
+    }
+    // Own process group, so the relaunched process doesn't get our terminal signals
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    
+    let spawned = command.spawn();
+    record_command(&command, spawned.as_ref().map(|_| None));
+    let mut child = spawned.map_err(|e| {
+        log_line!("[ERROR] Failed to relaunch {}: {}", argv[0], e);
+        ProcessError::command_failed(format!("Process {} was stopped but could not be relaunched: {}", pid, e))
+    })?;
+    let new_pid = child.id();
+    
+    // Reap the child when it eventually exits so it doesn't linger as a zombie
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    
+    log_line!("[INFO] Restarted PID {} as PID {}", pid, new_pid);
+    Ok(RestartResult { old_pid: pid, new_pid, argv })
+}
+
+// Split a captured command line into argv. ps prints argv joined by spaces, so quoting is
+// honoured where present ('...', "...", backslash escapes) and otherwise whitespace splits.
+// An argument that itself contained spaces can't be recovered without quotes.
+pub fn split_command_line(command: &str) -> Vec<String> {
+    let mut argv = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars();
+    
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+                in_arg = true;
+            }
+            (Some(_), c) => current.push(c),
+            (None, '\'') | (None, '"') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    argv.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    
+    if in_arg {
+        argv.push(current);
+    }
+    argv
+}
+
+// Working directory of a process, so a relaunch resolves relative paths the same way
+#[cfg(target_os = "linux")]
+fn read_cwd(pid: &str) -> Option<std::path::PathBuf> {
+    std::fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_cwd(_pid: &str) -> Option<std::path::PathBuf> {
+    None
+}
+
+// Kill a process together with all of its descendants
+#[tauri::command]
+fn kill_process_tree(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    pid: String,
+    force: bool,
+    confirmed: Option<bool>,
+) -> Result<Vec<KillResult>, ProcessError> {
+    log_line!("[INFO] Attempting to kill process tree rooted at PID: {} (force: {})", pid, force);
+    
+    let root_pid = validate_kill_pid(&pid)?;
+    let own_pid = std::process::id();
+    if let Some(reason) = protected_pid_reason(&pid, own_pid) {
+        return Err(ProcessError::command_failed(format!("Refusing to kill process tree rooted at PID {}: {}", pid, reason)));
+    }
+    
+    // Use ps to get the parent of every process so we can rebuild the tree
+    let ps_args = vec!["-A", "-o", "pid=,ppid="];
+    log_line!("[DEBUG] Executing command: ps {}", ps_args.join(" "));
+    
+    let output = state.tools().ps()
+        .args(&ps_args)
+        .audited_output()
+        .map_err(|e| {
+            log_line!("[ERROR] Failed to execute ps command: {}", e);
+            ProcessError::command_failed(format!("Failed to execute ps command: {}", e))
+        })?;
+    
+    if !output.status.success() {
+        let error_str = String::from_utf8_lossy(&output.stderr);
+        log_line!("[ERROR] ps command failed with status: {}, stderr: {}", output.status, error_str);
+        return Err(ProcessError::from_output(format!("Failed to execute ps command: {}", error_str.trim()), &output));
+    }
+    
+    let output_str = String::from_utf8_lossy(&output.stdout);
+    let entries = parse_ppid_table(&output_str);
+    // A tree above this application would take it down before the kill finishes
+    if ancestor_pids(own_pid, &entries).contains(&root_pid) {
+        return Err(ProcessError::command_failed(format!(
+            "Refusing to kill process tree rooted at PID {}: it contains this application", pid
+        )));
+    }
+    
+    let children = build_children_map(&entries);
+    let kill_order: Vec<u32> = tree_kill_order(root_pid, &children)
+        .into_iter()
+        .filter(|target| match protected_pid_reason(&target.to_string(), own_pid) {
+            Some(reason) => {
+                log_line!("[WARN] Not killing PID {}: {}", target, reason);
+                false
+            }
+            None => true,
+        })
+        .collect();
+    log_line!("[INFO] Process tree for PID {} contains {} process(es), kill order: {:?}", 
+             root_pid, kill_order.len(), kill_order);
+    
+    let targets: Vec<String> = kill_order.iter().map(|p| p.to_string()).collect();
+    let confirmed = confirmed.unwrap_or(false);
+    let killer = |target: String, force: bool| kill_and_record(&state, target, force, None, None, confirmed);
+    Ok(kill_pids_with_progress(&targets, force, killer, |progress| {
+        emit_kill_progress(&app, progress)
+    }))
+}
+
+// Free every port in a range (e.g. `3000-3010`, or any pattern `check_port` accepts) by
+// killing its listeners. With `dry_run` nothing is signalled, the summary shows what would be.
+#[tauri::command]
+fn kill_port_range(
+    state: State<'_, AppState>,
+    range: String,
+    force: bool,
+    dry_run: bool,
+    confirmed: Option<bool>,
+) -> Result<Vec<PortKillOutcome>, ProcessError> {
+    log_line!("[INFO] Killing listeners on port range {} (force: {}, dry run: {})", range, force, dry_run);
+    
+    let ports = parse_port_pattern(&range)?;
+    let listeners = find_listening_ports(&state.tools())?;
+    
+    let confirmed = confirmed.unwrap_or(false);
+    let outcomes = kill_port_range_with(ports, listeners, force, dry_run, std::process::id(), |pid, force| {
+        kill_and_record(&state, pid, force, None, None, confirmed)
+    });
+    
+    let killed = outcomes.iter().filter(|o| o.success && !o.skipped).count();
+    log_line!("[INFO] Port range {}: {} listener(s), {} killed", range, outcomes.len(), killed);
+    Ok(outcomes)
+}
+
+// Kill every process matching all criteria of `query`. With `dry_run` nothing is signalled
+// and the matches are returned for confirmation.
+#[tauri::command]
+fn kill_matching(
+    state: State<'_, AppState>,
+    query: KillQuery,
+    force: bool,
+    dry_run: bool,
+    confirmed: Option<bool>,
+) -> Result<KillQueryResult, ProcessError> {
+    log_line!("[INFO] Killing processes matching {:?} (force: {}, dry run: {})", query, force, dry_run);
+    
+    let tools = state.tools();
+    let ps_args = vec!["-A", "-o", "pid=,etime=,user=,comm="];
+    log_line!("[DEBUG] Executing command: ps {}", ps_args.join(" "));
+    
+    let output = tools.ps().args(&ps_args).audited_output().map_err(|e| {
+        log_line!("[ERROR] Failed to execute ps command: {}", e);
+        ProcessError::command_failed(format!("Failed to execute ps command: {}", e))
+    })?;
+    if !output.status.success() {
+        let error_str = String::from_utf8_lossy(&output.stderr);
+        log_line!("[ERROR] ps command failed with status: {}, stderr: {}", output.status, error_str);
+        return Err(ProcessError::from_output(format!("Failed to execute ps command: {}", error_str.trim()), &output));
+    }
+    let candidates = parse_kill_candidates(&String::from_utf8_lossy(&output.stdout));
+    
+    // lsof is only needed to answer a port criterion
+    let listeners = match query.ports {
+        Some(_) => find_listening_ports(&tools)?,
+        None => vec![],
+    };
+    
+    let matches = resolve_kill_query(&query, candidates, &listeners, std::process::id())?;
+    log_line!("[INFO] Kill query matched {} process(es)", matches.len());
+    
+    let results = if dry_run {
+        vec![]
+    } else {
+        let confirmed = confirmed.unwrap_or(false);
+        matches
+            .iter()
+            .map(|candidate| match kill_and_record(&state, candidate.pid.clone(), force, Some(&candidate.name), None, confirmed) {
+                Ok(message) => KillResult { pid: candidate.pid.clone(), success: true, message },
+                Err(e) => KillResult { pid: candidate.pid.clone(), success: false, message: e.to_string() },
+            })
+            .collect()
+    };
+    
+    Ok(KillQueryResult { matches, results, schema_version: SCHEMA_VERSION })
+}
+
+// Kill every process owned by `user`, or by the user this app runs as for "me". Nothing is
+// signalled without `confirmed`: the first call returns the matches for the UI to show,
+// and a confirmed call that isn't a dry run kills them. Critical PIDs and this
+// application are never included.
+#[tauri::command]
+fn kill_user_processes(
+    state: State<'_, AppState>,
+    user: String,
+    force: bool,
+    dry_run: bool,
+    confirmed: Option<bool>,
+) -> Result<KillQueryResult, ProcessError> {
+    let confirmed = confirmed.unwrap_or(false);
+    log_line!("[INFO] Killing processes of user {} (force: {}, dry run: {}, confirmed: {})", user, force, dry_run, confirmed);
+    
+    let tools = state.tools();
+    let own_pid = std::process::id();
+    let own_uid = get_process_owner(&tools, &own_pid.to_string()).map(|(uid, _)| uid);
+    let target = resolve_user_target(&user, own_uid)?;
+    
+    let ps_args = vec!["-u", target.as_str(), "-o", "pid=,comm="];
+    log_line!("[DEBUG] Executing command: ps {}", ps_args.join(" "));
+    
+    let output = tools.ps().args(&ps_args).audited_output().map_err(|e| {
+        log_line!("[ERROR] Failed to execute ps command: {}", e);
+        ProcessError::command_failed(format!("Failed to execute ps command: {}", e))
+    })?;
+    // ps also fails when the user simply has no processes, which is only an empty result
+    if !output.status.success() && !output.stderr.is_empty() {
+        let error_str = String::from_utf8_lossy(&output.stderr);
+        log_line!("[ERROR] ps command failed with status: {}, stderr: {}", output.status, error_str);
+        return Err(ProcessError::from_output(format!("Failed to execute ps command: {}", error_str.trim()), &output));
+    }
+    
+    let matches = parse_user_processes(&String::from_utf8_lossy(&output.stdout), &target, own_pid);
+    log_line!("[INFO] User {} has {} killable process(es)", target, matches.len());
+    
+    let results = if dry_run || !confirmed {
+        if !dry_run {
+            log_line!("[WARN] Not killing {} process(es) of user {} without confirmation", matches.len(), target);
+        }
+        vec![]
+    } else {
+        matches
+            .iter()
+            .map(|candidate| match kill_and_record(&state, candidate.pid.clone(), force, Some(&candidate.name), None, true) {
+                Ok(message) => KillResult { pid: candidate.pid.clone(), success: true, message },
+                Err(e) => KillResult { pid: candidate.pid.clone(), success: false, message: e.to_string() },
+            })
+            .collect()
+    };
+    
+    Ok(KillQueryResult { matches, results, schema_version: SCHEMA_VERSION })
+}
+
+// The user to pass to `ps -u`: "me" is our own uid, anything else must look like a user
+// name or uid, so it can't be taken for a ps option
+pub fn resolve_user_target(user: &str, own_uid: Option<u32>) -> Result<String, ProcessError> {
+    let user = user.trim();
+    if user.eq_ignore_ascii_case("me") {
+        return own_uid
+            .map(|uid| uid.to_string())
+            .ok_or_else(|| ProcessError::command_failed("Could not determine the current user"));
+    }
+    
+    let valid = !user.is_empty()
+        && !user.starts_with('-')
+        && user.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '$'));
+    if !valid {
+        return Err(ProcessError::InvalidUser(user.to_string()));
+    }
+    Ok(user.to_string())
+}
+
+// Parse `ps -u <user> -o pid=,comm=` rows, leaving out critical PIDs and our own process
+pub fn parse_user_processes(output: &str, user: &str, own_pid: u32) -> Vec<KillCandidate> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (fields, comm) = split_leading_fields(line, 1)?;
+            if comm.is_empty() || fields[0].parse::<u32>().is_err() {
+                return None;
+            }
+            if let Some(reason) = protected_pid_reason(fields[0], own_pid) {
+                log_line!("[DEBUG] Skipping PID {} of user {}: {}", fields[0], user, reason);
+                return None;
+            }
+            Some(KillCandidate {
+                pid: fields[0].to_string(),
+                name: normalize_process_name(process_basename(comm)),
+                user: user.to_string(),
+                age_seconds: None,
+                ports: vec![],
+            })
+        })
+        .collect()
+}
+
+// Parse `ps -o pid=,etime=,user=,comm=` rows into kill candidates. The user name is taken
+// to be a single word so that the command name, last, may contain spaces.
+pub fn parse_kill_candidates(output: &str) -> Vec<KillCandidate> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (fields, comm) = split_leading_fields(line, 3)?;
+            if comm.is_empty() || fields[0].parse::<u32>().is_err() {
+                return None;
+            }
+            Some(KillCandidate {
+                pid: fields[0].to_string(),
+                name: normalize_process_name(process_basename(comm)),
+                user: fields[2].to_string(),
+                age_seconds: parse_etime(fields[1]),
+                ports: vec![],
+            })
+        })
+        .collect()
+}
+
+// Narrow the candidates to those meeting every criterion of the query. Our own process
+// and critical PIDs never match; neither does a process whose age is unknown when a
+// minimum age is asked for.
+pub fn resolve_kill_query(
+    query: &KillQuery,
+    candidates: Vec<KillCandidate>,
+    listeners: &[ProcessInfo],
+    own_pid: u32,
+) -> Result<Vec<KillCandidate>, ProcessError> {
+    let given = |value: &Option<String>| value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+    let (name, user, ports) = (given(&query.name), given(&query.user), given(&query.ports));
+    if name.is_none() && user.is_none() && ports.is_none() && query.min_age_seconds.is_none() {
+        return Err(ProcessError::InvalidPattern {
+            pattern: String::new(),
+            reason: "a kill query needs at least one criterion".to_string(),
+        });
+    }
+    
+    let globs = match &name {
+        Some(name) => compile_globs(std::slice::from_ref(name))?,
+        None => vec![],
+    };
+    let range = ports.as_deref().map(parse_port_pattern).transpose()?;
+    
+    let matches = candidates
+        .into_iter()
+        .filter(|candidate| protected_pid_reason(&candidate.pid, own_pid).is_none())
+        .filter(|candidate| globs.is_empty() || glob_match_terms(&candidate.name, &globs, TermCombinator::Any))
+        .filter(|candidate| user.as_deref().is_none_or(|user| ps_user_matches(&candidate.user, user)))
+        .filter(|candidate| query.min_age_seconds.is_none_or(|min| candidate.age_seconds.is_some_and(|age| age >= min)))
+        .filter_map(|mut candidate| {
+            let Some(range) = &range else {
+                return Some(candidate);
+            };
+            for listener in listeners.iter().filter(|l| l.pid == candidate.pid) {
+                if let Ok(port) = listener.port.parse::<u16>() {
+                    if range.contains(&port) && !candidate.ports.contains(&port) {
+                        candidate.ports.push(port);
+                    }
+                }
+            }
+            (!candidate.ports.is_empty()).then_some(candidate)
+        })
+        .collect();
+    
+    Ok(matches)
+}
+
+// Whether a ps user column names `user`, allowing for a name ps truncated with `+`
+fn ps_user_matches(column: &str, user: &str) -> bool {
+    match parse_ps_user(column) {
+        Some((name, true)) => user.starts_with(&name),
+        Some((name, false)) => name == user,
+        None => false,
+    }
+}
+
+// Kill the listeners whose port falls in `ports`, in port order. A PID listening on several
+// ports is signalled once and its result repeated for the other ports. Critical PIDs and
+// our own process are never signalled.
+pub fn kill_port_range_with<K>(
+    ports: RangeInclusive<u16>,
+    listeners: Vec<ProcessInfo>,
+    force: bool,
+    dry_run: bool,
+    own_pid: u32,
+    killer: K,
+) -> Vec<PortKillOutcome>
+where
+    K: Fn(String, bool) -> Result<String, ProcessError>,
+{
+    let mut targets: Vec<(u16, ProcessInfo)> = listeners
+        .into_iter()
+        .filter_map(|listener| {
+            let port = listener.port.parse::<u16>().ok()?;
+            ports.contains(&port).then_some((port, listener))
+        })
+        .collect();
+    targets.sort_by_key(|(port, listener)| (*port, listener.pid.parse::<u32>().unwrap_or(u32::MAX)));
+    
+    let mut handled: HashMap<String, (bool, String)> = HashMap::new();
+    let mut outcomes = Vec::with_capacity(targets.len());
+    
+    for (port, listener) in targets {
+        let (skipped, success, message) = if let Some(reason) = protected_pid_reason(&listener.pid, own_pid) {
+            (true, false, format!("Skipped PID {}: {}", listener.pid, reason))
+        } else if dry_run {
+            (true, true, format!("Would {} PID {} ({})", if force { "force kill" } else { "terminate" }, listener.pid, listener.name))
+        } else {
+            let (success, message) = handled
+                .entry(listener.pid.clone())
+                .or_insert_with(|| match killer(listener.pid.clone(), force) {
+                    Ok(message) => (true, message),
+                    Err(e) => (false, e.to_string()),
+                })
+                .clone();
+            (false, success, message)
+        };
+        
+        outcomes.push(PortKillOutcome {
+            port,
+            pid: listener.pid,
+            name: listener.name,
+            skipped,
+            success,
+            message,
+        });
+    }
+    
+    outcomes
+}
+
+// Kill only the processes running the executable at `exe_path`, e.g. `/usr/local/bin/node`
+// but not another `node` install. Candidates are found by name, then narrowed to those
+// whose resolved executable path is exactly `exe_path`. Nothing is signalled without
+// `confirmed`: the first call returns the matches for the UI to show.
+#[tauri::command]
+fn kill_by_path(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    exe_path: String,
+    force: bool,
+    cleanup_files: Option<Vec<String>>,
+    confirmed: Option<bool>,
+) -> Result<PathKillResult, ProcessError> {
+    log_line!("[INFO] Attempting to kill processes running {} (force: {})", exe_path, force);
+    
+    let exe_path = exe_path.trim().to_string();
+    let basename = exe_path.rsplit('/').next().unwrap_or("");
+    if !exe_path.starts_with('/') || basename.is_empty() {
+        return Err(ProcessError::InvalidPath(exe_path));
+    }
+    
+    // macOS reports comm as the full path, so match the basename as a suffix
+    let tools = state.tools();
+    let options = SearchOptions {
+        match_mode: MatchMode::Suffix,
+        ..SearchOptions::for_term(basename)
+    };
+    let search = search_ps(&tools, &options, basename);
+    if let Some(error) = search.error {
+        return Err(ProcessError::command_failed(error));
+    }
+    
+    let own_pid = std::process::id();
+    let matched: Vec<ProcessInfo> = filter_by_exe_path(search.processes, &exe_path, |pid| get_exe_path(&tools, pid))
+        .into_iter()
+        .filter(|process| match protected_pid_reason(&process.pid, own_pid) {
+            Some(reason) => {
+                log_line!("[WARN] Not killing PID {}: {}", process.pid, reason);
+                false
+            }
+            None => true,
+        })
+        .collect();
+    
+    if !confirmed.unwrap_or(false) {
+        log_line!("[INFO] {} process(es) run {}, waiting for confirmation", matched.len(), exe_path);
+        return Ok(PathKillResult { exe_path, matched, results: vec![], cleaned_files: vec![] });
+    }
+    
+    // The user confirmed this exact set, root-owned members included
+    let pids: Vec<String> = matched.iter().map(|p| p.pid.clone()).collect();
+    let killer = |target: String, force: bool| kill_and_record(&state, target, force, None, None, true);
+    let results = kill_pids_with_progress(&pids, force, killer, |progress| {
+        emit_kill_progress(&app, progress)
+    });
+    
+    log_line!("[INFO] Killed {} of {} process(es) running {}", 
+             results.iter().filter(|r| r.success).count(), matched.len(), exe_path);
+    let cleaned_files = clean_up_companion_files(&tools, &results, &cleanup_files.unwrap_or_default());
+    Ok(PathKillResult { exe_path, matched, results, cleaned_files })
+}
+
+// Keep the candidates whose executable resolves to exactly `exe_path`. Linux marks the
+// link of a replaced or deleted binary with " (deleted)", which is ignored here.
+pub fn filter_by_exe_path<F>(candidates: Vec<ProcessInfo>, exe_path: &str, resolve: F) -> Vec<ProcessInfo>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    candidates
+        .into_iter()
+        .filter(|candidate| {
+            let resolved = resolve(&candidate.pid);
+            let path = resolved.as_deref().map(|path| path.strip_suffix(" (deleted)").unwrap_or(path));
+            log_line!("[DEBUG] PID {} runs {:?}", candidate.pid, path);
+            path == Some(exe_path)
+        })
+        .collect()
+}
+
+// Resolve the executable a PID is running from its /proc link
+#[cfg(target_os = "linux")]
+fn get_exe_path(_tools: &ToolPaths, pid: &str) -> Option<String> {
+    let path = std::fs::read_link(format!("/proc/{}/exe", pid)).ok()?;
+    Some(path.to_string_lossy().into_owned())
+}
+
+// Without /proc, the executable is the first `txt` file lsof lists for the PID
+#[cfg(not(target_os = "linux"))]
+fn get_exe_path(tools: &ToolPaths, pid: &str) -> Option<String> {
+    let output = tools.lsof().args(["-a", "-p", pid, "-d", "txt", "-Fn"]).audited_output().ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix('n'))
+        .map(str::to_string)
+}
+
+// Kill every process listening on a port, except the PIDs the caller wants to keep. Any
+// `cleanup_files` are removed once every killed process is confirmed gone, and the
+// outcome of each is returned alongside the kills.
+#[tauri::command]
+fn kill_by_port(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    port: String,
+    force: bool,
+    exclude_pids: Option<Vec<String>>,
+    cleanup_files: Option<Vec<String>>,
+    confirmed: Option<bool>,
+) -> Result<PortKillResult, ProcessError> {
+    log_line!("[INFO] Attempting to kill processes on port {} (force: {}, excluding: {:?})", 
+             port, force, exclude_pids);
+    
+    let port_num = u16::from_str(port.trim()).map_err(|_| ProcessError::InvalidPort(port.clone()))?;
+    let listeners = find_port_listeners(&state.tools(), port_num)?;
+    let targets = exclude_listeners(listeners, &exclude_pids.unwrap_or_default())?;
+    
+    // Protected listeners are reported as failed results rather than signalled
+    let own_pid = std::process::id();
+    let mut pids = Vec::new();
+    let mut skipped = Vec::new();
+    for target in targets {
+        match protected_pid_reason(&target.pid, own_pid) {
+            Some(reason) => {
+                log_line!("[WARN] Not killing PID {}: {}", target.pid, reason);
+                skipped.push(KillResult {
+                    message: format!("Skipped PID {}: {}", target.pid, reason),
+                    pid: target.pid,
+                    success: false,
+                });
+            }
+            None => pids.push(target.pid),
+        }
+    }
+    log_line!("[INFO] Killing {} process(es) on port {}: {:?}", pids.len(), port_num, pids);
+    
+    let confirmed = confirmed.unwrap_or(false);
+    let killer = |target: String, force: bool| kill_and_record(&state, target, force, None, None, confirmed);
+    let mut results = kill_pids_with_progress(&pids, force, killer, |progress| {
+        emit_kill_progress(&app, progress)
+    });
+    results.extend(skipped);
+    
+    let cleaned_files = clean_up_companion_files(&state.tools(), &results, &cleanup_files.unwrap_or_default());
+    Ok(PortKillResult { port: port_num, results, cleaned_files })
+}
+
+// Remove the caller's companion files after a kill, waiting up to SMART_KILL_GRACE_MS for
+// each killed process to exit first
+fn clean_up_companion_files(tools: &ToolPaths, results: &[KillResult], files: &[String]) -> Vec<FileCleanup> {
+    if files.is_empty() {
+        return vec![];
+    }
+    
+    let timeout = Duration::from_millis(SMART_KILL_GRACE_MS);
+    let is_dead = |pid: &str| {
+        !process_alive(tools, pid) || poll_for_exit(pid, timeout, |target| process_alive(tools, target), std::thread::sleep)
+    };
+    let outcomes = remove_companion_files(results, files, is_dead, |path| std::fs::remove_file(path));
+    
+    for outcome in &outcomes {
+        match &outcome.reason {
+            None => log_line!("[INFO] Removed companion file {}", outcome.path),
+            Some(reason) => log_line!("[WARN] Left companion file {} in place: {}", outcome.path, reason),
+        }
+    }
+    outcomes
+}
+
+// Remove exactly the listed files, and only once every kill succeeded and `is_dead`
+// confirms each killed PID is gone; otherwise a still-running process could lose its
+// lock. Only absolute paths to plain files are removed, never directories or globs.
+pub fn remove_companion_files<D, R>(results: &[KillResult], files: &[String], is_dead: D, mut remove: R) -> Vec<FileCleanup>
+where
+    D: Fn(&str) -> bool,
+    R: FnMut(&Path) -> std::io::Result<()>,
+{
+    let blocker = if results.is_empty() {
+        Some("no process was killed".to_string())
+    } else if let Some(failed) = results.iter().find(|result| !result.success) {
+        Some(format!("PID {} was not killed", failed.pid))
+    } else {
+        results
+            .iter()
+            .find(|result| !is_dead(&result.pid))
+            .map(|alive| format!("PID {} is still running", alive.pid))
+    };
+    
+    files
+        .iter()
+        .map(|file| {
+            let path = file.trim();
+            let reason = if let Some(blocker) = &blocker {
+                Some(blocker.clone())
+            } else if !path.starts_with('/') {
+                Some("not an absolute path".to_string())
+            } else {
+                match remove(Path::new(path)) {
+                    Ok(()) => None,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Some("file not found".to_string()),
+                    Err(e) => Some(e.to_string()),
+                }
+            };
+            FileCleanup { path: path.to_string(), removed: reason.is_none(), reason }
+        })
+        .collect()
+}
+
+// Poll a port in the background, emitting a `port-watch` event whenever it becomes
+// occupied or free, or changes hands. Polling speeds up after a change and backs off
+// while the port is stable (see `AdaptiveInterval`). A new watch on the same port
+// replaces the old one.
+#[tauri::command]
+fn watch_port(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    port: String,
+    interval_ms: Option<u64>,
+    min_interval_ms: Option<u64>,
+    max_interval_ms: Option<u64>,
+) -> Result<(), ProcessError> {
+    let port_num = u16::from_str(port.trim()).map_err(|_| ProcessError::InvalidPort(port.clone()))?;
+    let (min, max) = watch_intervals(interval_ms, min_interval_ms, max_interval_ms);
+    log_line!("[INFO] Watching port {} every {:?} to {:?}", port_num, min, max);
+    
+    let handle = WatchHandle::default();
+    match state.watches.lock() {
+        Ok(mut watches) => {
+            if watches.add(port_num, handle.clone()) {
+                log_line!("[INFO] Replaced existing watch on port {}", port_num);
+            }
+        }
+        Err(e) => {
+            log_line!("[ERROR] Watch registry lock poisoned: {}", e);
+            return Err(ProcessError::command_failed("Watch registry is unavailable"));
+        }
+    }
+    
+    let tools = state.tools();
+    std::thread::spawn(move || {
+        let mut last: Option<Vec<ProcessInfo>> = None;
+        let mut interval = AdaptiveInterval::new(min, max);
+        
+        while !handle.is_cancelled() {
+            let mut changed = false;
+            match find_port_listeners(&tools, port_num) {
+                Ok(processes) => {
+                    if let Some(event) = port_watch_event(port_num, last.as_deref(), &processes) {
+                        changed = true;
+                        if let Err(e) = app.emit(PORT_WATCH_EVENT, event) {
+                            log_line!("[WARN] Failed to emit {} event: {}", PORT_WATCH_EVENT, e);
+                        }
+                    }
+                    last = Some(processes);
+                }
+                Err(e) => log_line!("[WARN] Watch on port {} failed to check: {}", port_num, e),
+            }
+            std::thread::sleep(interval.next(changed));
+        }
+        
+        log_line!("[INFO] Stopped watching port {}", port_num);
+    });
+    
+    Ok(())
+}
+
+// Stop watching a port. Returns false if the port wasn't being watched.
+#[tauri::command]
+fn stop_watch_port(state: State<'_, AppState>, port: String) -> Result<bool, ProcessError> {
+    let port_num = u16::from_str(port.trim()).map_err(|_| ProcessError::InvalidPort(port.clone()))?;
+    
+    match state.watches.lock() {
+        Ok(mut watches) => Ok(watches.remove(port_num)),
+        Err(e) => {
+            log_line!("[ERROR] Watch registry lock poisoned: {}", e);
+            Err(ProcessError::command_failed("Watch registry is unavailable"))
+        }
+    }
+}
+
+// Cancel every background watch so no poll thread outlives the app
+fn abort_watches(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let aborted = match state.watches.lock() {
+        Ok(mut watches) => watches.abort_all(),
+        Err(poisoned) => poisoned.into_inner().abort_all(),
+    };
+    if aborted > 0 {
+        log_line!("[INFO] Aborted {} port watch(es) on exit", aborted);
+    }
+}
+
+// Find the processes listening on a port, deduplicated by PID
+fn find_port_listeners(tools: &ToolPaths, port: u16) -> Result<Vec<ProcessInfo>, ProcessError> {
+    let port_arg = format!(":{}", port);
+    let lsof_args = vec!["-i", &port_arg, "-P", "-n", "-sTCP:LISTEN"];
+    log_line!("[DEBUG] Executing command: lsof {}", lsof_args.join(" "));
+    
+    let output = tools.lsof()
+        .args(&lsof_args)
+        .audited_output()
+        .map_err(|e| {
+            log_line!("[ERROR] Failed to execute lsof command: {}", e);
+            ProcessError::command_failed(format!("Failed to execute lsof: {}", e))
+        })?;
+    
+    // A non-zero status means nothing is listening on the port
+    if !output.status.success() {
+        log_line!("[DEBUG] lsof found no listeners on port {}", port);
+        return Ok(vec![]);
+    }
+    
+    let output_str = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_lsof_output(&output_str, &port.to_string()))
+}
+
+// Whether `pid` is among the processes listening on `port`, e.g. to confirm that a
+// restarted service got its port back rather than something else grabbing it
+#[tauri::command]
+fn verify_port_owner(state: State<'_, AppState>, pid: String, port: String) -> Result<bool, ProcessError> {
+    let pid = pid.trim().to_string();
+    validate_kill_pid(&pid)?;
+    let port_num = u16::from_str(port.trim()).map_err(|_| ProcessError::InvalidPort(port.clone()))?;
+    
+    let owns = is_port_owner(&find_port_listeners(&state.tools(), port_num)?, &pid);
+    log_line!("[INFO] PID {} {} port {}", pid, if owns { "owns" } else { "does not own" }, port_num);
+    Ok(owns)
+}
+
+// Whether `pid` is one of the listeners `parse_lsof_output` found on a port
+pub fn is_port_owner(listeners: &[ProcessInfo], pid: &str) -> bool {
+    listeners.iter().any(|listener| listener.pid == pid)
+}
+
+// Remove excluded PIDs from a listener list. Excluded PIDs must be well-formed, but ones
+// that aren't among the listeners are simply ignored.
+pub fn exclude_listeners(listeners: Vec<ProcessInfo>, exclude_pids: &[String]) -> Result<Vec<ProcessInfo>, ProcessError> {
+    let mut excluded = HashSet::new();
+    for pid in exclude_pids {
+        excluded.insert(validate_kill_pid(pid.trim())?.to_string());
+    }
+    
+    Ok(listeners
+        .into_iter()
+        .filter(|p| {
+            let keep = !excluded.contains(&p.pid);
+            if !keep {
+                log_line!("[INFO] Excluding PID {} ({}) from kill", p.pid, p.name);
+            }
+            keep
+        })
+        .collect())
+}
+
+// Kill a batch of processes by PID, reporting progress after each one
+#[tauri::command]
+fn kill_processes(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    pids: Vec<String>,
+    force: bool,
+    confirmed: Option<bool>,
+) -> Vec<KillResult> {
+    log_line!("[INFO] Attempting to kill {} process(es) (force: {})", pids.len(), force);
+    
+    let confirmed = confirmed.unwrap_or(false);
+    let killer = |target: String, force: bool| kill_and_record(&state, target, force, None, None, confirmed);
+    let results = kill_pids_with_progress(&pids, force, killer, |progress| {
+        emit_kill_progress(&app, progress)
+    });
+    
+    let succeeded = results.iter().filter(|r| r.success).count();
+    log_line!("[INFO] Batch kill finished: {} succeeded, {} failed", succeeded, results.len() - succeeded);
+    results
+}
+
+fn emit_kill_progress(app: &AppHandle, progress: KillProgress) {
+    if let Err(e) = app.emit(KILL_PROGRESS_EVENT, progress) {
+        log_line!("[WARN] Failed to emit {} event: {}", KILL_PROGRESS_EVENT, e);
+    }
+}
+
+// Kill each PID in order using `killer`, handing a progress payload to `on_progress`
+// as soon as each kill completes. Both are injectable so the sequence can be tested.
+pub fn kill_pids_with_progress<K, E>(pids: &[String], force: bool, killer: K, mut on_progress: E) -> Vec<KillResult>
+where
+    K: Fn(String, bool) -> Result<String, ProcessError>,
+    E: FnMut(KillProgress),
+{
+    let total = pids.len();
+    let mut results = Vec::with_capacity(total);
+    
+    for (index, pid) in pids.iter().enumerate() {
+        let result = match killer(pid.clone(), force) {
+            Ok(message) => KillResult { pid: pid.clone(), success: true, message },
+            Err(e) => KillResult { pid: pid.clone(), success: false, message: e.to_string() },
+        };
+        
+        on_progress(KillProgress {
+            result: result.clone(),
+            completed: index + 1,
+            total,
+        });
+        results.push(result);
+    }
+    
+    results
+}
+
+// Find zombie children of a parent and send it SIGCHLD so it reaps them. Zombies can't
+// be killed themselves; only their parent calling wait() (or its exit) removes them.
+#[tauri::command]
+fn reap_zombies(state: State<'_, AppState>, parent_pid: String) -> Result<ReapResult, ProcessError> {
+    log_line!("[INFO] Looking for zombie children of PID: {}", parent_pid);
+    
+    let parent = validate_kill_pid(&parent_pid)?;
+    let tools = state.tools();
+    
+    let ps_args = vec!["-A", "-o", "pid=,ppid=,stat="];
+    log_line!("[DEBUG] Executing command: ps {}", ps_args.join(" "));
+    
+    let output = tools.ps()
+        .args(&ps_args)
+        .audited_output()
+        .map_err(|e| {
+            log_line!("[ERROR] Failed to execute ps command: {}", e);
+            ProcessError::command_failed(format!("Failed to execute ps command: {}", e))
+        })?;
+    
+    if !output.status.success() {
+        let error_str = String::from_utf8_lossy(&output.stderr);
+        log_line!("[ERROR] ps command failed with status: {}, stderr: {}", output.status, error_str);
+        return Err(ProcessError::from_output(format!("Failed to execute ps command: {}", error_str.trim()), &output));
+    }
+    
+    let zombies = find_zombie_children(&String::from_utf8_lossy(&output.stdout), parent);
+    log_line!("[INFO] Found {} zombie child(ren) of PID {}: {:?}", zombies.len(), parent, zombies);
+    
+    let (signaled, message) = if zombies.is_empty() {
+        (false, format!("No zombie children found for PID {}", parent))
+    } else if parent == 1 {
+        (false, format!("{} zombie(s) belong to init (PID 1), which reaps them automatically", zombies.len()))
+    } else {
+        let signal = tools.kill().args(["-CHLD", &parent_pid]).audited_output().map_err(|e| {
+            log_line!("[ERROR] Failed to execute kill command for PID {}: {}", parent, e);
+            ProcessError::command_failed(format!("Failed to execute kill command: {}", e))
+        })?;
+        if !signal.status.success() {
+            let error_str = String::from_utf8_lossy(&signal.stderr);
+            return Err(ProcessError::from_output(
+                format!("Failed to send SIGCHLD to PID {}: {}", parent, error_str.trim()), &signal
+            ));
+        }
+        (true, format!("Found {} zombie(s), sent SIGCHLD to PID {} to reap them", zombies.len(), parent))
+    };
+    
+    Ok(ReapResult {
+        parent_pid,
+        zombies,
+        signaled,
+        message,
+    })
+}
+
+// Parse `ps -o pid=,ppid=,stat=` output and return the zombie (state `Z`) children of `parent`
+pub fn find_zombie_children(output: &str, parent: u32) -> Vec<u32> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let (pid, ppid, stat) = (parts.first()?, parts.get(1)?, parts.get(2)?);
+            
+            if stat.starts_with('Z') && ppid.parse::<u32>().ok()? == parent {
+                pid.parse::<u32>().ok()
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// Parse `ps -o pid=,ppid=` output into (pid, ppid) pairs
+pub fn parse_ppid_table(output: &str) -> Vec<(u32, u32)> {
+    let mut entries = Vec::new();
+    
+    for (line_num, line) in output.lines().enumerate() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        
+        if parts.len() >= 2 {
+            match (parts[0].parse::<u32>(), parts[1].parse::<u32>()) {
+                (Ok(pid), Ok(ppid)) => entries.push((pid, ppid)),
+                _ => log_line!("[WARN] Skipping unparsable line {}: {}", line_num + 1, line),
+            }
+        } else if !line.trim().is_empty() {
+            log_line!("[WARN] Skipping malformed line {}: not enough parts ({})", 
+                     line_num + 1, parts.len());
+        }
+    }
+    
+    entries
+}
+
+// Build a parent PID -> child PIDs map from a flat (pid, ppid) table
+pub fn build_children_map(entries: &[(u32, u32)]) -> HashMap<u32, Vec<u32>> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    
+    for &(pid, ppid) in entries {
+        // A process listed as its own parent (e.g. PID 0 on macOS) is not a real edge
+        if pid != ppid {
+            children.entry(ppid).or_default().push(pid);
+        }
+    }
+    
+    children
+}
+
+// A PID and every process above it, following the (pid, ppid) table up to a process
+// whose parent isn't listed
+pub fn ancestor_pids(pid: u32, entries: &[(u32, u32)]) -> HashSet<u32> {
+    let parents: HashMap<u32, u32> = entries.iter().copied().collect();
+    let mut ancestors = HashSet::from([pid]);
+    let mut current = pid;
+    
+    while let Some(&parent) = parents.get(&current) {
+        // A loop or a process listed as its own parent ends the walk
+        if !ancestors.insert(parent) {
+            break;
+        }
+        current = parent;
+    }
+    
+    ancestors
+}
+
+// Collect all transitive descendants of a PID as (pid, depth) pairs in breadth-first order.
+// Depth 1 means a direct child. Visited PIDs are tracked so a malformed table can't loop forever.
+pub fn collect_descendants(root: u32, children: &HashMap<u32, Vec<u32>>) -> Vec<(u32, usize)> {
+    let mut descendants = Vec::new();
+    let mut visited = HashSet::from([root]);
+    let mut queue = VecDeque::from([(root, 0usize)]);
+    
+    while let Some((current, depth)) = queue.pop_front() {
+        if let Some(kids) = children.get(&current) {
+            for &child in kids {
+                if visited.insert(child) {
+                    descendants.push((child, depth + 1));
+                    queue.push_back((child, depth + 1));
+                }
+            }
+        }
+    }
+    
+    descendants
+}
+
+// Preview the process tree `kill_process_tree` would kill: every transitive child of the
+// PID, nearest first, each with its depth below it. The root itself isn't included.
+#[tauri::command]
+fn get_descendants(state: State<'_, AppState>, pid: String) -> Result<Vec<ProcessInfo>, ProcessError> {
+    log_line!("[INFO] Listing descendants of PID: {}", pid);
+    
+    let root_pid = validate_kill_pid(pid.trim())?;
+    
+    let ps_args = vec!["-A", "-o", "pid=,ppid=,comm="];
+    log_line!("[DEBUG] Executing command: ps {}", ps_args.join(" "));
+    
+    let output = state.tools().ps().args(&ps_args).audited_output().map_err(|e| {
+        log_line!("[ERROR] Failed to execute ps command: {}", e);
+        ProcessError::command_failed(format!("Failed to execute ps command: {}", e))
+    })?;
+    if !output.status.success() {
+        let error_str = String::from_utf8_lossy(&output.stderr);
+        log_line!("[ERROR] ps command failed with status: {}, stderr: {}", output.status, error_str);
+        return Err(ProcessError::from_output(format!("Failed to execute ps command: {}", error_str.trim()), &output));
+    }
+    
+    let mut descendants = list_descendants(root_pid, &String::from_utf8_lossy(&output.stdout));
+    log_line!("[INFO] PID {} has {} descendant(s)", root_pid, descendants.len());
+    state.annotate(&mut descendants);
+    Ok(descendants)
+}
+
+// Descendants of `root` from `ps -o pid=,ppid=,comm=` output, breadth-first. The walk
+// visits each PID once, so a cycle in a corrupt table can't loop forever.
+pub fn list_descendants(root: u32, output: &str) -> Vec<ProcessInfo> {
+    let names: HashMap<u32, String> = output
+        .lines()
+        .filter_map(|line| {
+            let (fields, comm) = split_leading_fields(line, 2)?;
+            let pid = fields[0].parse::<u32>().ok()?;
+            Some((pid, normalize_process_name(process_basename(comm))))
+        })
+        .collect();
+    let children = build_children_map(&parse_ppid_table(output));
+    
+    collect_descendants(root, &children)
+        .into_iter()
+        .map(|(pid, depth)| ProcessInfo {
+            pid: pid.to_string(),
+            name: names.get(&pid).cloned().unwrap_or_default(),
+            port: "Unknown".to_string(),
+            depth: Some(depth),
+            is_privileged_port: false,
+            ..ProcessInfo::default()
+        })
+        .collect()
+}
+
+// Order a process tree for killing: deepest descendants first, the root last.
+// Killing parents first would re-parent their children to init before we reach them.
+pub fn tree_kill_order(root: u32, children: &HashMap<u32, Vec<u32>>) -> Vec<u32> {
+    let mut descendants = collect_descendants(root, children);
+    descendants.sort_by_key(|&(_, depth)| std::cmp::Reverse(depth));
+    
+    let mut order: Vec<u32> = descendants.into_iter().map(|(pid, _)| pid).collect();
+    order.push(root);
+    order
+}
+
+// Parse ps output to extract process information for name search
+// ps output format: PID COMMAND, optionally preceded by a header row
+pub fn parse_ps_output(output: &str, search_name: &str) -> Vec<ProcessInfo> {
+    log_line!("[DEBUG] Parsing ps output for search term: '{}', total lines: {}", search_name, output.lines().count());
+    parse_ps_stream(output.as_bytes(), &SearchOptions::for_term(search_name))
+}
+
+// Parse ps output line by line from a reader, so memory stays bounded by the matches
+// rather than the whole process table. Stops reading once `max_results` matches are found.
+pub fn parse_ps_stream<R: BufRead>(reader: R, options: &SearchOptions) -> Vec<ProcessInfo> {
+    scan_ps_stream(reader, options, true).0
+}
+
+// Like `parse_ps_stream`, but reads the whole stream so the total number of matches is
+// known even when only the first `max_results` are kept. Returns (kept, total).
+pub fn count_ps_stream<R: BufRead>(reader: R, options: &SearchOptions) -> (Vec<ProcessInfo>, usize) {
+    scan_ps_stream(reader, options, false)
+}
+
+fn scan_ps_stream<R: BufRead>(mut reader: R, options: &SearchOptions, stop_at_cap: bool) -> (Vec<ProcessInfo>, usize) {
+    let mut processes = Vec::new();
+    let mut total_matches = 0;
+    let terms_lower: Vec<String> = options.terms
+        .iter()
+        .map(|term| fold_case(term).trim().to_string())
+        .filter(|term| !term.is_empty())
+        .collect();
+    
+    // Return empty result for empty or whitespace-only search terms
+    if terms_lower.is_empty() {
+        log_line!("[DEBUG] Empty search term provided, returning no matches");
+        return (processes, 0);
+    }
+    
+    let globs = if options.glob {
+        match compile_globs(&terms_lower) {
+            Ok(globs) => Some(globs),
+            Err(e) => {
+                log_line!("[ERROR] {}", e);
+                return (processes, 0);
+            }
+        }
+    } else {
+        None
+    };
+    
+    let match_paths = terms_lower.iter().any(|term| term.contains('/'));
+    let mut buffer = Vec::new();
+    let mut line_num = 0;
+    let mut seen_row = false;
+    
+    // Fuzzy results are ranked by score, so all of them are kept until the end
+    let at_cap = |kept: usize| !options.fuzzy && options.max_results.is_some_and(|max| kept >= max);
+    
+    loop {
+        if stop_at_cap && at_cap(processes.len()) {
+            log_line!("[INFO] Reached result cap of {}, stopping ps parsing early", processes.len());
+            break;
+        }
+        
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                log_line!("[ERROR] Failed to read ps output: {}", e);
+                break;
+            }
+        }
+        line_num += 1;
+        
+        let line = String::from_utf8_lossy(&buffer);
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        
+        if parts.len() >= 2 {
+            let pid = parts[0];
+            let args = parts.get(2).copied().unwrap_or("");
+            
+            // Output from `-o pid,comm` (without `=`) starts with a "PID COMMAND" header
+            let is_header = !seen_row && pid.parse::<u32>().is_err();
+            seen_row = true;
+            if is_header {
+                log_line!("[DEBUG] Skipping ps header line: {}", line.trim());
+                continue;
+            }
+            
+            if options.hide_kernel_threads && is_kernel_thread(pid, parts[1], args) {
+                continue;
+            }
+            
+            // Some systems report comm as a full path; the directories are only noise in
+            // the name unless the search itself is for a path. Other names with a slash,
+            // like the kernel's `kworker/0:1`, aren't paths and stay whole.
+            let full_name = normalize_process_name(&recover_full_name(parts[1], args));
+            let command = if full_name.starts_with('/') && !match_paths {
+                process_basename(&full_name).to_string()
+            } else {
+                full_name
+            };
+            
+            if options.fuzzy {
+                if let Some((score, match_range)) = fuzzy_match_terms(&command, &terms_lower, options.combinator, options.fuzzy_threshold) {
+                    log_line!("[DEBUG] Found fuzzy match - PID: '{}', Name: '{}', score: {:.2}", pid, command, score);
+                    total_matches += 1;
+                    processes.push(ProcessInfo {
+                        pid: pid.to_string(),
+                        name: command,
+                        port: "Unknown".to_string(), // Port is unknown for name-based search
+                        match_range,
+                        score: Some(score),
+                        socket_type: None,
+                        fd: None,
+                        fd_type: None,
+                        note: None,
+                        depth: None,
+                        is_privileged_port: false,
+                    });
+                }
+                continue;
+            }
+            
+            // Check if the command name contains the search terms (case-insensitive).
+            // A glob always matches the whole name.
+            let matched = match &globs {
+                Some(globs) => glob_match_terms(&fold_case(&command), globs, options.combinator).then_some(Some((0, command.len()))),
+                None if options.whole_word => word_match_terms(&command, &terms_lower, options.combinator).map(Some),
+                None => match_terms(&fold_case(&command), &terms_lower, options.combinator, options.match_mode)
+                    .map(|matched_term| match options.match_mode {
+                        MatchMode::Suffix => find_suffix_match_range(&command, matched_term),
+                        _ => find_match_range(&command, matched_term),
+                    }),
+            };
+            
+            if let Some(match_range) = matched {
+                log_line!("[DEBUG] Found matching process - PID: '{}', Name: '{}'", pid, command);
+                
+                total_matches += 1;
+                if at_cap(processes.len()) {
+                    continue;
+                }
+                
+                processes.push(ProcessInfo {
+                    pid: pid.to_string(),
+                    name: command,
+                    port: "Unknown".to_string(), // Port is unknown for name-based search
+                    match_range,
+                    score: None,
+                    socket_type: None,
+                    fd: None,
+                    fd_type: None,
+                    note: None,
+                    depth: None,
+                    is_privileged_port: false,
+                });
+            }
+        } else if !line.trim().is_empty() {
+            log_line!("[WARN] Skipping malformed line {}: not enough parts ({})", 
+                     line_num, parts.len());
+        }
+    }
+    
+    if options.fuzzy {
+        // Best match first; the stable sort keeps ps order among equal scores
+        processes.sort_by(|a, b| b.score.unwrap_or(0.0).total_cmp(&a.score.unwrap_or(0.0)));
+        if let Some(max) = options.max_results {
+            processes.truncate(max);
+        }
+    }
+    
+    log_line!("[INFO] Successfully parsed {} matching processes from ps output ({} kept)", total_matches, processes.len());
+    (processes, total_matches)
+}
+
+// Compile search terms as case-insensitive globs. Globs are anchored, so `node*` matches
+// "node" and "nodemon" but not "my-node".
+pub fn compile_globs(terms: &[String]) -> Result<Vec<globset::GlobMatcher>, ProcessError> {
+    terms
+        .iter()
+        .map(|term| term.trim())
+        .filter(|term| !term.is_empty())
+        .map(|term| {
+            globset::GlobBuilder::new(term)
+                .case_insensitive(true)
+                .build()
+                .map(|glob| glob.compile_matcher())
+                .map_err(|e| ProcessError::InvalidPattern {
+                    pattern: term.to_string(),
+                    reason: e.kind().to_string(),
+                })
+        })
+        .collect()
+}
+
+// Glob counterpart of `match_terms`
+fn glob_match_terms(name: &str, globs: &[globset::GlobMatcher], combinator: TermCombinator) -> bool {
+    match combinator {
+        TermCombinator::Any => globs.iter().any(|glob| glob.is_match(name)),
+        TermCombinator::All => globs.iter().all(|glob| glob.is_match(name)),
+    }
+}
+
+// Fuzzy counterpart of `match_terms`: `Any` takes the best-scoring term, `All` needs every
+// term to reach the threshold and reports the weakest. Returns the score and the range
+// of the name that matched best.
+fn fuzzy_match_terms(name: &str, terms_lower: &[String], combinator: TermCombinator, threshold: f64) -> Option<(f64, Option<(usize, usize)>)> {
+    let scores = terms_lower.iter().map(|term| fuzzy_score(name, term));
+    
+    let (score, range) = match combinator {
+        TermCombinator::Any => scores.max_by(|a, b| a.0.total_cmp(&b.0))?,
+        TermCombinator::All => scores.min_by(|a, b| a.0.total_cmp(&b.0))?,
+    };
+    
+    (score >= threshold).then_some((score, range))
+}
+
+// Score how well an already lowercased term matches a name. A substring match scores 1.0;
+// otherwise each word of the name (split on non-alphanumerics, e.g. `nginx: worker`) is
+// compared by Jaro-Winkler similarity and the best word wins.
+pub fn fuzzy_score(name: &str, term_lower: &str) -> (f64, Option<(usize, usize)>) {
+    if let Some(range) = find_match_range(name, term_lower) {
+        return (1.0, Some(range));
+    }
+    
+    let mut best = (0.0, None);
+    let mut start = None;
+    
+    for (index, c) in name.char_indices().chain(std::iter::once((name.len(), ' '))) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(index),
+            (false, Some(from)) => {
+                let score = strsim::jaro_winkler(&fold_case(&name[from..index]), term_lower);
+                if score > best.0 {
+                    best = (score, Some((from, index)));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    
+    best
+}
+
+// Detect kernel threads: Linux shows them with a bracketed command line (`[kworker/0:1]`)
+// since they have no argv, and macOS lists the kernel itself as PID 0 (`kernel_task`).
+pub fn is_kernel_thread(pid: &str, comm: &str, args: &str) -> bool {
+    let bracketed = |s: &str| s.len() > 2 && s.starts_with('[') && s.ends_with(']');
+    pid.trim() == "0" || bracketed(comm.trim()) || bracketed(args.trim())
+}
+
+// Linux truncates comm to TASK_COMM_LEN - 1 characters
+pub const PS_COMM_MAX_LEN: usize = 15;
+
+// Recover the untruncated process name when comm hit the kernel's length limit, using
+// the executable's basename from argv[0]. The basename must extend the truncated comm,
+// so a renamed process (e.g. `postgres: writer`) keeps its comm.
+pub fn recover_full_name(comm: &str, args: &str) -> String {
+    if comm.chars().count() != PS_COMM_MAX_LEN {
+        return comm.to_string();
+    }
+    
+    let argv0 = args.split_whitespace().next().unwrap_or("");
+    let basename = process_basename(argv0);
+    
+    if basename.len() > comm.len() && basename.starts_with(comm) {
+        log_line!("[DEBUG] Recovered truncated comm '{}' as '{}'", comm, basename);
+        basename.to_string()
+    } else {
+        comm.to_string()
+    }
+}
+
+// Fold a name or search term for case-insensitive comparison. Unicode default case
+// folding goes further than lowercasing (`ß` folds to `ss`, the `ﬁ` ligature to `fi`)
+// while ASCII folds exactly as `to_lowercase` would. No locale tailoring is applied, so
+// Turkish dotless `ı` stays distinct from `i`.
+pub fn fold_case(text: &str) -> String {
+    caseless::default_case_fold_str(text)
+}
+
+// Apply the combinator to case-folded terms, returning the first term found in the name.
+// `Any` needs one term to match, `All` needs every term to, each according to `mode`.
+fn match_terms<'a>(name_lower: &str, terms_lower: &'a [String], combinator: TermCombinator, mode: MatchMode) -> Option<&'a str> {
+    let matched = match combinator {
+        TermCombinator::Any => terms_lower.iter().find(|term| mode.matches(name_lower, term)),
+        TermCombinator::All => terms_lower
+            .iter()
+            .all(|term| mode.matches(name_lower, term))
+            .then(|| terms_lower.first())
+            .flatten(),
+    };
+    
+    matched.map(|term| term.as_str())
+}
+
+// Whole-word counterpart of `match_terms`, returning the range of the first term's match
+fn word_match_terms(name: &str, terms_lower: &[String], combinator: TermCombinator) -> Option<(usize, usize)> {
+    match combinator {
+        TermCombinator::Any => terms_lower.iter().find_map(|term| find_whole_word(name, term)),
+        TermCombinator::All => terms_lower
+            .iter()
+            .map(|term| find_whole_word(name, term))
+            .collect::<Option<Vec<_>>>()?
+            .first()
+            .copied(),
+    }
+}
+
+// Like `find_match_range`, but only for an occurrence with no letter, digit or `_` right
+// before or after it, so `ssh` is found in "/usr/bin/ssh host" but not in "sshd" or
+// "openssh-sftp-server"
+pub fn find_whole_word(text: &str, query_lower: &str) -> Option<(usize, usize)> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    text.char_indices().find_map(|(start, _)| {
+        let (from, to) = find_match_range(&text[start..], query_lower)?;
+        let (from, to) = (start + from, start + to);
+        let bounded = !text[..from].chars().next_back().is_some_and(is_word)
+            && !text[to..].chars().next().is_some_and(is_word);
+        bounded.then_some((from, to))
+    })
+}
+
+// Like `find_match_range`, but for the occurrence that ends the name
+fn find_suffix_match_range(name: &str, query_lower: &str) -> Option<(usize, usize)> {
+    name.char_indices().find_map(|(start, _)| {
+        let (from, to) = find_match_range(&name[start..], query_lower)?;
+        (start + to == name.len()).then_some((start + from, start + to))
+    })
+}
+
+// Find the byte range in `name` matched case-insensitively by an already case-folded query.
+// Offsets refer to the original name and always fall on char boundaries, even when
+// folding changes a character's byte length.
+pub fn find_match_range(name: &str, query_lower: &str) -> Option<(usize, usize)> {
+    if query_lower.is_empty() {
+        return None;
+    }
+    
+    for (start, _) in name.char_indices() {
+        let mut folded = String::new();
+        
+        for (offset, c) in name[start..].char_indices() {
+            folded.push_str(&fold_case(c.encode_utf8(&mut [0; 4])));
+            
+            if !query_lower.starts_with(folded.as_str()) {
+                break;
+            }
+            if folded.len() == query_lower.len() {
+                return Some((start, start + offset + c.len_utf8()));
+            }
+        }
+    }
+    
+    None
+}
+
+// The executable name without its directory: `/usr/bin/node` yields `node`, a bare
+// `node` is returned as is
+pub fn process_basename(name: &str) -> &str {
+    match name.trim_end_matches('/').rsplit_once('/') {
+        Some((_, basename)) if !basename.is_empty() => basename,
+        _ => name,
+    }
+}
+
+// Clean a process name from ps/lsof for display: control characters are dropped,
+// whitespace runs collapse to a single space and the ends are trimmed.
+// Printable Unicode (e.g. `node应用`) is kept as-is.
+pub fn normalize_process_name(raw: &str) -> String {
+    let mut normalized = String::with_capacity(raw.len());
+    let mut pending_space = false;
+    
+    for c in raw.chars() {
+        if c.is_whitespace() {
+            pending_space = true;
+        } else if !c.is_control() {
+            if pending_space && !normalized.is_empty() {
+                normalized.push(' ');
+            }
+            pending_space = false;
+            normalized.push(c);
+        }
+    }
+    
+    normalized
+}
+
+// Whether the listeners in lsof output for one port can be reached over IPv4, IPv6 or both
+// ("ipv4", "ipv6", "dual"), judged from every row's TYPE column before rows are merged.
+// An IPv6 wildcard socket (`*:port`, `[::]:port`) also accepts IPv4 clients unless
+// IPV6_V6ONLY is set, which is off by default on Linux and macOS, so it counts as dual.
+pub fn port_binding(output: &str) -> Option<String> {
+    let mut ipv4 = false;
+    let mut ipv6 = false;
+    
+    for line in output.lines().skip(1) {
+        // TYPE comes before any column lsof may leave empty; NAME is found by scanning
+        let Some(endpoint) = find_row_endpoint(line) else {
+            continue;
+        };
+        
+        match line.split_whitespace().nth(4) {
+            Some("IPv4") => ipv4 = true,
+            Some("IPv6") => {
+                ipv6 = true;
+                let local = endpoint.split("->").next().unwrap_or(endpoint);
+                if matches!(split_host_port(local), Some(("*", _)) | Some(("::", _))) {
+                    ipv4 = true;
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    match (ipv4, ipv6) {
+        (true, true) => Some("dual".to_string()),
+        (true, false) => Some("ipv4".to_string()),
+        (false, true) => Some("ipv6".to_string()),
+        (false, false) => None,
+    }
+}
+
+// Which lsof rows `parse_lsof_rows` treats as the same listener; the first row of each is kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DedupKey {
+    #[default]
+    Pid,        // One entry per process, the simple port view
+    PidPort,    // One per process and port, taken from the row's NAME column
+    PidAddress, // One per process and local address; IPv4 and IPv6 wildcards (`*:3000`) differ
+    Row,        // Every socket row (fd), for audits
+}
+
+// Parse lsof output to extract process information
+// lsof output format: COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+// Since we use -sTCP:LISTEN, all results are already LISTEN processes
+// Rows are deduplicated by PID, so a process listening on IPv4 and IPv6 appears once
+pub fn parse_lsof_output(output: &str, port: &str) -> Vec<ProcessInfo> {
+    parse_lsof_rows(output, port, DedupKey::default())
+}
+
+// Same as `parse_lsof_output`, with the caller choosing how rows are deduplicated
+pub fn parse_lsof_rows(output: &str, port: &str, dedup: DedupKey) -> Vec<ProcessInfo> {
+    log_line!("[DEBUG] Parsing lsof output, total lines: {}", output.lines().count());
+    let mut processes = Vec::new();
+    let mut seen = HashSet::new();
+    
+    // Skip the header line and process each line
+    for (line_num, line) in output.lines().skip(1).enumerate() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        log_line!("[DEBUG] Line {}: {} parts - {}", line_num + 1, parts.len(), line);
+        
+        if parts.len() >= 2 {
+            let name = normalize_process_name(parts[0]);
+            let pid = parts[1].to_string();
+            
+            log_line!("[DEBUG] Extracted LISTEN process - Name: '{}', PID: '{}'", name, pid);
+            
+            let endpoint = find_row_endpoint(line);
+            let key = match dedup {
+                DedupKey::Pid => Some(pid.clone()),
+                DedupKey::PidPort => {
+                    let row_port = endpoint.and_then(extract_port);
+                    Some(format!("{} {}", pid, row_port.map_or_else(|| port.to_string(), |p| p.to_string())))
+                }
+                DedupKey::PidAddress => Some(format!(
+                    "{} {} {}",
+                    pid,
+                    parts.get(4).unwrap_or(&""),
+                    endpoint.unwrap_or("")
+                )),
+                DedupKey::Row => None,
+            };
+            
+            if key.is_none_or(|key| seen.insert(key)) {
+                let pid_for_log = pid.clone(); // Clone for logging before moving
+                processes.push(ProcessInfo {
+                    pid,
+                    name,
+                    port: port.to_string(),
+                    match_range: None,
+                    score: None,
+                    socket_type: parts.get(7).map(|protocol| protocol.to_string()),
+                    fd: parts.get(3).map(|fd| fd.to_string()),
+                    fd_type: parts.get(4).map(|fd_type| fd_type.to_string()),
+                    note: None,
+                    depth: None,
+                    is_privileged_port: port.parse().is_ok_and(is_privileged_port),
+                });
+                log_line!("[DEBUG] Added process with PID: {}", pid_for_log);
+            } else {
+                log_line!("[DEBUG] Skipping duplicate row for PID {} (dedup: {:?})", pid, dedup);
+            }
+        } else {
+            log_line!("[WARN] Skipping malformed line {}: not enough parts ({})", 
+                     line_num + 1, parts.len());
+        }
+    }
+    
+    log_line!("[INFO] Successfully parsed {} LISTEN process row(s) from lsof output (dedup: {:?})", processes.len(), dedup);
+    processes
+}
+
+// List every process listening on a TCP port using a single lsof run
+#[tauri::command]
+fn list_listening_ports(state: State<'_, AppState>) -> Result<Vec<ProcessInfo>, ProcessError> {
+    find_listening_ports(&state.tools())
+}
+
+// Ports dev servers and local databases usually run on: app servers and bundlers, then
+// Postgres, MySQL, Redis, MongoDB and Elasticsearch
+pub const DEV_PORTS: &[u16] = &[
+    3000, 3001, 4200, 5000, 5173, 8000, 8080, 8888, 5432, 3306, 6379, 27017, 9200,
+];
+
+// Occupancy of one port in a `scan_dev_ports` result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevPortStatus {
+    pub port: u16,
+    pub is_occupied: bool,
+    pub processes: Vec<ProcessInfo>,
+}
+
+// Check the common dev ports (DEV_PORTS, or `ports` when given) with a single lsof run
+#[tauri::command]
+fn scan_dev_ports(state: State<'_, AppState>, ports: Option<Vec<u16>>) -> Result<Vec<DevPortStatus>, ProcessError> {
+    let ports = ports.unwrap_or_else(|| DEV_PORTS.to_vec());
+    log_line!("[INFO] Scanning {} dev port(s)", ports.len());
+    
+    let statuses = filter_dev_ports(find_listening_ports(&state.tools())?, &ports);
+    
+    log_line!("[INFO] {} of {} dev port(s) occupied", statuses.iter().filter(|s| s.is_occupied).count(), statuses.len());
+    Ok(statuses)
+}
+
+// One status per requested port, in the order given (repeats dropped), each with the
+// listeners on that port
+pub fn filter_dev_ports(listeners: Vec<ProcessInfo>, ports: &[u16]) -> Vec<DevPortStatus> {
+    let mut statuses: Vec<DevPortStatus> = Vec::with_capacity(ports.len());
+    for &port in ports {
+        if !statuses.iter().any(|status| status.port == port) {
+            statuses.push(DevPortStatus { port, is_occupied: false, processes: vec![] });
+        }
+    }
+    
+    for listener in listeners {
+        let Ok(port) = listener.port.parse::<u16>() else {
+            continue;
+        };
+        if let Some(status) = statuses.iter_mut().find(|status| status.port == port) {
+            status.is_occupied = true;
+            status.processes.push(listener);
+        }
+    }
+    
+    statuses
+}
+
+fn find_listening_ports(tools: &ToolPaths) -> Result<Vec<ProcessInfo>, ProcessError> {
+    find_family_listeners(tools, IpFamily::Any)
+}
+
+// Like `find_listening_ports`, narrowed to the IPv4 or IPv6 sockets when asked
+fn find_family_listeners(tools: &ToolPaths, family: IpFamily) -> Result<Vec<ProcessInfo>, ProcessError> {
+    log_line!("[INFO] Listing all listening ports (family: {:?})", family);
+    
+    let lsof_args = vec![family.lsof_tcp_selection(), "-sTCP:LISTEN", "-P", "-n"];
+    log_line!("[DEBUG] Executing command: lsof {}", lsof_args.join(" "));
+    
+    let output = tools.lsof()
+        .args(&lsof_args)
+        .audited_output();
+    
+    match output {
+        Ok(output) => {
+            let output_str = String::from_utf8_lossy(&output.stdout);
+            let error_str = String::from_utf8_lossy(&output.stderr);
+            
+            // lsof exits with status 1 and no stderr when nothing is listening
+            if output.status.success() || error_str.trim().is_empty() {
+                let processes = parse_listening_ports(&output_str);
+                log_line!("[INFO] Found {} listening process/port pair(s)", processes.len());
+                Ok(processes)
+            } else {
+                log_line!("[ERROR] lsof command failed with status: {}, stderr: {}", 
+                         output.status, error_str);
+                Err(ProcessError::from_output(format!("Failed to execute lsof: {}", error_str.trim()), &output))
+            }
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute lsof command: {}", e);
+            Err(ProcessError::command_failed(format!("Failed to execute lsof: {}", e)))
+        }
+    }
+}
+
+// Build a process -> ports index covering every TCP and UDP socket from a single lsof run
+#[tauri::command]
+fn all_process_ports(state: State<'_, AppState>) -> Result<Vec<ProcessPorts>, ProcessError> {
+    log_line!("[INFO] Listing ports for all processes");
+    
+    let lsof_args = vec!["-iTCP", "-iUDP", "-P", "-n"];
+    log_line!("[DEBUG] Executing command: lsof {}", lsof_args.join(" "));
+    
+    let output = state.tools().lsof()
+        .args(&lsof_args)
+        .audited_output();
+    
+    match output {
+        Ok(output) => {
+            let output_str = String::from_utf8_lossy(&output.stdout);
+            let error_str = String::from_utf8_lossy(&output.stderr);
+            
+            // lsof exits with status 1 and no stderr when there are no sockets
+            if output.status.success() || error_str.trim().is_empty() {
+                let processes = group_ports_by_process(&output_str);
+                log_line!("[INFO] Found sockets for {} process(es)", processes.len());
+                Ok(processes)
+            } else {
+                log_line!("[ERROR] lsof command failed with status: {}, stderr: {}", 
+                         output.status, error_str);
+                Err(ProcessError::from_output(format!("Failed to execute lsof: {}", error_str.trim()), &output))
+            }
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute lsof command: {}", e);
+            Err(ProcessError::command_failed(format!("Failed to execute lsof: {}", e)))
+        }
+    }
+}
+
+// Group lsof socket rows by PID in one pass. Processes are returned in ascending PID order
+// and each process's ports are sorted and deduplicated (a forked server shares its sockets).
+pub fn group_ports_by_process(output: &str) -> Vec<ProcessPorts> {
+    let mut processes: Vec<ProcessPorts> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    
+    for (line_num, line) in output.lines().skip(1).enumerate() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        
+        if parts.len() < 9 {
+            if !line.trim().is_empty() {
+                log_line!("[WARN] Skipping malformed line {}: not enough parts ({})", 
+                         line_num + 1, parts.len());
+            }
+            continue;
+        }
+        
+        let Some(port) = extract_port(parts[8]) else {
+            log_line!("[WARN] No port found on line {}: {}", line_num + 1, line);
+            continue;
+        };
+        
+        let entry = PortEntry {
+            port,
+            state: parts.get(9).map(|state| state.trim_matches(|c| c == '(' || c == ')').to_string()),
+            protocol: parts[7].to_string(),
+        };
+        
+        let pid = parts[1];
+        let slot = *index.entry(pid).or_insert_with(|| {
+            processes.push(ProcessPorts {
+                pid: pid.to_string(),
+                name: normalize_process_name(parts[0]),
+                ports: Vec::new(),
+            });
+            processes.len() - 1
+        });
+        processes[slot].ports.push(entry);
+    }
+    
+    for process in &mut processes {
+        process.ports.sort_by(|a, b| {
+            (a.port, &a.protocol, &a.state).cmp(&(b.port, &b.protocol, &b.state))
+        });
+        process.ports.dedup();
+    }
+    processes.sort_by_key(|process| process.pid.parse::<u32>().unwrap_or(u32::MAX));
+    
+    processes
+}
+
+// Capture all listening ports at once so the UI renders a consistent picture
+#[tauri::command]
+fn snapshot(state: State<'_, AppState>, filter: Option<String>) -> Result<ProcessSnapshot, ProcessError> {
+    take_snapshot(&state.tools(), filter)
+}
+
+fn take_snapshot(tools: &ToolPaths, filter: Option<String>) -> Result<ProcessSnapshot, ProcessError> {
+    log_line!("[INFO] Taking process snapshot with filter: {:?}", filter);
+    
+    let processes = find_listening_ports(tools)?;
+    Ok(build_snapshot(processes, filter.as_deref(), now_millis()))
+}
+
+// Assemble a snapshot from already parsed listeners, keeping only entries whose
+// name or port contains the filter (case-insensitive)
+pub fn build_snapshot(processes: Vec<ProcessInfo>, filter: Option<&str>, timestamp: u64) -> ProcessSnapshot {
+    let filter = filter
+        .map(|f| f.trim().to_lowercase())
+        .filter(|f| !f.is_empty());
+    
+    let listening_ports = match filter {
+        Some(filter) => processes
+            .into_iter()
+            .filter(|p| p.name.to_lowercase().contains(&filter) || p.port.contains(&filter))
+            .collect(),
+        None => processes,
+    };
+    
+    log_line!("[DEBUG] Snapshot contains {} listening process/port pair(s)", listening_ports.len());
+    ProcessSnapshot {
+        listening_ports,
+        timestamp,
+        schema_version: SCHEMA_VERSION,
+    }
+}
+
+// Export the current listening-ports snapshot as JSON or CSV for sharing
+#[tauri::command]
+fn export_processes(state: State<'_, AppState>, format: String) -> Result<String, ProcessError> {
+    log_line!("[INFO] Exporting process list as {}", format);
+    
+    let tools = state.tools();
+    let current = take_snapshot(&tools, None)?;
+    // Only the CSV carries command lines; JSON keeps the snapshot as it is
+    let commands = if format.trim().eq_ignore_ascii_case("csv") {
+        let pids: Vec<&str> = current.listening_ports.iter().map(|p| p.pid.as_str()).collect();
+        fetch_commands(&tools, &pids)
+    } else {
+        HashMap::new()
+    };
+    let exported = export_snapshot(&current, &format, &commands)?;
+    
+    log_line!("[INFO] Exported {} process(es), {} characters", current.listening_ports.len(), exported.len());
+    Ok(exported)
+}
+
+// Compare two snapshots taken with `snapshot`, e.g. before and after starting a service
+#[tauri::command]
+fn diff_snapshots(a: ProcessSnapshot, b: ProcessSnapshot) -> SnapshotDiff {
+    let diff = compute_snapshot_diff(&a, &b);
+    log_line!("[INFO] Snapshot diff: {} added, {} removed, {} changed", diff.added.len(), diff.removed.len(), diff.changed.len());
+    diff
+}
+
+// Diff two snapshots regardless of the order they were passed in: the older one is the
+// baseline. Lists keep the order of the snapshot each entry comes from.
+pub fn compute_snapshot_diff(a: &ProcessSnapshot, b: &ProcessSnapshot) -> SnapshotDiff {
+    let (before, after) = if b.timestamp < a.timestamp { (b, a) } else { (a, b) };
+    let key = |p: &ProcessInfo| (p.pid.clone(), p.port.clone());
+    let earlier: HashMap<(String, String), &ProcessInfo> = before.listening_ports.iter().map(|p| (key(p), p)).collect();
+    let later: HashSet<(String, String)> = after.listening_ports.iter().map(key).collect();
+    
+    let mut diff = SnapshotDiff { schema_version: SCHEMA_VERSION, ..Default::default() };
+    for process in &after.listening_ports {
+        match earlier.get(&key(process)) {
+            None => diff.added.push(process.clone()),
+            Some(old) if old.name != process.name || old.socket_type != process.socket_type => {
+                diff.changed.push(process.clone())
+            }
+            Some(_) => {}
+        }
+    }
+    diff.removed = before.listening_ports.iter().filter(|p| !later.contains(&key(p))).cloned().collect();
+    
+    diff
+}
+
+// Full command lines for a set of PIDs from one ps call, keyed by PID. PIDs that have
+// exited since are simply missing.
+fn fetch_commands(tools: &ToolPaths, pids: &[&str]) -> HashMap<String, String> {
+    let mut unique: Vec<&str> = pids.iter().copied().filter(|pid| pid.parse::<u32>().is_ok()).collect();
+    unique.sort_unstable();
+    unique.dedup();
+    if unique.is_empty() {
+        return HashMap::new();
+    }
+    
+    // ps exits non-zero when any PID is gone but still lists the rest, so the status is ignored
+    let list = unique.join(",");
+    let output = match tools.ps().args(["-ww", "-o", "pid=,args=", "-p", &list]).audited_output() {
+        Ok(output) => output,
+        Err(e) => {
+            log_line!("[WARN] Failed to read command lines for export: {}", e);
+            return HashMap::new();
+        }
+    };
+    
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (pid, command) = line.trim_start().split_once(char::is_whitespace)?;
+            Some((pid.to_string(), command.trim().to_string()))
+        })
+        .collect()
+}
+
+// Serialize a snapshot in the requested format ("json" or "csv", case-insensitive). The
+// CSV takes each process's command line from `commands`, keyed by PID.
+pub fn export_snapshot(
+    snapshot: &ProcessSnapshot,
+    format: &str,
+    commands: &HashMap<String, String>,
+) -> Result<String, ProcessError> {
+    match format.trim().to_lowercase().as_str() {
+        "json" => serde_json::to_string_pretty(snapshot)
+            .map_err(|e| ProcessError::command_failed(format!("Failed to serialize snapshot: {}", e))),
+        "csv" => Ok(processes_to_csv(&snapshot.listening_ports, commands)),
+        _ => Err(ProcessError::InvalidFormat(format.to_string())),
+    }
+}
+
+// Render processes as CSV with a header row. The command column is left empty for a PID
+// missing from `commands`.
+pub fn processes_to_csv(processes: &[ProcessInfo], commands: &HashMap<String, String>) -> String {
+    let mut csv = String::from("pid,name,port,command\n");
+    
+    for process in processes {
+        csv.push_str(&format!("{},{},{},{}\n", 
+                              csv_escape(&process.pid), 
+                              csv_escape(&process.name), 
+                              csv_escape(&process.port),
+                              csv_escape(commands.get(&process.pid).map_or("", String::as_str))));
+    }
+    
+    csv
+}
+
+// Quote a CSV field when it contains a delimiter, quote or line break, doubling embedded quotes
+pub fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Parse lsof output listing all listeners, extracting the port of each row from the NAME column.
+// A process listening on several ports yields one entry per port; IPv4/IPv6 duplicates are merged.
+pub fn parse_listening_ports(output: &str) -> Vec<ProcessInfo> {
+    log_line!("[DEBUG] Parsing listening ports from lsof output, total lines: {}", output.lines().count());
+    let mut processes = Vec::new();
+    let mut seen = HashSet::new();
+    
+    for (line_num, line) in output.lines().skip(1).enumerate() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        
+        if parts.len() >= 9 {
+            let name = normalize_process_name(parts[0]);
+            let pid = parts[1];
+            
+            match extract_port(parts[8]) {
+                Some(port) => {
+                    if seen.insert((pid.to_string(), port)) {
+                        processes.push(ProcessInfo {
+                            pid: pid.to_string(),
+                            name,
+                            port: port.to_string(),
+                            match_range: None,
+                            score: None,
+                            socket_type: Some(parts[7].to_string()),
+                            fd: Some(parts[3].to_string()),
+                            fd_type: Some(parts[4].to_string()),
+                            note: None,
+                            depth: None,
+                            is_privileged_port: is_privileged_port(port),
+                        });
+                    }
+                }
+                None => log_line!("[WARN] No port found on line {}: {}", line_num + 1, line),
+            }
+        } else if !line.trim().is_empty() {
+            log_line!("[WARN] Skipping malformed line {}: not enough parts ({})", 
+                     line_num + 1, parts.len());
+        }
+    }
+    
+    processes
+}
+
+// Extract the numeric port from an lsof NAME field such as `*:3000`, `127.0.0.1:8080` or `[::1]:3000`.
+// For connection rows (`local->remote`) the local endpoint's port is used.
+pub fn extract_port(name_field: &str) -> Option<u16> {
+    let local = name_field.split("->").next()?;
+    let (_, port) = split_host_port(local)?;
+    port.parse::<u16>().ok()
+}
+
+// Find the port in an lsof row by scanning for the `host:port` token rather than assuming
+// NAME is the ninth column; some socket types leave columns empty, shifting it left. The
+// scan runs from the end, where NAME and the trailing state live, and skips COMMAND.
+pub fn find_row_port(line: &str) -> Option<u16> {
+    find_row_endpoint(line).and_then(extract_port)
+}
+
+// The `host:port` token `find_row_port` reads the port from
+pub fn find_row_endpoint(line: &str) -> Option<&str> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    row_name_index(&parts).map(|index| parts[index])
+}
+
+// Index of the NAME column among a row's whitespace-split parts. It is found by scanning
+// as in `find_row_port`; a NAME without a port can only be taken from the ninth column.
+// None when the row is too short to hold the columns before NAME.
+fn row_name_index(parts: &[&str]) -> Option<usize> {
+    (1..parts.len())
+        .rev()
+        .find(|&index| extract_port(parts[index]).is_some())
+        .or((parts.len() >= 9).then_some(8))
+        .filter(|&index| index > 5)
+}
+
+// Split an lsof endpoint into host and port on the last colon outside of IPv6 brackets.
+// `[fe80::1%en0]:8080` yields ("fe80::1%en0", "8080"); a bare IPv6 address like `::1`
+// has no unambiguous port and yields None.
+pub fn split_host_port(endpoint: &str) -> Option<(&str, &str)> {
+    if let Some(rest) = endpoint.strip_prefix('[') {
+        let close = rest.find(']')?;
+        let port = rest[close + 1..].strip_prefix(':')?;
+        return Some((&rest[..close], port));
+    }
+    
+    // Without brackets, more than one colon means a raw IPv6 address rather than host:port
+    if endpoint.matches(':').count() != 1 {
+        return None;
+    }
+    
+    endpoint.rsplit_once(':')
+}
+
+// Split the zone index off an IPv6 host: `fe80::1%en0` yields ("fe80::1", Some("en0")).
+// Hosts without a zone, including every IPv4 address, come back unchanged.
+pub fn split_zone(host: &str) -> (&str, Option<&str>) {
+    match host.split_once('%') {
+        Some((address, zone)) if address.contains(':') && !zone.is_empty() => (address, Some(zone)),
+        _ => (host, None),
+    }
+}
+
+// Well-known ports that resolve without consulting the system's services database
+pub const WELL_KNOWN_PORTS: &[(u16, &str)] = &[
+    (21, "ftp"),
+    (22, "ssh"),
+    (25, "smtp"),
+    (53, "domain"),
+    (80, "http"),
+    (443, "https"),
+    (3306, "mysql"),
+    (5432, "postgresql"),
+    (6379, "redis"),
+    (27017, "mongodb"),
+];
+
+pub const SERVICES_PATH: &str = "/etc/services";
+
+// Name the service registered for a port, e.g. 5432 -> "postgresql". The built-in table
+// is tried first, then /etc/services for the given protocol ("tcp" unless specified).
+// `fresh` re-reads /etc/services instead of using the cached copy.
+#[tauri::command]
+fn port_service_name(state: State<'_, AppState>, port: u16, protocol: Option<String>, fresh: Option<bool>) -> Option<String> {
+    let protocol = protocol.unwrap_or_else(|| "tcp".to_string());
+    
+    let mut services = match state.services.lock() {
+        Ok(services) => services,
+        Err(e) => {
+            log_line!("[ERROR] Services cache lock poisoned: {}", e);
+            return resolve_service_name(port, &protocol, &HashMap::new());
+        }
+    };
+    if fresh.unwrap_or(false) {
+        *services = None;
+    }
+    let services = services.get_or_insert_with(read_system_services);
+    
+    let service = resolve_service_name(port, &protocol, services);
+    log_line!("[DEBUG] Service for port {}/{}: {:?}", port, protocol, service);
+    service
+}
+
+// Read and parse /etc/services. A missing file (as in minimal containers) leaves only the
+// built-in table.
+fn read_system_services() -> HashMap<(u16, String), String> {
+    match std::fs::read_to_string(SERVICES_PATH) {
+        Ok(contents) => parse_services(&contents),
+        Err(e) => {
+            log_line!("[WARN] Could not read {}: {}", SERVICES_PATH, e);
+            HashMap::new()
+        }
+    }
+}
+
+// Empty every in-memory cache (port listeners, /etc/services) for a guaranteed-fresh view
+#[tauri::command]
+fn clear_caches(state: State<'_, AppState>) {
+    log_line!("[INFO] Clearing all caches");
+    state.clear_caches();
+}
+
+// Resolve a port to a service name from the built-in table, falling back to `services`
+pub fn resolve_service_name(port: u16, protocol: &str, services: &HashMap<(u16, String), String>) -> Option<String> {
+    WELL_KNOWN_PORTS
+        .iter()
+        .find(|(known, _)| *known == port)
+        .map(|(_, name)| name.to_string())
+        .or_else(|| services.get(&(port, protocol.to_ascii_lowercase())).cloned())
+}
+
+// Parse /etc/services lines of the form `name port/protocol [aliases...] [# comment]`
+// into a (port, protocol) -> name map. The first entry for a port and protocol wins.
+pub fn parse_services(contents: &str) -> HashMap<(u16, String), String> {
+    let mut services = HashMap::new();
+    
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(port_protocol)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let Some((port, protocol)) = port_protocol.split_once('/') else {
+            continue;
+        };
+        if let Ok(port) = port.parse::<u16>() {
+            services
+                .entry((port, protocol.to_ascii_lowercase()))
+                .or_insert_with(|| name.to_string());
+        }
+    }
+    
+    services
+}
+
+// Find the listeners on a port and return their full details in a single call
+#[tauri::command]
+fn inspect_port(state: State<'_, AppState>, port: String) -> Result<Vec<ProcessDetail>, ProcessError> {
+    log_line!("[INFO] Inspecting port: {}", port);
+    
+    let port_num = u16::from_str(port.trim()).map_err(|_| ProcessError::InvalidPort(port.clone()))?;
+    let tools = state.tools();
+    let listeners = find_port_listeners(&tools, port_num)?;
+    
+    Ok(assemble_port_details(listeners, INSPECT_PORT_MAX_PROCESSES, |pid| {
+        fetch_process_detail(&tools, pid.to_string())
+    }))
+}
+
+pub const INSPECT_PORT_MAX_PROCESSES: usize = 16;
+
+// Fetch details for up to `max` listeners concurrently, keeping the listener order. A listener
+// whose detail can't be fetched (e.g. it exited meanwhile) still appears with its basic info.
+pub fn assemble_port_details<F>(listeners: Vec<ProcessInfo>, max: usize, fetch: F) -> Vec<ProcessDetail>
+where
+    F: Fn(&str) -> Result<ProcessDetail, String> + Sync,
+{
+    if listeners.len() > max {
+        log_line!("[WARN] {} listeners found, only inspecting the first {}", listeners.len(), max);
+    }
+    let listeners: Vec<ProcessInfo> = listeners.into_iter().take(max).collect();
+    
+    let fetched: Vec<Result<ProcessDetail, String>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = listeners
+            .iter()
+            .map(|listener| scope.spawn(|| fetch(&listener.pid)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or_else(|_| Err("Detail lookup panicked".to_string())))
+            .collect()
+    });
+    
+    listeners
+        .into_iter()
+        .zip(fetched)
+        .map(|(listener, detail)| match detail {
+            Ok(detail) => detail,
+            Err(e) => {
+                log_line!("[WARN] No detail for PID {}: {}", listener.pid, e);
+                ProcessDetail {
+                    pid: listener.pid,
+                    name: listener.name,
+                    port: listener.port,
+                    ..Default::default()
+                }
+            }
+        })
+        .collect()
+}
+
+// Get detailed process information using ps command. `fields` limits the work to the
+// listed attributes (see DETAIL_FIELDS); without it everything is fetched.
+#[tauri::command]
+fn get_process_detail(state: State<'_, AppState>, pid: String, fields: Option<Vec<String>>) -> Result<ProcessDetail, String> {
+    let mut detail = match fields {
+        Some(fields) if !fields.is_empty() => {
+            let plan = plan_detail_fields(&fields).map_err(|e| e.to_string())?;
+            fetch_selected_detail(&state.tools(), &pid, &plan)?
+        }
+        _ => fetch_process_detail(&state.tools(), pid)?,
+    };
+    if let Ok(mut notes) = state.notes.lock() {
+        detail.note = notes.get(&detail.pid, Some(&detail.name));
+    }
+    Ok(detail)
+}
+
+// Placeholder for the value of a variable named in the redaction list
+pub const REDACTED_VALUE: &str = "<redacted>";
+
+// A process's environment variables in the order the process holds them, e.g. to see which
+// PORT a misconfigured service thinks it should bind. Values of the variables named in
+// `redact` (matched case-insensitively) are replaced; nothing is redacted by default.
+#[tauri::command]
+fn get_process_env(state: State<'_, AppState>, pid: String, redact: Option<Vec<String>>) -> Result<Vec<(String, String)>, ProcessError> {
+    log_line!("[INFO] Reading environment of PID: {}", pid);
+    
+    let pid = pid.trim();
+    validate_kill_pid(pid)?;
+    
+    let vars = read_process_env(&state.tools(), pid)?;
+    log_line!("[INFO] Read {} environment variable(s) of PID {}", vars.len(), pid);
+    Ok(redact_env(vars, &redact.unwrap_or_default()))
+}
+
+// Read /proc/<pid>/environ; only the owner (or root) may read another process's environment
+#[cfg(target_os = "linux")]
+fn read_process_env(_tools: &ToolPaths, pid: &str) -> Result<Vec<(String, String)>, ProcessError> {
+    match std::fs::read(format!("/proc/{}/environ", pid)) {
+        Ok(data) => Ok(parse_environ(&String::from_utf8_lossy(&data))),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            log_line!("[WARN] Permission denied reading environment of PID {}", pid);
+            Err(ProcessError::command_failed(format!(
+                "Permission denied reading the environment of PID {}; it belongs to another user", pid)))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err(ProcessError::command_failed(format!("Process {} not found", pid)))
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to read environment of PID {}: {}", pid, e);
+            Err(ProcessError::command_failed(format!("Failed to read environment of PID {}: {}", pid, e)))
+        }
+    }
+}
+
+// `ps -E` appends the environment to the command line, so the plain command line is fetched
+// too and stripped off. ps silently leaves out the environment of other users' processes.
+#[cfg(not(target_os = "linux"))]
+fn read_process_env(tools: &ToolPaths, pid: &str) -> Result<Vec<(String, String)>, ProcessError> {
+    let run = |args: &[&str]| -> Result<String, ProcessError> {
+        log_line!("[DEBUG] Executing command: ps {}", args.join(" "));
+        let output = tools.ps().args(args).audited_output().map_err(|e| {
+            log_line!("[ERROR] Failed to execute ps command: {}", e);
+            ProcessError::command_failed(format!("Failed to execute ps command: {}", e))
+        })?;
+        if !output.status.success() {
+            return Err(ProcessError::from_output(format!("Process {} not found", pid), &output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    
+    let command = run(&["-ww", "-p", pid, "-o", "command="])?;
+    let with_env = run(&["-Eww", "-p", pid, "-o", "command="])?;
+    Ok(parse_ps_env(&with_env, &command))
+}
+
+// Parse NUL-separated `KEY=VALUE` entries as found in /proc/<pid>/environ. Entries without
+// an `=` are skipped; a value may itself contain `=`.
+pub fn parse_environ(data: &str) -> Vec<(String, String)> {
+    data.split('\0')
+        .filter_map(|entry| entry.split_once('='))
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+// Parse the environment out of `ps -E -o command=` output, given the plain command line it
+// starts with. ps joins the variables with spaces, so a word that doesn't start with
+// `NAME=` is taken to continue the previous value.
+pub fn parse_ps_env(output: &str, command: &str) -> Vec<(String, String)> {
+    let output = output.trim();
+    let rest = output.strip_prefix(command.trim()).unwrap_or(output);
+    let is_name = |key: &str| {
+        !key.is_empty()
+            && !key.starts_with(|c: char| c.is_ascii_digit())
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    
+    let mut vars: Vec<(String, String)> = Vec::new();
+    for word in rest.split_whitespace() {
+        match word.split_once('=') {
+            Some((key, value)) if is_name(key) => vars.push((key.to_string(), value.to_string())),
+            _ => {
+                if let Some((_, value)) = vars.last_mut() {
+                    value.push(' ');
+                    value.push_str(word);
+                }
+            }
+        }
+    }
+    vars
+}
+
+// Replace the values of the named variables with `REDACTED_VALUE`
+pub fn redact_env(vars: Vec<(String, String)>, redact: &[String]) -> Vec<(String, String)> {
+    vars.into_iter()
+        .map(|(key, value)| {
+            if redact.iter().any(|name| name.trim().eq_ignore_ascii_case(&key)) {
+                (key, REDACTED_VALUE.to_string())
+            } else {
+                (key, value)
+            }
+        })
+        .collect()
+}
+
+// Label a running process for this session, e.g. "frontend" or "db". The note sticks to
+// the PID only while it runs the same program; a blank note removes it.
+#[tauri::command]
+fn set_process_note(state: State<'_, AppState>, pid: String, note: String) -> Result<(), ProcessError> {
+    validate_kill_pid(&pid)?;
+    let name = get_process_name(&state.tools(), &pid)
+        .ok_or_else(|| ProcessError::InvalidPid(format!("No process with PID {}", pid)))?;
+    
+    log_line!("[INFO] Setting note for PID {} ({}): '{}'", pid, name, note);
+    match state.notes.lock() {
+        Ok(mut notes) => notes.set(&pid, &name, &note),
+        Err(e) => log_line!("[ERROR] Process notes lock poisoned, note not saved: {}", e),
+    }
+    Ok(())
+}
+
+fn fetch_process_detail(tools: &ToolPaths, pid: String) -> Result<ProcessDetail, String> {
+    log_line!("[INFO] Getting detailed information for process PID: {}", pid);
+    
+    // Validate PID format
+    if let Err(e) = pid.parse::<u32>() {
+        log_line!("[ERROR] Invalid PID format '{}': {}", pid, e);
+        return Err(format!("Invalid PID format: {}", pid));
+    }
+    
+    // Use ps command to get detailed process information
+    // We'll use separate ps calls for better field parsing
+    log_line!("[DEBUG] Getting basic process info for PID: {}", pid);
+    
+    // Get basic info: pid, real and effective uid, command name, full command
+    let basic = run_ps_columns(tools, &pid, "pid=,ruid=,uid=,comm=,args=");
+    
+    // Get resource usage, scheduler state and user: pid, pcpu, pmem, vsz, rss, stat, user.
+    // The user goes last, since directory-service display names can contain spaces.
+    let resource = run_ps_columns(tools, &pid, "pid=,pcpu=,pmem=,vsz=,rss=,stat=,user=");
+    
+    // Get timing: pid, elapsed time (for an exact timestamp), cumulative CPU time and
+    // lstart (for display)
+    let time = run_ps_columns(tools, &pid, "pid=,etime=,time=,lstart=");
+    
+    // Only the basic query decides whether the process exists; the others just add detail
+    let Some(mut detail) = basic.as_deref().and_then(|basic| {
+        parse_process_detail(basic, resource.as_deref().unwrap_or(""), time.as_deref().unwrap_or(""), now_millis() / 1000)
+    }) else {
+        log_line!("[ERROR] Unable to resolve process info for PID: {}", pid);
+        return Err(format!("Failed to get process information for PID: {}", pid));
+    };
+    
+    // Try to get port information from lsof
+    let port_info = get_process_port(tools, &pid);
+    complete_detail(tools, &mut detail, &port_info);
+    
+    log_line!("[INFO] Successfully retrieved detailed information for PID: {}", pid);
+    Ok(detail)
+}
+
+// Attributes `get_process_detail` can be limited to
+pub const DETAIL_FIELDS: [&str; 10] = ["name", "command", "user", "cpu", "memory", "state", "time", "port", "container", "oom"];
+
+// One ps query of a detail plan: fixed-width columns, then at most one column whose
+// values may contain spaces, which has to come last to be parsed whole
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PsQuery {
+    pub fixed: Vec<&'static str>,
+    pub variable: Option<&'static str>,
+}
+
+impl PsQuery {
+    // The `-o` format, always led by the PID
+    pub fn format(&self) -> String {
+        std::iter::once("pid")
+            .chain(self.fixed.iter().copied())
+            .chain(self.variable)
+            .map(|column| format!("{}=", column))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+// What fetching a chosen set of detail fields takes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetailPlan {
+    pub queries: Vec<PsQuery>,
+    pub port: bool,      // Run the lsof port lookup
+    pub shared: bool,    // Read shared memory from /proc (Linux only)
+    pub container: bool, // Read the container id from /proc (Linux only)
+    pub oom: bool,       // Read the OOM scores from /proc (Linux only)
+}
+
+// Work out the ps columns and extra lookups needed for the requested fields. Fixed-width
+// columns share the first query; each further space-containing column (comm, args, user,
+// lstart) needs its own. The first query always runs, since it also proves the PID exists.
+pub fn plan_detail_fields(fields: &[String]) -> Result<DetailPlan, ProcessError> {
+    let mut fixed: Vec<&'static str> = Vec::new();
+    let mut variable: Vec<&'static str> = Vec::new();
+    let mut plan = DetailPlan { queries: vec![], port: false, shared: false, container: false, oom: false };
+    
+    for field in fields {
+        let (field_fixed, field_variable): (&[&'static str], Option<&'static str>) = match field.trim().to_lowercase().as_str() {
+            "name" => (&[], Some("comm")),
+            "command" => (&[], Some("args")),
+            "user" => (&["ruid", "uid"], Some("user")),
+            "cpu" => (&["pcpu"], None),
+            "memory" => {
+                plan.shared = true;
+                (&["pmem", "vsz", "rss"], None)
+            }
+            "state" => (&["stat"], None),
+            "time" => (&["etime", "time"], Some("lstart")),
+            "port" => {
+                plan.port = true;
+                (&[], None)
+            }
+            "container" => {
+                plan.container = true;
+                (&[], None)
+            }
+            "oom" => {
+                plan.oom = true;
+                (&[], None)
+            }
+            _ => {
+                log_line!("[ERROR] Unknown detail field '{}'", field);
+                return Err(ProcessError::InvalidField {
+                    field: field.clone(),
+                    valid: DETAIL_FIELDS.to_vec(),
+                });
+            }
+        };
+        
+        for column in field_fixed {
+            if !fixed.contains(column) {
+                fixed.push(column);
+            }
+        }
+        if let Some(column) = field_variable.filter(|column| !variable.contains(column)) {
+            variable.push(column);
+        }
+    }
+    
+    let mut variable = variable.into_iter();
+    plan.queries.push(PsQuery { fixed, variable: variable.next() });
+    plan.queries.extend(variable.map(|column| PsQuery { fixed: vec![], variable: Some(column) }));
+    Ok(plan)
+}
+
+// Fetch only the detail fields a plan asks for
+fn fetch_selected_detail(tools: &ToolPaths, pid: &str, plan: &DetailPlan) -> Result<ProcessDetail, String> {
+    log_line!("[INFO] Getting selected detail for PID {} ({} ps quer(ies), port: {})", pid, plan.queries.len(), plan.port);
+    validate_kill_pid(pid).map_err(|e| e.to_string())?;
+    
+    let mut detail = ProcessDetail {
+        pid: pid.to_string(),
+        ..Default::default()
+    };
+    let now_secs = now_millis() / 1000;
+    
+    for (index, query) in plan.queries.iter().enumerate() {
+        let output = run_ps_columns(tools, pid, &query.format());
+        match output {
+            Some(output) => apply_ps_query(&mut detail, query, &output, now_secs),
+            // Only the first query decides whether the process exists
+            None if index == 0 => return Err(format!("Failed to get process information for PID: {}", pid)),
+            None => {}
+        }
+    }
+    
+    if detail.user.is_some() {
+        detail.real_user = match (detail.ruid, detail.euid) {
+            (Some(ruid), Some(euid)) if ruid != euid => get_real_user(tools, pid),
+            _ => detail.user.clone(),
+        };
+    }
+    if plan.port {
+        detail.port = get_process_port(tools, pid).describe();
+    }
+    if plan.shared {
+        detail.shared_bytes = read_shared_bytes(pid);
+    }
+    if plan.container {
+        detail.container_id = read_container_id(pid);
+    }
+    if plan.oom {
+        (detail.oom_score, detail.oom_score_adj) = read_oom_scores(pid);
+    }
+    Ok(detail)
+}
+
+// Store the values of one ps query row in a detail, column by column
+pub fn apply_ps_query(detail: &mut ProcessDetail, query: &PsQuery, output: &str, now_secs: u64) {
+    let Some((fields, rest)) = split_leading_fields(output, 1 + query.fixed.len()) else {
+        return;
+    };
+    
+    for (column, value) in query.fixed.iter().zip(&fields[1..]) {
+        set_ps_column(detail, column, value, now_secs);
+    }
+    if let Some(column) = query.variable {
+        set_ps_column(detail, column, rest, now_secs);
+    }
+}
+
+fn set_ps_column(detail: &mut ProcessDetail, column: &str, value: &str, now_secs: u64) {
+    let value = value.trim();
+    match column {
+        "comm" => detail.name = value.to_string(),
+        "args" => detail.command = (!value.is_empty()).then(|| value.to_string()),
+        "ruid" => detail.ruid = value.parse().ok(),
+        "uid" => detail.euid = value.parse().ok(),
+        "user" => {
+            let (user, truncated) = parse_ps_user(value).map_or((None, false), |(user, truncated)| (Some(user), truncated));
+            detail.user = user;
+            detail.user_truncated = truncated;
+        }
+        "pcpu" => {
+            detail.cpu_usage = Some(format!("{}%", value));
+            detail.cpu_cores = parse_cpu_cores(value);
+        }
+        "pmem" => detail.memory_usage = Some(format_memory_usage(value)),
+        "vsz" => detail.vsz_bytes = kib_to_bytes(value),
+        "rss" => detail.rss_bytes = kib_to_bytes(value),
+        "stat" => detail.is_killable = is_killable_state(value),
+        "etime" => {
+            detail.elapsed_seconds = parse_etime(value);
+            detail.uptime_human = detail.elapsed_seconds.map(format_uptime);
+            detail.start_time_iso = start_time_iso(now_secs, value);
+        }
+        "time" => {
+            detail.cpu_time = Some(value.to_string());
+            detail.cpu_time_seconds = parse_cpu_time(value);
+        }
+        "lstart" => detail.start_time = (!value.is_empty()).then(|| value.to_string()),
+        _ => {}
+    }
+}
+
+// Fill in the parts of a detail that don't come from the ps queries
+fn complete_detail(tools: &ToolPaths, detail: &mut ProcessDetail, port_info: &PortLookup) {
+    // The real user's name is only looked up when it differs (setuid)
+    if let (Some(ruid), Some(euid)) = (detail.ruid, detail.euid) {
+        if ruid != euid {
+            detail.real_user = get_real_user(tools, &detail.pid);
+        }
+    }
+    
+    detail.port = port_info.describe();
+    detail.shared_bytes = read_shared_bytes(&detail.pid);
+    detail.container_id = read_container_id(&detail.pid);
+    (detail.oom_score, detail.oom_score_adj) = read_oom_scores(&detail.pid);
+}
+
+// Details for several processes at once, e.g. a multi-selection in the UI. The same three
+// ps queries and one lsof run cover every PID; PIDs that no longer exist are left out.
+#[tauri::command]
+fn get_process_details(state: State<'_, AppState>, pids: Vec<String>) -> Result<Vec<ProcessDetail>, ProcessError> {
+    let mut pid_list: Vec<String> = Vec::new();
+    for pid in &pids {
+        let pid = validate_kill_pid(pid.trim())?.to_string();
+        if !pid_list.contains(&pid) {
+            pid_list.push(pid);
+        }
+    }
+    if pid_list.is_empty() {
+        return Ok(vec![]);
+    }
+    
+    let tools = state.tools();
+    let mut details = fetch_process_details(&tools, &pid_list.join(","));
+    if let Ok(mut notes) = state.notes.lock() {
+        for detail in &mut details {
+            detail.note = notes.get(&detail.pid, Some(&detail.name));
+        }
+    }
+    Ok(details)
+}
+
+fn fetch_process_details(tools: &ToolPaths, pid_list: &str) -> Vec<ProcessDetail> {
+    log_line!("[INFO] Getting detailed information for PIDs: {}", pid_list);
+    
+    // ps exits non-zero when any PID is missing but still prints the others, so the
+    // output is used regardless of status
+    let run = |columns: &str| match tools.ps().args(["-p", pid_list, "-o", columns]).audited_output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute ps for PIDs {}: {}", pid_list, e);
+            String::new()
+        }
+    };
+    let basic = run("pid=,ruid=,uid=,comm=,args=");
+    let resource = run("pid=,pcpu=,pmem=,vsz=,rss=,stat=,user=");
+    let time = run("pid=,etime=,time=,lstart=");
+    
+    let mut details = parse_process_details(&basic, &resource, &time, now_millis() / 1000);
+    
+    // One lsof run for all ports; -a makes -p and -i narrow each other instead of adding up
+    let ports = match tools.lsof().args(["-a", "-p", pid_list, "-P", "-n", "-iTCP"]).audited_output() {
+        Ok(output) => group_ports_by_pid(&String::from_utf8_lossy(&output.stdout)),
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute lsof command: {}", e);
+            HashMap::new()
+        }
+    };
+    
+    for detail in &mut details {
+        let port_info = match ports.get(&detail.pid) {
+            Some(ports) => PortLookup::Ports(ports.clone()),
+            None => PortLookup::NoPorts,
+        };
+        complete_detail(tools, detail, &port_info);
+    }
+    
+    log_line!("[INFO] Retrieved details for {} process(es)", details.len());
+    details
+}
+
+// Build one detail per PID from multi-row output of the three detail ps queries. Rows
+// are matched up by their leading PID; a PID missing from the basic query has exited.
+pub fn parse_process_details(basic: &str, resource: &str, time: &str, now_secs: u64) -> Vec<ProcessDetail> {
+    let rows_by_pid = |output: &str| -> HashMap<String, String> {
+        output
+            .lines()
+            .filter_map(|line| Some((line.split_whitespace().next()?.to_string(), line.to_string())))
+            .collect()
+    };
+    let (resource_rows, time_rows) = (rows_by_pid(resource), rows_by_pid(time));
+    
+    basic
+        .lines()
+        .filter_map(|line| {
+            let pid = line.split_whitespace().next()?;
+            parse_process_detail(
+                line,
+                resource_rows.get(pid).map(String::as_str).unwrap_or(""),
+                time_rows.get(pid).map(String::as_str).unwrap_or(""),
+                now_secs,
+            )
+        })
+        .collect()
+}
+
+// Ports from lsof socket rows, grouped by PID in order of appearance without duplicates
+pub fn group_ports_by_pid(output: &str) -> HashMap<String, Vec<String>> {
+    let mut ports: HashMap<String, Vec<String>> = HashMap::new();
+    
+    for line in output.lines().skip(1) {
+        let (Some(pid), Some(port)) = (line.split_whitespace().nth(1), find_row_port(line)) else {
+            continue;
+        };
+        let entry = ports.entry(pid.to_string()).or_default();
+        if !entry.contains(&port.to_string()) {
+            entry.push(port.to_string());
+        }
+    }
+    
+    ports
+}
+
+// Run `ps -p <pid> -o <columns>`, None if it fails or prints nothing
+fn run_ps_columns(tools: &ToolPaths, pid: &str, columns: &str) -> Option<String> {
+    match tools.ps().args(["-p", pid, "-o", columns]).audited_output() {
+        Ok(output) if output.status.success() => {
+            let output_str = String::from_utf8_lossy(&output.stdout).into_owned();
+            log_line!("[DEBUG] ps {} for PID {}: {}", columns, pid, output_str.trim());
+            (!output_str.trim().is_empty()).then_some(output_str)
+        }
+        Ok(output) => {
+            log_line!("[WARN] ps {} failed for PID {}: status={}", columns, pid, output.status);
+            None
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute ps for PID {}: {}", pid, e);
+            None
+        }
+    }
+}
+
+// Build a process detail from the three ps queries of `fetch_process_detail`. Only the
+// PID at the start of the basic row is required; any column that is missing or doesn't
+// parse (restricted processes, ps variants) is left None rather than failing the whole
+// detail. Port, shared memory, container and OOM scores are filled in separately.
+pub fn parse_process_detail(basic: &str, resource: &str, time: &str, now_secs: u64) -> Option<ProcessDetail> {
+    let basic_fields: Vec<&str> = basic.split_whitespace().collect();
+    let pid = *basic_fields.first().filter(|pid| pid.parse::<u32>().is_ok())?;
+    
+    // Parse basic info; with fewer columns than expected, the last one is taken as the name
+    let (ids, name, command) = match split_leading_fields(basic, 4) {
+        Some((basic_parts, command)) => (
+            parse_user_ids(basic_parts[1], basic_parts[2]),
+            basic_parts[3],
+            (!command.is_empty()).then(|| command.to_string()),
+        ),
+        None => (None, basic_fields.get(1..).and_then(|rest| rest.last()).copied().unwrap_or(""), None),
+    };
+    
+    // Parse resource info; everything after the sixth column is the user
+    let (resource_parts, user_field) = split_leading_fields(resource, 6)
+        .unwrap_or_else(|| (resource.split_whitespace().collect(), ""));
+    let (user, user_truncated) = match parse_ps_user(user_field) {
+        Some((user, truncated)) => (Some(user), truncated),
+        None => (None, false),
+    };
+    let cpu_usage = resource_parts.get(1).map(|pcpu| format!("{}%", pcpu));
+    let cpu_cores = resource_parts.get(1).and_then(|pcpu| parse_cpu_cores(pcpu));
+    let memory_usage = resource_parts.get(2).map(|pmem| format_memory_usage(pmem));
+    let vsz_bytes = resource_parts.get(3).and_then(|vsz| kib_to_bytes(vsz));
+    let rss_bytes = resource_parts.get(4).and_then(|rss| kib_to_bytes(rss));
+    let is_killable = resource_parts.get(5).is_none_or(|stat| is_killable_state(stat));
+    
+    // Parse start time (skip PID, etime and CPU time, take the rest)
+    let time_parts: Vec<&str> = time.split_whitespace().collect();
+    let start_time = (time_parts.len() > 3).then(|| time_parts[3..].join(" "));
+    let cpu_time = time_parts.get(2).map(|time| time.to_string());
+    let cpu_time_seconds = time_parts.get(2).and_then(|time| parse_cpu_time(time));
+    let start_time_iso = time_parts.get(1).and_then(|etime| start_time_iso(now_secs, etime));
+    let elapsed_seconds = time_parts.get(1).and_then(|etime| parse_etime(etime));
+    
+    Some(ProcessDetail {
+        pid: pid.to_string(),
+        name: name.to_string(),
+        port: "Unknown".to_string(),
+        real_user: user.clone(),
+        user,
+        user_truncated,
+        ruid: ids.map(|ids| ids.ruid),
+        euid: ids.map(|ids| ids.euid),
+        command,
+        cpu_usage,
+        cpu_cores,
+        memory_usage,
+        rss_bytes,
+        vsz_bytes,
+        start_time,
+        start_time_iso,
+        elapsed_seconds,
+        uptime_human: elapsed_seconds.map(format_uptime),
+        cpu_time,
+        cpu_time_seconds,
+        is_killable,
+        ..Default::default()
+    })
+}
+
+// Split a ps row into its first `count` whitespace-separated columns and the verbatim
+// rest of the line, which keeps any spaces inside a trailing variable-width column
+// (args, user). None if the row has fewer columns.
+pub fn split_leading_fields(line: &str, count: usize) -> Option<(Vec<&str>, &str)> {
+    let mut rest = line.trim();
+    let mut fields = Vec::with_capacity(count);
+    
+    for _ in 0..count {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        fields.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    
+    Some((fields, rest))
+}
+
+// Real and effective user id of a process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserIds {
+    pub ruid: u32,
+    pub euid: u32,
+}
+
+impl UserIds {
+    // A setuid binary runs with its owner's rights but was launched by someone else
+    pub fn is_setuid(&self) -> bool {
+        self.ruid != self.euid
+    }
+}
+
+// Parse the ps `ruid` and `uid` columns
+pub fn parse_user_ids(ruid: &str, euid: &str) -> Option<UserIds> {
+    Some(UserIds {
+        ruid: ruid.trim().parse().ok()?,
+        euid: euid.trim().parse().ok()?,
+    })
+}
+
+// Name of the real user, alone on its ps row since it may contain spaces
+fn get_real_user(tools: &ToolPaths, pid: &str) -> Option<String> {
+    let output = tools.ps().args(["-p", pid, "-o", "ruser="]).audited_output().ok()?;
+    parse_ps_user(&String::from_utf8_lossy(&output.stdout)).map(|(user, _)| user)
+}
+
+// Parse the ps user column, which may contain spaces. Linux ps cuts names that don't fit
+// the column and appends `+`; that marker is stripped and reported as truncated.
+pub fn parse_ps_user(field: &str) -> Option<(String, bool)> {
+    let user = field.trim();
+    if user.is_empty() {
+        return None;
+    }
+    
+    match user.strip_suffix('+') {
+        Some(cut) if !cut.is_empty() => {
+            log_line!("[WARN] ps truncated user name '{}'", user);
+            Some((cut.to_string(), true))
+        }
+        _ => Some((user.to_string(), false)),
+    }
+}
+
+// Whether signals can take effect for a process in the given ps `stat` state. A process in
+// uninterruptible sleep (`D`, typically blocked on I/O such as a dead disk or NFS mount)
+// ignores even SIGKILL until the syscall returns, and a zombie (`Z`) has already exited.
+pub fn is_killable_state(stat: &str) -> bool {
+    !matches!(stat.trim().chars().next(), Some('D') | Some('Z'))
+}
+
+// Parse ps `etime` ([[dd-]hh:]mm:ss) into seconds. Unlike `etimes` it is supported on
+// both Linux and macOS.
+pub fn parse_etime(etime: &str) -> Option<u64> {
+    let (days, clock) = match etime.trim().split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, etime.trim()),
+    };
+    
+    let fields: Vec<u64> = clock
+        .split(':')
+        .map(|field| field.parse::<u64>().ok())
+        .collect::<Option<_>>()?;
+    let (hours, minutes, seconds) = match fields[..] {
+        [minutes, seconds] => (0, minutes, seconds),
+        [hours, minutes, seconds] => (hours, minutes, seconds),
+        _ => return None,
+    };
+    
+    Some(((days * 24 + hours) * 60 + minutes) * 60 + seconds)
+}
+
+// Parse ps `time` (cumulative CPU time) into whole seconds. Linux prints it like etime,
+// [[dd-]hh:]mm:ss; macOS adds hundredths ("1:02.37"), which are dropped.
+pub fn parse_cpu_time(time: &str) -> Option<u64> {
+    let time = time.trim();
+    let whole = match time.rsplit_once('.') {
+        Some((whole, fraction)) if !fraction.is_empty() && fraction.chars().all(|c| c.is_ascii_digit()) => whole,
+        _ => time,
+    };
+    parse_etime(whole)
+}
+
+// Compute a process's start time as an RFC 3339 UTC timestamp from the current time
+// and its elapsed running time. UTC avoids depending on the locale's lstart format.
+pub fn start_time_iso(now_secs: u64, etime: &str) -> Option<String> {
+    let started = now_secs.checked_sub(parse_etime(etime)?)?;
+    let started = chrono::DateTime::from_timestamp(i64::try_from(started).ok()?, 0)?;
+    Some(started.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+// Format a running time compactly with its two largest units: "45s", "14m", "2h 14m",
+// "3d 2h". Seconds are only shown for the first minute.
+pub fn format_uptime(seconds: u64) -> String {
+    let days = seconds / 86_400;
+    let hours = seconds % 86_400 / 3_600;
+    let minutes = seconds % 3_600 / 60;
+    
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+// Convert a ps memory column (vsz, rss), reported in KiB, to bytes
+pub fn kib_to_bytes(field: &str) -> Option<u64> {
+    field.trim().parse::<u64>().ok()?.checked_mul(1024)
+}
+
+// Read a process's shared resident memory from /proc/<pid>/statm (Linux only)
+#[cfg(target_os = "linux")]
+fn read_shared_bytes(pid: &str) -> Option<u64> {
+    let statm = std::fs::read_to_string(format!("/proc/{}/statm", pid)).ok()?;
+    parse_statm_shared(&statm, page_size())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_shared_bytes(_pid: &str) -> Option<u64> {
+    None
+}
+
+// The system page size, which statm counts in. Falls back to the common 4 KiB if getconf
+// is unavailable.
+#[cfg(target_os = "linux")]
+fn page_size() -> u64 {
+    static PAGE_SIZE: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
+    *PAGE_SIZE.get_or_init(|| {
+        Command::new("getconf")
+            .arg("PAGESIZE")
+            .audited_output()
+            .ok()
+            .and_then(|output| String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+            .unwrap_or(4096)
+    })
+}
+
+// Parse the shared page count (third field) of /proc/<pid>/statm into bytes. The fields
+// are `size resident shared text lib data dt`, all in pages.
+pub fn parse_statm_shared(statm: &str, page_size: u64) -> Option<u64> {
+    let shared = statm.split_whitespace().nth(2)?.parse::<u64>().ok()?;
+    shared.checked_mul(page_size)
+}
+
+// Read a process's OOM score and adjustment from /proc/<pid>/oom_score{,_adj} (Linux only).
+// Either is None when its file can't be read, e.g. the process exited or is hidden from us.
+#[cfg(target_os = "linux")]
+fn read_oom_scores(pid: &str) -> (Option<i32>, Option<i32>) {
+    let read = |file: &str| {
+        std::fs::read_to_string(format!("/proc/{}/{}", pid, file))
+            .map_err(|e| log_line!("[DEBUG] Cannot read /proc/{}/{}: {}", pid, file, e))
+            .ok()
+            .and_then(|contents| parse_oom_value(&contents))
+    };
+    (read("oom_score"), read("oom_score_adj"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_oom_scores(_pid: &str) -> (Option<i32>, Option<i32>) {
+    (None, None)
+}
+
+// Parse the single integer in /proc/<pid>/oom_score or oom_score_adj
+pub fn parse_oom_value(contents: &str) -> Option<i32> {
+    contents.trim().parse().ok()
+}
+
+// Find the container a process belongs to from its cgroup membership (Linux only)
+#[cfg(target_os = "linux")]
+fn read_container_id(pid: &str) -> Option<String> {
+    let cgroup = std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    let container_id = parse_container_id(&cgroup);
+    if let Some(id) = &container_id {
+        log_line!("[DEBUG] PID {} belongs to container {}", pid, id);
+    }
+    container_id
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_container_id(_pid: &str) -> Option<String> {
+    None
+}
+
+// Extract a container id from /proc/<pid>/cgroup contents. Recognizes the cgroup v1 and v2
+// layouts used by Docker, containerd, CRI-O and Podman, e.g. `0::/system.slice/docker-<id>.scope`
+// or `12:memory:/docker/<id>`.
+pub fn parse_container_id(cgroup: &str) -> Option<String> {
+    const RUNTIMES: [&str; 5] = ["docker", "containerd", "kubepods", "crio", "libpod"];
+    const PREFIXES: [&str; 4] = ["docker-", "cri-containerd-", "crio-", "libpod-"];
+    
+    for line in cgroup.lines() {
+        // Format is hierarchy-id:controllers:path
+        let path = match line.splitn(3, ':').nth(2) {
+            Some(path) => path,
+            None => continue,
+        };
+        if !RUNTIMES.iter().any(|runtime| path.contains(runtime)) {
+            continue;
+        }
+        
+        for segment in path.split('/').rev() {
+            let segment = segment.strip_suffix(".scope").unwrap_or(segment);
+            let id = PREFIXES
+                .iter()
+                .find_map(|prefix| segment.strip_prefix(prefix))
+                .unwrap_or(segment);
+            
+            if id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Some(id.to_string());
+            }
+        }
+    }
+    
+    None
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PortCheckResult {
-    pub is_occupied: bool,
-    pub processes: Vec<ProcessInfo>,
-    pub error: Option<String>,
+// Convert a ps pcpu value into core-equivalents. pcpu is a share of a single core,
+// so a process saturating four cores reports ~400; dividing by 100 gives 4.0.
+// Accepts a comma decimal separator as printed under some locales.
+pub fn parse_cpu_cores(pcpu: &str) -> Option<f64> {
+    let value: f64 = pcpu.trim().trim_end_matches('%').replace(',', ".").parse().ok()?;
+    if value.is_finite() && value >= 0.0 {
+        Some(value / 100.0)
+    } else {
+        None
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProcessSearchResult {
-    pub processes: Vec<ProcessInfo>,
-    pub error: Option<String>,
+// Label a ps pmem value explicitly as a share of physical memory, so it isn't
+// confused with pcpu (which is per-core and unbounded)
+pub fn format_memory_usage(pmem: &str) -> String {
+    format!("{}% of RAM", pmem.trim().trim_end_matches('%'))
 }
 
-// Search for processes by name
+// List a process's sockets with both endpoints and their TCP state
 #[tauri::command]
-fn search_processes_by_name(process_name: String) -> ProcessSearchResult {
-    println!("[INFO] Searching for processes with name containing: {}", process_name);
+fn get_connections(state: State<'_, AppState>, pid: String) -> Result<Vec<Connection>, ProcessError> {
+    log_line!("[INFO] Getting connections for PID: {}", pid);
     
-    if process_name.trim().is_empty() {
-        return ProcessSearchResult {
-            processes: vec![],
-            error: Some("Process name cannot be empty".to_string()),
-        };
-    }
+    validate_kill_pid(&pid)?;
     
-    // Use ps command to search for processes by name
-    // -A: show all processes, -o: specify output format
-    let ps_args = vec!["-A", "-o", "pid=,comm="];
-    println!("[DEBUG] Executing command: ps {}", ps_args.join(" "));
+    // -a ANDs the selections, otherwise lsof lists the PID's files *or* every socket
+    let lsof_args = vec!["-a", "-p", &pid, "-iTCP", "-iUDP", "-P", "-n"];
+    log_line!("[DEBUG] Executing command: lsof {}", lsof_args.join(" "));
     
-    let output = Command::new("ps")
-        .args(&ps_args)
-        .output();
+    let output = state.tools().lsof()
+        .args(&lsof_args)
+        .audited_output()
+        .map_err(|e| {
+            log_line!("[ERROR] Failed to execute lsof command: {}", e);
+            ProcessError::command_failed(format!("Failed to execute lsof: {}", e))
+        })?;
     
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                println!("[DEBUG] ps command successful, output length: {} characters", output_str.len());
-                
-                let processes = parse_ps_output(&output_str, &process_name);
-                println!("[INFO] Found {} process(es) matching name '{}'", processes.len(), process_name);
-                
-                ProcessSearchResult {
-                    processes,
-                    error: None,
-                }
-            } else {
-                let error_str = String::from_utf8_lossy(&output.stderr);
-                println!("[ERROR] ps command failed with status: {}, stderr: {}", 
-                         output.status, error_str);
-                
-                ProcessSearchResult {
-                    processes: vec![],
-                    error: Some(format!("Failed to execute ps command: {}", error_str)),
-                }
-            }
-        }
-        Err(e) => {
-            println!("[ERROR] Failed to execute ps command: {}", e);
-            ProcessSearchResult {
-                processes: vec![],
-                error: Some(format!("Failed to execute ps command: {}", e)),
-            }
-        }
+    let error_str = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() && !error_str.trim().is_empty() {
+        log_line!("[ERROR] lsof command failed with status: {}, stderr: {}", output.status, error_str);
+        return Err(ProcessError::from_output(format!("Failed to execute lsof: {}", error_str.trim()), &output));
     }
+    
+    // A non-zero status with no stderr means the process has no sockets
+    let connections = parse_connections(&String::from_utf8_lossy(&output.stdout), &pid);
+    log_line!("[INFO] Found {} connection(s) for PID {}", connections.len(), pid);
+    Ok(connections)
 }
 
+// List every TCP and UDP socket on the system with its owning process
+#[tauri::command]
+fn list_connections(state: State<'_, AppState>) -> Result<Vec<ConnectionInfo>, ProcessError> {
+    log_line!("[INFO] Listing all connections");
+    
+    let connections = fetch_connections(&state.tools())?;
+    log_line!("[INFO] Found {} connection(s)", connections.len());
+    Ok(connections)
+}
 
-
-// Check if a port is occupied and return process information
+// Quick security snapshot: listener counts by protocol and by what they are bound to
 #[tauri::command]
-fn check_port(port: String) -> PortCheckResult {
-    println!("[INFO] Starting port check for port: {}", port);
+fn port_summary(state: State<'_, AppState>) -> Result<PortSummary, ProcessError> {
+    log_line!("[INFO] Summarizing listening ports");
     
-    let port_num = match u16::from_str(&port) {
-        Ok(p) => {
-            println!("[DEBUG] Port number parsed successfully: {}", p);
-            p
-        },
-        Err(e) => {
-            println!("[ERROR] Invalid port number '{}': {}", port, e);
-            return PortCheckResult {
-                is_occupied: false,
-                processes: vec![],
-                error: Some("Invalid port number".to_string()),
-            };
-        }
-    };
+    let summary = summarize_ports(&fetch_connections(&state.tools())?);
+    log_line!("[INFO] Port summary: {} TCP, {} UDP listener(s), {} on all interfaces", 
+             summary.tcp_listeners, summary.udp_listeners, summary.wildcard);
+    Ok(summary)
+}
 
-    // Use lsof to check port usage - works on macOS and Linux
-    // -sTCP:LISTEN only shows processes in LISTEN state to avoid duplicates
+// Run one lsof over all TCP and UDP sockets
+fn fetch_connections(tools: &ToolPaths) -> Result<Vec<ConnectionInfo>, ProcessError> {
+    fetch_connections_with(tools, &["-iTCP", "-iUDP", "-P", "-n"])
+}
+
+fn fetch_connections_with(tools: &ToolPaths, lsof_args: &[&str]) -> Result<Vec<ConnectionInfo>, ProcessError> {
+    log_line!("[DEBUG] Executing command: lsof {}", lsof_args.join(" "));
+    
+    let output = tools.lsof()
+        .args(lsof_args)
+        .audited_output()
+        .map_err(|e| {
+            log_line!("[ERROR] Failed to execute lsof command: {}", e);
+            ProcessError::command_failed(format!("Failed to execute lsof: {}", e))
+        })?;
+    
+    let error_str = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() && !error_str.trim().is_empty() {
+        log_line!("[ERROR] lsof command failed with status: {}, stderr: {}", output.status, error_str);
+        return Err(ProcessError::from_output(format!("Failed to execute lsof: {}", error_str.trim()), &output));
+    }
+    
+    Ok(parse_connection_info(&String::from_utf8_lossy(&output.stdout)))
+}
+
+// The listeners of a port as CSV, for pasting into spreadsheets and docs
+#[tauri::command]
+fn check_port_csv(state: State<'_, AppState>, port: String) -> Result<String, ProcessError> {
+    log_line!("[INFO] Checking port {} for CSV output", port);
+    
+    let port_num = u16::from_str(port.trim()).map_err(|_| ProcessError::InvalidPort(port.clone()))?;
     let port_arg = format!(":{}", port_num);
-    let lsof_args = vec!["-i", &port_arg, "-P", "-n", "-sTCP:LISTEN"];
-    println!("[DEBUG] Executing command: lsof {}", lsof_args.join(" "));
+    let connections = fetch_connections_with(&state.tools(), &["-i", &port_arg, "-P", "-n", "-sTCP:LISTEN"])?;
     
-    let output = Command::new("lsof")
-        .args(&lsof_args)
-        .output();
+    log_line!("[INFO] Found {} socket(s) on port {}", connections.len(), port_num);
+    Ok(connections_to_csv(&connections))
+}
 
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                println!("[DEBUG] lsof command successful, output length: {} characters", output_str.len());
-                println!("[DEBUG] lsof raw output:\n{}", output_str);
-                
-                let processes = parse_lsof_output(&output_str, &port);
-                println!("[INFO] Found {} LISTEN processes using port {}", processes.len(), port);
-                
-                for process in &processes {
-                    println!("[DEBUG] Process found - PID: {}, Name: {}, Port: {}", 
-                             process.pid, process.name, process.port);
-                }
-                
-                PortCheckResult {
-                    is_occupied: !processes.is_empty(),
-                    processes,
-                    error: None,
-                }
-            } else {
-                let error_str = String::from_utf8_lossy(&output.stderr);
-                println!("[DEBUG] lsof command failed with status: {}, stderr: {}", 
-                         output.status, error_str);
-                println!("[INFO] Port {} appears to be available (no processes found)", port);
-                
-                PortCheckResult {
-                    is_occupied: false,
-                    processes: vec![],
-                    error: None,
-                }
-            }
+// Render connections as CSV with a `pid,name,port,protocol,address` header. IPv4 and IPv6
+// sockets of one listener that give the same row are written once.
+pub fn connections_to_csv(connections: &[ConnectionInfo]) -> String {
+    let mut csv = String::from("pid,name,port,protocol,address\n");
+    let mut written = HashSet::new();
+    
+    for connection in connections {
+        let port = connection.port.map(|port| port.to_string()).unwrap_or_default();
+        let row = format!("{},{},{},{},{}\n", 
+                          csv_escape(&connection.pid), 
+                          csv_escape(&connection.name), 
+                          port, 
+                          csv_escape(&connection.protocol), 
+                          csv_escape(&connection.address));
+        if written.insert(row.clone()) {
+            csv.push_str(&row);
         }
-        Err(e) => {
-            println!("[ERROR] Failed to execute lsof command: {}", e);
-            PortCheckResult {
-                is_occupied: false,
-                processes: vec![],
-                error: Some(format!("Failed to execute lsof: {}", e)),
-            }
-        },
     }
+    
+    csv
 }
 
-// Kill a process by PID using SIGKILL signal (force kill)
-#[tauri::command]
-fn kill_process(pid: String) -> Result<String, String> {
-    kill_process_with_signal(pid, true)
+// Listener counts from `port_summary`. Each listener falls into exactly one bind scope.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortSummary {
+    pub tcp_listeners: usize,
+    pub udp_listeners: usize,
+    pub loopback: usize, // Bound to 127.0.0.0/8 or ::1, only reachable from this machine
+    pub wildcard: usize, // Bound to all interfaces (`*`, 0.0.0.0, ::), exposed to the network
+    pub specific: usize, // Bound to one particular address
 }
 
-// Kill a process by PID using SIGTERM signal (graceful kill)
-#[tauri::command]
-fn graceful_kill_process(pid: String) -> Result<String, String> {
-    kill_process_with_signal(pid, false)
+// Where a listener is bound, judged from its local address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindScope {
+    Loopback,
+    Wildcard,
+    Specific,
 }
 
-// Internal function to kill process with specified signal
-pub fn kill_process_with_signal(pid: String, force: bool) -> Result<String, String> {
-    let signal_type = if force { "SIGKILL (-9)" } else { "SIGTERM (-15)" };
-    println!("[INFO] Attempting to {} process with PID: {} using {}", 
-             if force { "force kill" } else { "gracefully terminate" }, pid, signal_type);
-    
-    // Validate PID format
-    if let Err(e) = pid.parse::<u32>() {
-        println!("[ERROR] Invalid PID format '{}': {}", pid, e);
-        return Err(format!("Invalid PID format: {}", pid));
+pub fn bind_scope(address: &str) -> BindScope {
+    if address == "*" {
+        return BindScope::Wildcard;
     }
     
-    let signal_arg = if force { "-9" } else { "-15" };
-    println!("[DEBUG] Executing kill {} command for PID: {}", signal_arg, pid);
-    
-    let output = Command::new("kill")
-        .arg(signal_arg)
-        .arg(&pid)
-        .output();
+    match address.parse::<std::net::IpAddr>() {
+        Ok(ip) if ip.is_unspecified() => BindScope::Wildcard,
+        Ok(ip) if ip.is_loopback() => BindScope::Loopback,
+        Err(_) if address.eq_ignore_ascii_case("localhost") => BindScope::Loopback,
+        _ => BindScope::Specific,
+    }
+}
 
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                let action = if force { "force killed" } else { "gracefully terminated" };
-                println!("[INFO] Successfully {} process with PID: {}", action, pid);
-                Ok(format!("Process {} {} successfully", pid, action))
-            } else {
-                let error_msg = String::from_utf8_lossy(&output.stderr);
-                println!("[ERROR] Failed to {} process {}: status={}, stderr='{}'", 
-                         if force { "force kill" } else { "gracefully terminate" }, 
-                         pid, output.status, error_msg);
-                Err(format!("Failed to {} process {}: {}", 
-                           if force { "force kill" } else { "gracefully terminate" }, 
-                           pid, error_msg))
-            }
+// Count listeners: TCP sockets in LISTEN and bound UDP sockets. Connected sockets
+// (established TCP, UDP without a port) aren't listeners and are left out.
+pub fn summarize_ports(connections: &[ConnectionInfo]) -> PortSummary {
+    let mut summary = PortSummary::default();
+    
+    for connection in connections.iter().filter(|c| c.port.is_some()) {
+        match connection.protocol.as_str() {
+            "TCP" if connection.state.as_deref() == Some("LISTEN") => summary.tcp_listeners += 1,
+            "UDP" => summary.udp_listeners += 1,
+            _ => continue,
+        }
+        
+        match bind_scope(&connection.address) {
+            BindScope::Loopback => summary.loopback += 1,
+            BindScope::Wildcard => summary.wildcard += 1,
+            BindScope::Specific => summary.specific += 1,
         }
-        Err(e) => {
-            println!("[ERROR] Failed to execute kill command for PID {}: {}", pid, e);
-            Err(format!("Failed to execute kill command: {}", e))
-        },
     }
+    
+    summary
 }
 
-// Parse ps output to extract process information for name search
-// ps output format: PID COMMAND
-pub fn parse_ps_output(output: &str, search_name: &str) -> Vec<ProcessInfo> {
-    println!("[DEBUG] Parsing ps output for search term: '{}', total lines: {}", search_name, output.lines().count());
-    let mut processes = Vec::new();
-    let search_name_lower = search_name.to_lowercase().trim().to_string();
+// Exact output of a diagnostic tool run, for bug reports about parsing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawCommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub code: Option<i32>, // None if the tool was killed by a signal
+}
+
+// Tools `raw_command` may run; all of them only read system state
+pub const RAW_COMMAND_TOOLS: [&str; 3] = ["lsof", "ps", "ss"];
+pub const RAW_COMMAND_MAX_ARGS: usize = 32;
+pub const RAW_COMMAND_MAX_ARG_LEN: usize = 256;
+
+// Run lsof, ps or ss with the given arguments and return exactly what it printed, so users
+// can capture what the app sees without a shell. The configured tool paths are honoured.
+#[tauri::command]
+fn raw_command(state: State<'_, AppState>, tool: String, args: Vec<String>) -> Result<RawCommandOutput, ProcessError> {
+    log_line!("[INFO] Running raw command: {} {}", tool, args.join(" "));
     
-    // Return empty result for empty or whitespace-only search terms
-    if search_name_lower.is_empty() {
-        println!("[DEBUG] Empty search term provided, returning no matches");
-        return processes;
+    validate_raw_command(&tool, &args)?;
+    
+    let tools = state.tools();
+    let mut command = match tool.as_str() {
+        "lsof" => tools.lsof(),
+        "ps" => tools.ps(),
+        _ => Command::new(&tool),
+    };
+    
+    let output = command.args(&args).audited_output().map_err(|e| {
+        log_line!("[ERROR] Failed to execute {} command: {}", tool, e);
+        ProcessError::command_failed(format!("Failed to execute {}: {}", tool, e))
+    })?;
+    
+    // A non-zero exit is part of what the user wants to see, not an error
+    Ok(RawCommandOutput {
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        code: output.status.code(),
+    })
+}
+
+// Check a raw command against the tool allowlist and sanitize its arguments. Arguments are
+// passed straight to the tool without a shell, so the checks only keep out control
+// characters and the few options that would modify state or never return.
+pub fn validate_raw_command(tool: &str, args: &[String]) -> Result<(), ProcessError> {
+    let reject = |reason: String| {
+        log_line!("[ERROR] Rejected raw command '{}': {}", tool, reason);
+        Err(ProcessError::InvalidCommand { tool: tool.to_string(), reason })
+    };
+    
+    if !RAW_COMMAND_TOOLS.contains(&tool) {
+        return reject(format!("only {} can be run", RAW_COMMAND_TOOLS.join(", ")));
+    }
+    if args.len() > RAW_COMMAND_MAX_ARGS {
+        return reject(format!("at most {} arguments are allowed", RAW_COMMAND_MAX_ARGS));
     }
     
-    for (line_num, line) in output.lines().enumerate() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
+    // lsof -r/+r repeats forever; ss -K kills sockets and -D writes a file. The second set
+    // lists the short options that take a value, which may be attached (`-sTCP:LISTEN`).
+    let (forbidden_letters, value_letters, forbidden_long): (&str, &str, &[&str]) = match tool {
+        "lsof" => ("r", "AcdDeFgikLmopsSTuxz", &[]),
+        "ss" => ("KD", "AfFN", &["--kill", "--diag"]),
+        _ => ("", "", &[]),
+    };
+    
+    for arg in args {
+        if arg.len() > RAW_COMMAND_MAX_ARG_LEN {
+            return reject(format!("argument longer than {} characters", RAW_COMMAND_MAX_ARG_LEN));
+        }
+        if arg.chars().any(char::is_control) {
+            return reject(format!("argument {:?} contains control characters", arg));
+        }
+        // Long options may carry their value after `=`
+        let uses = |option: &str| arg == option || arg.starts_with(&format!("{}=", option));
+        if let Some(option) = forbidden_long.iter().find(|option| uses(option)) {
+            return reject(format!("option {} is not allowed", option));
+        }
         
-        if parts.len() >= 2 {
-            let pid = parts[0];
-            let command = parts[1];
-            
-            // Check if the command name contains the search term (case-insensitive)
-            if command.to_lowercase().contains(&search_name_lower) {
-                println!("[DEBUG] Found matching process - PID: '{}', Name: '{}'", pid, command);
-                
-                processes.push(ProcessInfo {
-                    pid: pid.to_string(),
-                    name: command.to_string(),
-                    port: "Unknown".to_string(), // Port is unknown for name-based search
-                });
+        // Short options can be grouped (`-tK`), so every letter is checked up to the first
+        // one whose value makes up the rest of the argument
+        if arg.starts_with("--") {
+            continue;
+        }
+        let Some(letters) = arg.strip_prefix('-').or_else(|| arg.strip_prefix('+')) else {
+            continue;
+        };
+        for letter in letters.chars() {
+            if forbidden_letters.contains(letter) {
+                return reject(format!("option {}{} is not allowed", &arg[..1], letter));
+            }
+            if value_letters.contains(letter) {
+                break;
             }
-        } else if !line.trim().is_empty() {
-            println!("[WARN] Skipping malformed line {}: not enough parts ({})", 
-                     line_num + 1, parts.len());
         }
     }
     
-    println!("[INFO] Successfully parsed {} matching processes from ps output", processes.len());
-    processes
+    Ok(())
 }
 
-// Parse lsof output to extract process information
-// lsof output format: COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
-// Since we use -sTCP:LISTEN, all results are already LISTEN processes
-pub fn parse_lsof_output(output: &str, port: &str) -> Vec<ProcessInfo> {
-    println!("[DEBUG] Parsing lsof output, total lines: {}", output.lines().count());
-    let mut processes = Vec::new();
-    let mut seen_pids = std::collections::HashSet::new();
+// Parse lsof socket rows into `ConnectionInfo`, one per distinct socket. Address and
+// port come from the local side of the NAME column.
+pub fn parse_connection_info(output: &str) -> Vec<ConnectionInfo> {
+    let mut connections = Vec::new();
     
-    // Skip the header line and process each line
     for (line_num, line) in output.lines().skip(1).enumerate() {
         let parts: Vec<&str> = line.split_whitespace().collect();
-        println!("[DEBUG] Line {}: {} parts - {}", line_num + 1, parts.len(), line);
         
-        if parts.len() >= 2 {
-            let name = parts[0].to_string();
-            let pid = parts[1].to_string();
-            
-            println!("[DEBUG] Extracted LISTEN process - Name: '{}', PID: '{}'", name, pid);
-            
-            // Check if we've already seen this PID (deduplication)
-            if !seen_pids.contains(&pid) {
-                seen_pids.insert(pid.clone());
-                let pid_for_log = pid.clone(); // Clone for logging before moving
-                processes.push(ProcessInfo {
-                    pid,
-                    name,
-                    port: port.to_string(),
-                });
-                println!("[DEBUG] Added unique process with PID: {}", pid_for_log);
-            } else {
-                println!("[DEBUG] Skipping duplicate PID: {} (IPv4/IPv6 duplicate)", pid);
+        let Some(name_index) = row_name_index(&parts) else {
+            if !line.trim().is_empty() {
+                log_line!("[WARN] Skipping malformed line {}: no NAME column in {} parts", 
+                         line_num + 1, parts.len());
             }
-        } else {
-            println!("[WARN] Skipping malformed line {}: not enough parts ({})", 
-                     line_num + 1, parts.len());
+            continue;
+        };
+        
+        let name = parts[name_index];
+        let local = name.split("->").next().unwrap_or(name);
+        let (host, port) = match split_host_port(local) {
+            Some((host, port)) => (host, port.parse::<u16>().ok()),
+            None => (local, None),
+        };
+        let (address, zone) = split_zone(host);
+        
+        let connection = ConnectionInfo {
+            pid: parts[1].to_string(),
+            name: normalize_process_name(parts[0]),
+            user: parts[2].to_string(),
+            protocol: parts[name_index - 1].to_string(),
+            address: address.to_string(),
+            port,
+            state: parts.get(name_index + 1).map(|state| state.trim_matches(|c| c == '(' || c == ')').to_string()),
+            fd: Some(parts[3].to_string()),
+            fd_type: Some(parts[4].to_string()),
+            zone: zone.map(str::to_string),
+        };
+        
+        if !connections.contains(&connection) {
+            connections.push(connection);
         }
     }
     
-    println!("[INFO] Successfully parsed {} unique LISTEN processes from lsof output (after deduplication)", processes.len());
-    processes
+    connections
 }
 
-// Get detailed process information using ps command
-#[tauri::command]
-fn get_process_detail(pid: String) -> Result<ProcessDetail, String> {
-    println!("[INFO] Getting detailed information for process PID: {}", pid);
+// Parse lsof socket rows for one PID into local/remote endpoint pairs. The optional
+// trailing column holds the TCP state, e.g. `(LISTEN)` or `(ESTABLISHED)`.
+pub fn parse_connections(output: &str, pid: &str) -> Vec<Connection> {
+    let mut connections = Vec::new();
     
-    // Validate PID format
-    if let Err(e) = pid.parse::<u32>() {
-        println!("[ERROR] Invalid PID format '{}': {}", pid, e);
-        return Err(format!("Invalid PID format: {}", pid));
+    for line in output.lines().skip(1) {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let Some(name_index) = row_name_index(&parts).filter(|_| parts[1] == pid) else {
+            continue;
+        };
+        
+        let name = parts[name_index];
+        let (local, remote) = match name.split_once("->") {
+            Some((local, remote)) => (local.to_string(), Some(remote.to_string())),
+            None => (name.to_string(), None),
+        };
+        
+        let connection = Connection {
+            local,
+            remote,
+            state: parts.get(name_index + 1).map(|state| state.trim_matches(|c| c == '(' || c == ')').to_string()),
+            protocol: parts[name_index - 1].to_string(),
+        };
+        
+        if !connections.contains(&connection) {
+            connections.push(connection);
+        }
     }
     
-    // Use ps command to get detailed process information
-    // We'll use separate ps calls for better field parsing
-    println!("[DEBUG] Getting basic process info for PID: {}", pid);
-    
-    // Get basic info: pid, command name, user, full command
-    let basic_args = vec!["-p", &pid, "-o", "pid=,comm=,user=,args="];
-    let basic_output = Command::new("ps").args(&basic_args).output();
-    
-    // Get resource usage: pid, pcpu, pmem
-    let resource_args = vec!["-p", &pid, "-o", "pid=,pcpu=,pmem="];
-    let resource_output = Command::new("ps").args(&resource_args).output();
-    
-    // Get start time: pid, lstart
-    let time_args = vec!["-p", &pid, "-o", "pid=,lstart="];
-    let time_output = Command::new("ps").args(&time_args).output();
-
-    match (basic_output, resource_output, time_output) {
-        (Ok(basic), Ok(resource), Ok(time)) => {
-            if basic.status.success() && resource.status.success() && time.status.success() {
-                let basic_str = String::from_utf8_lossy(&basic.stdout);
-                let resource_str = String::from_utf8_lossy(&resource.stdout);
-                let time_str = String::from_utf8_lossy(&time.stdout);
-                
-                println!("[DEBUG] Basic info: {}", basic_str.trim());
-                println!("[DEBUG] Resource info: {}", resource_str.trim());
-                println!("[DEBUG] Time info: {}", time_str.trim());
-                
-                // Parse basic info
-                let basic_parts: Vec<&str> = basic_str.trim().split_whitespace().collect();
-                if basic_parts.len() >= 4 {
-                    let pid_parsed = basic_parts[0];
-                    let name = basic_parts[1];
-                    let user = basic_parts[2];
-                    let command = basic_parts[3..].join(" ");
-                    
-                    // Parse resource info
-                    let resource_parts: Vec<&str> = resource_str.trim().split_whitespace().collect();
-                    let (cpu_usage, memory_usage) = if resource_parts.len() >= 3 {
-                        (
-                            Some(format!("{}%", resource_parts[1])),
-                            Some(format!("{}%", resource_parts[2]))
-                        )
-                    } else {
-                        (None, None)
-                    };
-                    
-                    // Parse start time (skip PID, take the rest)
-                    let start_time = if let Some(first_space) = time_str.trim().find(' ') {
-                        Some(time_str.trim()[first_space + 1..].to_string())
-                    } else {
-                        None
-                    };
-                    
-                    // Try to get port information from lsof
-                    let port_info = get_process_port(&pid);
-                    
-                    let detail = ProcessDetail {
-                        pid: pid_parsed.to_string(),
-                        name: name.to_string(),
-                        port: port_info.unwrap_or_else(|| "Unknown".to_string()),
-                        user: Some(user.to_string()),
-                        command: Some(command),
-                        cpu_usage,
-                        memory_usage,
-                        start_time,
-                    };
-                    
-                    println!("[INFO] Successfully retrieved detailed information for PID: {}", pid);
-                    Ok(detail)
-                } else {
-                    println!("[ERROR] Unable to parse basic process info for PID: {}", pid);
-                    Err("Unable to parse basic process info".to_string())
-                }
-            } else {
-                println!("[ERROR] One or more ps commands failed for PID: {}", pid);
-                Err(format!("Failed to get process information for PID: {}", pid))
-            }
-        }
-        _ => {
-            println!("[ERROR] Failed to execute ps commands for PID: {}", pid);
-            Err("Failed to execute ps commands".to_string())
+    connections
+}
+
+// Result of looking up a process's ports, keeping "no ports" apart from "not allowed to look"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortLookup {
+    NoPorts,
+    PermissionDenied,
+    Ports(Vec<String>),
+}
+
+impl PortLookup {
+    // Text for `ProcessDetail.port`
+    pub fn describe(&self) -> String {
+        match self {
+            PortLookup::NoPorts => "Unknown".to_string(),
+            PortLookup::PermissionDenied => "Requires privileges".to_string(),
+            PortLookup::Ports(ports) => ports.join(", "),
         }
     }
 }
 
 // Helper function to get all port information for a specific process
-fn get_process_port(pid: &str) -> Option<String> {
-    let lsof_args = vec!["-p", pid, "-P", "-n", "-iTCP"];
+fn get_process_port(tools: &ToolPaths, pid: &str) -> PortLookup {
+    // -a ANDs the selections, otherwise lsof lists the PID's files *or* every TCP socket
+    let lsof_args = vec!["-a", "-p", pid, "-P", "-n", "-iTCP"];
     
-    let output = Command::new("lsof")
-        .args(&lsof_args)
-        .output();
-    
-    if let Ok(output) = output {
-        if output.status.success() {
+    match tools.lsof().args(&lsof_args).audited_output() {
+        Ok(output) => {
             let output_str = String::from_utf8_lossy(&output.stdout);
-            println!("[DEBUG] lsof output for PID {}:\n{}", pid, output_str);
-            
-            let mut ports = Vec::new();
+            let error_str = String::from_utf8_lossy(&output.stderr);
+            log_line!("[DEBUG] lsof output for PID {}:\n{}", pid, output_str);
             
-            // Parse lsof output to find all port information
-            for line in output_str.lines().skip(1) {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 9 {
-                    let name_field = parts[8];
-                    println!("[DEBUG] Checking name field: {}", name_field);
-                    
-                    // Look for patterns like *:port, localhost:port, or IP:port
-                    if name_field.contains(':') {
-                        if let Some(port_part) = name_field.split(':').last() {
-                            // Check if it's a number (port) and not a service name
-                            if let Ok(port_num) = port_part.parse::<u16>() {
-                                let port_str = port_num.to_string();
-                                // Avoid duplicates
-                                if !ports.contains(&port_str) {
-                                    ports.push(port_str);
-                                    println!("[DEBUG] Found port: {}", port_num);
-                                }
-                            }
-                        }
-                    }
-                }
+            let lookup = interpret_port_lookup(pid, &output_str, &error_str);
+            match &lookup {
+                PortLookup::Ports(ports) => log_line!("[INFO] Found {} port(s) for PID {}: {}", ports.len(), pid, ports.join(", ")),
+                PortLookup::PermissionDenied => log_line!("[WARN] Not permitted to list ports of PID {}", pid),
+                PortLookup::NoPorts => log_line!("[INFO] No ports found for PID {}", pid),
             }
-            
-            if !ports.is_empty() {
-                let result = ports.join(", ");
-                println!("[INFO] Found {} port(s) for PID {}: {}", ports.len(), pid, result);
-                return Some(result);
+            lookup
+        }
+        Err(e) => {
+            log_line!("[ERROR] Failed to execute lsof command: {}", e);
+            PortLookup::NoPorts
+        }
+    }
+}
+
+// Interpret lsof's output for a single PID. Ports found win over warnings; with no ports,
+// a permission error on stderr means the answer is unknown rather than "none". Rows of
+// other processes are ignored, in case lsof listed more than was asked for.
+pub fn interpret_port_lookup(pid: &str, stdout: &str, stderr: &str) -> PortLookup {
+    let mut ports = Vec::new();
+    
+    // Parse lsof output to find all port information
+    for line in stdout.lines().skip(1) {
+        if line.split_whitespace().nth(1) != Some(pid) {
+            continue;
+        }
+        if let Some(port_num) = find_row_port(line) {
+            let port_str = port_num.to_string();
+            // Avoid duplicates
+            if !ports.contains(&port_str) {
+                ports.push(port_str);
+                log_line!("[DEBUG] Found port: {}", port_num);
             }
         }
     }
     
-    println!("[INFO] No ports found for PID {}", pid);
-    None
+    if !ports.is_empty() {
+        return PortLookup::Ports(ports);
+    }
+    
+    let stderr = stderr.to_lowercase();
+    if stderr.contains("permission denied") || stderr.contains("not permitted") {
+        PortLookup::PermissionDenied
+    } else {
+        PortLookup::NoPorts
+    }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    if let Ok(mode) = std::env::var(LOG_MODE_ENV) {
+        match serde_json::from_value::<LogMode>(serde_json::Value::String(mode.to_lowercase())) {
+            Ok(mode) => set_log_mode(mode),
+            Err(_) => log_line!("[WARN] Ignoring unknown {}={}, expected text, json or quiet", LOG_MODE_ENV, mode),
+        }
+    }
+    
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .manage(AppState::default())
         .invoke_handler(tauri::generate_handler![
             check_port, 
+            check_unix_socket,
+            processes_using_file,
             kill_process, 
             graceful_kill_process, 
+            send_signal,
+            smart_kill,
+            which_alive,
+            process_name_counts,
+            port_service_name,
+            clear_caches,
+            kill_process_group,
+            set_process_note,
+            wait_for_exit,
+            get_process_details,
+            kill_async,
+            cancel_kill_async,
+            get_process_env,
+            diff_snapshots,
+            kill_matching,
+            get_descendants,
+            check_port_csv,
+            can_kill,
+            set_kill_rate_limit,
+            get_kill_rate_limit,
+            scan_dev_ports,
+            get_command_audit,
+            kill_user_processes,
+            verify_port_owner,
+            restart_process,
             get_process_detail, 
-            search_processes_by_name
+            search_processes_by_name,
+            kill_process_tree,
+            kill_processes,
+            kill_by_port,
+            list_listening_ports,
+            all_process_ports,
+            get_connections,
+            inspect_port,
+            reap_zombies,
+            snapshot,
+            export_processes,
+            get_kill_history,
+            set_tool_paths,
+            get_tool_paths,
+            set_kill_allowlist,
+            get_kill_allowlist,
+            lookup_pid,
+            watch_port,
+            stop_watch_port,
+            kill_port_range,
+            kill_by_path,
+            list_connections,
+            port_summary,
+            raw_command
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                abort_watches(app);
+            }
+        });
 }
 
@@ -10,6 +10,10 @@
 // - Unicode support and special characters
 // - Real-world scenarios
 
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
 use kill_process_lib::{
     parse_lsof_output, 
     parse_ps_output,
@@ -17,7 +21,167 @@ use kill_process_lib::{
     ProcessInfo, 
     ProcessDetail, 
     PortCheckResult,
-    ProcessSearchResult
+    ProcessSearchResult,
+    parse_ppid_table,
+    build_children_map,
+    tree_kill_order,
+    KillResult,
+    parse_listening_ports,
+    build_snapshot,
+    extract_port,
+    split_host_port,
+    kill_pids_with_progress,
+    KillProgress,
+    export_snapshot,
+    processes_to_csv,
+    csv_escape,
+    ProcessSnapshot,
+    ProcessError,
+    KillHistory,
+    KillHistoryEntry,
+    KILL_HISTORY_CAPACITY,
+    validate_kill_pid,
+    is_pid_query,
+    parse_pid_lookup_output,
+    normalize_process_name,
+    find_match_range,
+    parse_ps_stream,
+    PortPidCache,
+    SearchOptions,
+    TermCombinator,
+    parse_container_id,
+    exclude_listeners,
+    parse_cpu_cores,
+    format_memory_usage,
+    group_ports_by_process,
+    ToolPaths,
+    smart_kill_with,
+    SmartKillStatus,
+    recover_full_name,
+    is_kernel_thread,
+    parse_connections,
+    verify_expected_name,
+    parse_port_pattern,
+    is_port_pattern,
+    interpret_port_lookup,
+    PortLookup,
+    assemble_port_details,
+    parse_lsof_rows,
+    MatchMode,
+    count_ps_stream,
+    merge_search_results,
+    find_zombie_children,
+    parse_etime,
+    start_time_iso,
+    WatchHandle,
+    WatchRegistry,
+    check_allowlist,
+    is_killable_state,
+    kill_port_range_with,
+    ConnectionInfo,
+    parse_connection_info,
+    fuzzy_score,
+    FUZZY_THRESHOLD,
+    ResourceTotals,
+    sum_resource_rows,
+    parse_signal,
+    SIGNALS,
+    validate_raw_command,
+    split_leading_fields,
+    parse_ps_user,
+    summarize_ports,
+    bind_scope,
+    BindScope,
+    PortSummary,
+    filter_by_exe_path,
+    protected_pid_reason,
+    format_uptime,
+    parse_unix_socket_output,
+    split_command_line,
+    poll_for_exit,
+    port_binding,
+    render_log_line,
+    LogMode,
+    LogOutput,
+    set_log_mode,
+    log_mode,
+    parse_lsof_file_output,
+    parse_alive_pids,
+    compile_globs,
+    parse_user_ids,
+    tally_process_names,
+    find_row_port,
+    parse_cpu_time,
+    check_min_age,
+    check_confirmation,
+    kib_to_bytes,
+    parse_statm_shared,
+    parse_services,
+    resolve_service_name,
+    AppState,
+    process_group_kill_args,
+    fold_case,
+    ProcessNotes,
+    parse_process_detail,
+    filter_to_listeners,
+    already_gone,
+    SMART_KILL_POLL_MS,
+    parse_process_details,
+    group_ports_by_pid,
+    plan_detail_fields,
+    apply_ps_query,
+    PsQuery,
+    kill_async_with,
+    KillCompleteEvent,
+    timed_output,
+    elapsed_ms,
+    parse_environ,
+    parse_ps_env,
+    redact_env,
+    REDACTED_VALUE,
+    DEFAULT_MAX_OUTPUT_BYTES,
+    read_capped,
+    capped_output,
+    split_zone,
+    compute_snapshot_diff,
+    KillQuery,
+    parse_kill_candidates,
+    resolve_kill_query,
+    list_descendants,
+    connections_to_csv,
+    kill_precheck,
+    KillPrecheck,
+    KillTargetState,
+    AdaptiveInterval,
+    watch_intervals,
+    WATCH_PORT_FAST_INTERVAL_MS,
+    WATCH_PORT_SLOW_INTERVAL_MS,
+    WATCH_PORT_MIN_INTERVAL_MS,
+    process_basename,
+    kill_history_entry,
+    KillRateLimiter,
+    filter_dev_ports,
+    DEV_PORTS,
+    SCHEMA_VERSION,
+    SnapshotDiff,
+    KillQueryResult,
+    KillCandidate,
+    port_watch_event,
+    parse_oom_value,
+    remove_companion_files,
+    AuditedOutput,
+    command_audit,
+    find_whole_word,
+    resolve_user_target,
+    parse_user_processes,
+    is_privileged_port,
+    IpFamily,
+    decode_name,
+    strict_name,
+    is_port_owner,
+    DedupKey,
+    PortKillResult,
+    ancestor_pids,
 };
 
 #[test]
@@ -97,14 +261,19 @@ node     1234 testuser   20u  IPv4 0x1234567890abcdef      0t0  TCP *:3000 (LIST
 fn test_port_check_result_structure() {
     let result = PortCheckResult {
         is_occupied: true,
+        binding: None,
         processes: vec![
             ProcessInfo {
                 pid: "1234".to_string(),
                 name: "test_process".to_string(),
                 port: "3000".to_string(),
+                ..Default::default()
             }
         ],
         error: None,
+        duration_ms: None,
+        output_truncated: false,
+        schema_version: SCHEMA_VERSION,
     };
     
     assert!(result.is_occupied);
@@ -122,8 +291,9 @@ fn test_process_detail_structure() {
         user: Some("testuser".to_string()),
         command: Some("/usr/bin/node app.js".to_string()),
         cpu_usage: Some("5.2%".to_string()),
-        memory_usage: Some("128.5%".to_string()),
+        memory_usage: Some("12.8% of RAM".to_string()),
         start_time: Some("Jan 15 10:30:00".to_string()),
+        ..Default::default()
     };
     
     assert_eq!(detail.pid, "1234");
@@ -142,9 +312,9 @@ fn test_kill_process_with_signal_validation() {
     let invalid_pids = vec!["abc", "", "12.34", "not_a_number"];
     
     for invalid_pid in invalid_pids {
-        let result = kill_process_with_signal(invalid_pid.to_string(), true);
+        let result = kill_process_with_signal(invalid_pid.to_string(), true, None, None, false, &[]);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Invalid PID format"));
+        assert!(result.unwrap_err().to_string().contains("Invalid PID format"));
     }
 }
 
@@ -362,9 +532,15 @@ fn test_process_search_result_structure() {
                 pid: "1234".to_string(),
                 name: "test_process".to_string(),
                 port: "Unknown".to_string(),
+                ..Default::default()
             }
         ],
+        total_matches: 1,
+        totals: ResourceTotals::default(),
         error: None,
+        duration_ms: None,
+        output_truncated: false,
+        schema_version: SCHEMA_VERSION,
     };
     
     assert_eq!(result.processes.len(), 1);
@@ -378,7 +554,12 @@ fn test_process_search_result_structure() {
 fn test_process_search_result_with_error() {
     let result = ProcessSearchResult {
         processes: vec![],
+        total_matches: 0,
+        totals: ResourceTotals::default(),
         error: Some("Process name cannot be empty".to_string()),
+        duration_ms: None,
+        output_truncated: false,
+        schema_version: SCHEMA_VERSION,
     };
     
     assert_eq!(result.processes.len(), 0);
@@ -484,6 +665,7 @@ fn test_process_detail_minimal_info() {
         cpu_usage: None,
         memory_usage: None,
         start_time: None,
+        ..Default::default()
     };
     
     assert_eq!(detail.pid, "1234");
@@ -505,8 +687,9 @@ fn test_process_detail_with_complex_command() {
         user: Some("testuser".to_string()),
         command: Some("/usr/local/bin/node --inspect=0.0.0.0:9229 --max-old-space-size=4096 server.js".to_string()),
         cpu_usage: Some("15.7%".to_string()),
-        memory_usage: Some("256.8%".to_string()),
+        memory_usage: Some("25.6% of RAM".to_string()),
         start_time: Some("Mon Jan 15 10:30:45 2024".to_string()),
+        ..Default::default()
     };
     
     assert_eq!(detail.pid, "1234");
@@ -543,11 +726,9 @@ fn test_kill_process_invalid_formats() {
     ];
     
     for invalid_pid in invalid_formats {
-        // Test that these would be rejected by the validation
-        if invalid_pid == "0" {
-            // PID 0 is technically valid in parse but not for kill
-            continue;
-        }
+        // Every one of these must be rejected by the kill validation
+        assert!(validate_kill_pid(invalid_pid).is_err(), "Should reject: {}", invalid_pid);
+        
         let parse_result = invalid_pid.trim().parse::<u32>();
         if invalid_pid.contains('.') || invalid_pid.contains('e') || 
            invalid_pid.contains(char::is_alphabetic) {
@@ -562,19 +743,25 @@ fn test_kill_process_invalid_formats() {
 fn test_port_check_result_comprehensive() {
     let result_with_multiple_processes = PortCheckResult {
         is_occupied: true,
+        binding: None,
         processes: vec![
             ProcessInfo {
                 pid: "1234".to_string(),
                 name: "node".to_string(),
                 port: "3000".to_string(),
+                ..Default::default()
             },
             ProcessInfo {
                 pid: "5678".to_string(),
                 name: "nginx".to_string(),
                 port: "3000".to_string(),
+                ..Default::default()
             }
         ],
         error: None,
+        duration_ms: None,
+        output_truncated: false,
+        schema_version: SCHEMA_VERSION,
     };
     
     assert!(result_with_multiple_processes.is_occupied);
@@ -591,12 +778,4310 @@ fn test_port_check_result_comprehensive() {
 fn test_port_check_result_with_error() {
     let result_with_error = PortCheckResult {
         is_occupied: false,
+        binding: None,
         processes: vec![],
         error: Some("Failed to execute lsof: command not found".to_string()),
+        duration_ms: None,
+        output_truncated: false,
+        schema_version: SCHEMA_VERSION,
     };
     
     assert!(!result_with_error.is_occupied);
     assert_eq!(result_with_error.processes.len(), 0);
     assert!(result_with_error.error.is_some());
     assert!(result_with_error.error.unwrap().contains("lsof"));
-}
\ No newline at end of file
+}
+
+// Tests for process tree killing
+
+#[test]
+fn test_build_process_tree_multiple_generations() {
+    // init -> shell(100) -> npm(200) -> node(300) -> worker(400), plus a sibling npm(201)
+    let ps_output = r#"    1     0
+  100     1
+  200   100
+  201   100
+  300   200
+  400   300
+  999     1
+"#;
+    
+    let entries = parse_ppid_table(ps_output);
+    assert_eq!(entries.len(), 7);
+    
+    let children = build_children_map(&entries);
+    assert_eq!(children.get(&100).unwrap(), &vec![200, 201]);
+    assert_eq!(children.get(&300).unwrap(), &vec![400]);
+    
+    let order = tree_kill_order(100, &children);
+    
+    // The whole subtree is included, the unrelated process is not
+    assert_eq!(order.len(), 5);
+    assert!(!order.contains(&999));
+    assert!(!order.contains(&1));
+    
+    // Deepest first, root last
+    assert_eq!(order[0], 400);
+    assert_eq!(order[1], 300);
+    assert_eq!(*order.last().unwrap(), 100);
+    
+    // Every child is killed before its parent
+    let position = |pid: u32| order.iter().position(|&p| p == pid).unwrap();
+    for (pid, ppid) in &entries {
+        if order.contains(pid) && order.contains(ppid) {
+            assert!(position(*pid) < position(*ppid), "{} should be killed before {}", pid, ppid);
+        }
+    }
+}
+
+#[test]
+fn test_tree_kill_order_leaf_process() {
+    let children = build_children_map(&parse_ppid_table("  100     1\n  200     1\n"));
+    
+    // A process without children only kills itself
+    assert_eq!(tree_kill_order(200, &children), vec![200]);
+}
+
+#[test]
+fn test_ancestor_pids_walks_up_to_init() {
+    let entries = parse_ppid_table("    1     0
+  100     1
+  200   100
+  300   200
+  999     1
+");
+    
+    assert_eq!(ancestor_pids(300, &entries), HashSet::from([300, 200, 100, 1, 0]));
+    assert_eq!(ancestor_pids(4242, &entries), HashSet::from([4242]));
+    
+    // A looping table still ends
+    let looped = [(10, 20), (20, 10)];
+    assert_eq!(ancestor_pids(10, &looped), HashSet::from([10, 20]));
+}
+
+#[test]
+fn test_parse_ppid_table_skips_malformed_lines() {
+    let ps_output = "  100     1\nbogus\n  abc   def\n\n  200   100\n";
+    
+    let entries = parse_ppid_table(ps_output);
+    
+    assert_eq!(entries, vec![(100, 1), (200, 100)]);
+}
+
+#[test]
+fn test_list_descendants_with_depth() {
+    let ps_output = r#"    1     0 launchd
+  100     1 zsh
+  200   100 npm
+  201   100 /usr/bin/vim
+  300   200 node
+  400   300 Google Chrome Helper
+  999     1 sshd
+"#;
+    
+    let descendants = list_descendants(100, ps_output);
+    
+    let tree: Vec<(&str, &str, Option<usize>)> = descendants.iter()
+        .map(|p| (p.pid.as_str(), p.name.as_str(), p.depth))
+        .collect();
+    assert_eq!(tree, vec![
+        ("200", "npm", Some(1)),
+        ("201", "vim", Some(1)),
+        ("300", "node", Some(2)),
+        ("400", "Google Chrome Helper", Some(3)),
+    ]);
+    
+    assert!(list_descendants(999, ps_output).is_empty());
+}
+
+#[test]
+fn test_list_descendants_survives_cycle() {
+    // A corrupt table where 100 and 200 are each other's parent
+    let ps_output = "  100   200 a
+  200   100 b
+  300   200 c
+";
+    
+    let descendants = list_descendants(100, ps_output);
+    
+    let pids: Vec<&str> = descendants.iter().map(|p| p.pid.as_str()).collect();
+    assert_eq!(pids, vec!["200", "300"]);
+}
+
+#[test]
+fn test_kill_result_structure() {
+    let result = KillResult {
+        pid: "1234".to_string(),
+        success: false,
+        message: "Invalid PID format: abc".to_string(),
+    };
+    
+    assert_eq!(result.pid, "1234");
+    assert!(!result.success);
+    assert!(result.message.contains("Invalid PID"));
+}
+
+// Tests for the listening-ports snapshot
+
+#[test]
+fn test_snapshot_from_single_lsof_dump() {
+    let lsof_output = r#"COMMAND   PID    USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+node     1234 testuser   20u  IPv4 0x1234567890abcdef      0t0  TCP *:3000 (LISTEN)
+node     1234 testuser   21u  IPv6 0x1234567890abcdef      0t0  TCP *:3000 (LISTEN)
+node     1234 testuser   22u  IPv4 0x1234567890abcdef      0t0  TCP 127.0.0.1:9229 (LISTEN)
+nginx    5678 www-data   10u  IPv4 0x9876543210fedcba      0t0  TCP *:8080 (LISTEN)
+"#;
+    
+    let snapshot = build_snapshot(parse_listening_ports(lsof_output), None, 1700000000000);
+    
+    assert_eq!(snapshot.timestamp, 1700000000000);
+    assert_eq!(snapshot.listening_ports.len(), 3);
+    
+    // Each entry carries the port from its own row rather than a shared label
+    assert_eq!(snapshot.listening_ports[0].pid, "1234");
+    assert_eq!(snapshot.listening_ports[0].port, "3000");
+    assert_eq!(snapshot.listening_ports[1].pid, "1234");
+    assert_eq!(snapshot.listening_ports[1].port, "9229");
+    assert_eq!(snapshot.listening_ports[2].name, "nginx");
+    assert_eq!(snapshot.listening_ports[2].port, "8080");
+}
+
+#[test]
+fn test_snapshot_filter() {
+    let lsof_output = r#"COMMAND   PID    USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+node     1234 testuser   20u  IPv4 0x1234567890abcdef      0t0  TCP *:3000 (LISTEN)
+nginx    5678 www-data   10u  IPv4 0x9876543210fedcba      0t0  TCP *:8080 (LISTEN)
+"#;
+    
+    let by_name = build_snapshot(parse_listening_ports(lsof_output), Some("NGINX"), 0);
+    assert_eq!(by_name.listening_ports.len(), 1);
+    assert_eq!(by_name.listening_ports[0].pid, "5678");
+    
+    let by_port = build_snapshot(parse_listening_ports(lsof_output), Some("3000"), 0);
+    assert_eq!(by_port.listening_ports.len(), 1);
+    assert_eq!(by_port.listening_ports[0].name, "node");
+    
+    // A blank filter keeps everything
+    let blank = build_snapshot(parse_listening_ports(lsof_output), Some("  "), 0);
+    assert_eq!(blank.listening_ports.len(), 2);
+}
+
+#[test]
+fn test_diff_snapshots() {
+    let before = build_snapshot(parse_listening_ports(r#"COMMAND   PID    USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+node     1234 testuser   20u  IPv4 0x1234567890abcdef      0t0  TCP *:3000 (LISTEN)
+node     1234 testuser   22u  IPv4 0x1234567890abcdef      0t0  TCP 127.0.0.1:9229 (LISTEN)
+nginx    5678 www-data   10u  IPv4 0x9876543210fedcba      0t0  TCP *:8080 (LISTEN)
+"#), None, 1000);
+    let after = build_snapshot(parse_listening_ports(r#"COMMAND   PID    USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+node     1234 testuser   20u  IPv4 0x1234567890abcdef      0t0  TCP *:3000 (LISTEN)
+deno     5678 www-data   10u  IPv4 0x9876543210fedcba      0t0  TCP *:8080 (LISTEN)
+python   9012 testuser    3u  IPv4 0x1111111111111111      0t0  TCP *:8000 (LISTEN)
+"#), None, 2000);
+    
+    let diff = compute_snapshot_diff(&before, &after);
+    
+    let keys = |list: &[ProcessInfo]| list.iter().map(|p| format!("{}:{}", p.pid, p.port)).collect::<Vec<_>>();
+    assert_eq!(keys(&diff.added), vec!["9012:8000"]);
+    assert_eq!(keys(&diff.removed), vec!["1234:9229"]);
+    assert_eq!(keys(&diff.changed), vec!["5678:8080"]);
+    assert_eq!(diff.changed[0].name, "deno");
+    
+    // The older snapshot is the baseline whichever way round they are passed
+    let swapped = compute_snapshot_diff(&after, &before);
+    assert_eq!(keys(&swapped.added), vec!["9012:8000"]);
+    assert_eq!(keys(&swapped.removed), vec!["1234:9229"]);
+    
+    let same = compute_snapshot_diff(&before, &before);
+    assert!(same.added.is_empty() && same.removed.is_empty() && same.changed.is_empty());
+}
+
+#[test]
+fn test_extract_port_basic() {
+    assert_eq!(extract_port("*:3000"), Some(3000));
+    assert_eq!(extract_port("127.0.0.1:8080"), Some(8080));
+    assert_eq!(extract_port("localhost:http"), None);
+    assert_eq!(extract_port("/tmp/app.sock"), None);
+}
+
+// Tests for IPv6-aware port extraction
+
+#[test]
+fn test_extract_port_ipv6_brackets() {
+    assert_eq!(extract_port("[::1]:8080"), Some(8080));
+    assert_eq!(extract_port("[fe80::1%en0]:8080"), Some(8080));
+    assert_eq!(extract_port("*:8080"), Some(8080));
+    assert_eq!(extract_port("[::]:443"), Some(443));
+}
+
+#[test]
+fn test_extract_port_bare_ipv6_has_no_port() {
+    // Without brackets the last colon segment is part of the address, not a port
+    assert_eq!(extract_port("::1"), None);
+    assert_eq!(extract_port("fe80::1"), None);
+    assert_eq!(extract_port("[::1]"), None);
+}
+
+#[test]
+fn test_extract_port_connection_uses_local_endpoint() {
+    assert_eq!(extract_port("127.0.0.1:5000->127.0.0.1:6000"), Some(5000));
+    assert_eq!(extract_port("[::1]:5000->[::1]:6000"), Some(5000));
+}
+
+#[test]
+fn test_split_host_port() {
+    assert_eq!(split_host_port("[fe80::1%en0]:8080"), Some(("fe80::1%en0", "8080")));
+    assert_eq!(split_host_port("*:8080"), Some(("*", "8080")));
+    assert_eq!(split_host_port("::1"), None);
+}
+
+#[test]
+fn test_parse_listening_ports_ipv6() {
+    let lsof_output = r#"COMMAND   PID    USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+node     1234 testuser   20u  IPv6 0x1234567890abcdef      0t0  TCP [::1]:8080 (LISTEN)
+node     1234 testuser   21u  IPv4 0x1234567890abcdef      0t0  TCP 127.0.0.1:8080 (LISTEN)
+python   4321 testuser   5u   IPv6 0x1234567890abcdef      0t0  TCP [fe80::1%en0]:9000 (LISTEN)
+"#;
+    
+    let result = parse_listening_ports(lsof_output);
+    
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].port, "8080");
+    assert_eq!(result[1].pid, "4321");
+    assert_eq!(result[1].port, "9000");
+}
+
+// Tests for batch kill progress reporting
+
+#[test]
+fn test_kill_progress_sequence() {
+    let pids = vec!["100".to_string(), "200".to_string(), "300".to_string()];
+    let mut events: Vec<KillProgress> = Vec::new();
+    
+    // Simulated killer: PID 200 fails, the others succeed
+    let killer = |pid: String, force: bool| {
+        assert!(force);
+        if pid == "200" {
+            Err(ProcessError::command_failed(format!("Failed to force kill process {}: Operation not permitted", pid)))
+        } else {
+            Ok(format!("Process {} force killed successfully", pid))
+        }
+    };
+    
+    let results = kill_pids_with_progress(&pids, true, killer, |progress| events.push(progress));
+    
+    // One event per PID, in order, with running counts
+    assert_eq!(events.len(), 3);
+    let sequence: Vec<(&str, bool, usize, usize)> = events
+        .iter()
+        .map(|e| (e.result.pid.as_str(), e.result.success, e.completed, e.total))
+        .collect();
+    assert_eq!(sequence, vec![
+        ("100", true, 1, 3),
+        ("200", false, 2, 3),
+        ("300", true, 3, 3),
+    ]);
+    assert!(events[1].result.message.contains("Operation not permitted"));
+    
+    // The final return value still summarizes everything
+    assert_eq!(results.len(), 3);
+    assert_eq!(results.iter().filter(|r| r.success).count(), 2);
+}
+
+#[test]
+fn test_kill_progress_empty_batch() {
+    let mut events = 0;
+    
+    let results = kill_pids_with_progress(&[], false, |_, _| Ok(String::new()), |_| events += 1);
+    
+    assert!(results.is_empty());
+    assert_eq!(events, 0);
+}
+
+// Tests for exporting the process list
+
+#[test]
+fn test_csv_escape() {
+    assert_eq!(csv_escape("node"), "node");
+    assert_eq!(csv_escape("my,app"), "\"my,app\"");
+    assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+}
+
+#[test]
+fn test_processes_to_csv_escapes_comma_and_quote() {
+    let processes = vec![
+        ProcessInfo {
+            pid: "1234".to_string(),
+            name: "web,\"server\"".to_string(),
+            port: "3000".to_string(),
+            ..Default::default()
+        },
+        ProcessInfo {
+            pid: "5678".to_string(),
+            name: "nginx".to_string(),
+            port: "8080".to_string(),
+            ..Default::default()
+        },
+    ];
+    
+    let csv = processes_to_csv(&processes, &HashMap::new());
+    let lines: Vec<&str> = csv.lines().collect();
+    
+    assert_eq!(lines[0], "pid,name,port,command");
+    assert_eq!(lines[1], "1234,\"web,\"\"server\"\"\",3000,");
+    assert_eq!(lines[2], "5678,nginx,8080,");
+}
+
+#[test]
+fn test_processes_to_csv_escapes_command() {
+    let processes = vec![ProcessInfo {
+        pid: "1234".to_string(),
+        name: "node".to_string(),
+        port: "3000".to_string(),
+        ..Default::default()
+    }];
+    let commands = HashMap::from([(
+        "1234".to_string(),
+        "node server.js --hosts=a,b --title \"dev\"".to_string(),
+    )]);
+    
+    let csv = processes_to_csv(&processes, &commands);
+    
+    assert_eq!(
+        csv.lines().nth(1),
+        Some("1234,node,3000,\"node server.js --hosts=a,b --title \"\"dev\"\"\"")
+    );
+}
+
+#[test]
+fn test_connections_to_csv() {
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+my,app   1234 user   20u  IPv4 0x1234      0t0  TCP *:3000 (LISTEN)
+my,app   1234 user   21u  IPv6 0x1235      0t0  TCP *:3000 (LISTEN)
+node     5678 user   22u  IPv6 0x5678      0t0  TCP [::1]:3000 (LISTEN)"#;
+    
+    let csv = connections_to_csv(&parse_connection_info(output));
+    let lines: Vec<&str> = csv.lines().collect();
+    
+    assert_eq!(lines, vec![
+        "pid,name,port,protocol,address",
+        "1234,\"my,app\",3000,TCP,*",
+        "5678,node,3000,TCP,::1",
+    ]);
+}
+
+#[test]
+fn test_connections_to_csv_empty() {
+    assert_eq!(connections_to_csv(&[]), "pid,name,port,protocol,address\n");
+}
+
+#[test]
+fn test_export_snapshot_json() {
+    let snapshot = ProcessSnapshot {
+        listening_ports: vec![ProcessInfo {
+            pid: "1234".to_string(),
+            name: "node".to_string(),
+            port: "3000".to_string(),
+            ..Default::default()
+        }],
+        timestamp: 42,
+        schema_version: SCHEMA_VERSION,
+    };
+    
+    let json = export_snapshot(&snapshot, "JSON", &HashMap::new()).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    
+    assert_eq!(value["timestamp"], 42);
+    assert_eq!(value["listening_ports"][0]["pid"], "1234");
+}
+
+#[test]
+fn test_export_snapshot_unsupported_format() {
+    let snapshot = ProcessSnapshot {
+        listening_ports: vec![],
+        timestamp: 0,
+        schema_version: SCHEMA_VERSION,
+    };
+    
+    let result = export_snapshot(&snapshot, "xml", &HashMap::new());
+    
+    assert!(matches!(result, Err(ProcessError::InvalidFormat(_))));
+    assert!(result.unwrap_err().to_string().contains("xml"));
+}
+
+// Tests for the kill history log
+
+fn history_entry(pid: &str, timestamp: u64) -> KillHistoryEntry {
+    KillHistoryEntry {
+        timestamp,
+        pid: pid.to_string(),
+        name: Some("node".to_string()),
+        signal: "SIGTERM".to_string(),
+        success: true,
+        message: format!("Process {} gracefully terminated successfully", pid),
+        detail: None,
+    }
+}
+
+#[test]
+fn test_kill_history_append_newest_first() {
+    let mut history = KillHistory::default();
+    assert!(history.is_empty());
+    
+    history.record(history_entry("100", 1));
+    history.record(history_entry("200", 2));
+    history.record(history_entry("300", 3));
+    
+    let entries = history.newest_first();
+    assert_eq!(history.len(), 3);
+    let pids: Vec<&str> = entries.iter().map(|e| e.pid.as_str()).collect();
+    assert_eq!(pids, vec!["300", "200", "100"]);
+    assert_eq!(entries[0].timestamp, 3);
+}
+
+#[test]
+fn test_kill_history_cap_drops_oldest() {
+    let mut history = KillHistory::with_capacity(3);
+    
+    for i in 1..=5 {
+        history.record(history_entry(&i.to_string(), i));
+    }
+    
+    let entries = history.newest_first();
+    assert_eq!(entries.len(), 3);
+    let pids: Vec<&str> = entries.iter().map(|e| e.pid.as_str()).collect();
+    assert_eq!(pids, vec!["5", "4", "3"]);
+}
+
+#[test]
+fn test_kill_history_default_capacity() {
+    let mut history = KillHistory::default();
+    
+    for i in 0..(KILL_HISTORY_CAPACITY as u64 + 50) {
+        history.record(history_entry(&i.to_string(), i));
+    }
+    
+    assert_eq!(history.len(), KILL_HISTORY_CAPACITY);
+    assert_eq!(KILL_HISTORY_CAPACITY, 200);
+}
+
+#[test]
+fn test_kill_history_entry_carries_pre_kill_detail() {
+    let detail = ProcessDetail {
+        pid: "4242".to_string(),
+        name: "node".to_string(),
+        port: "3000, 9229".to_string(),
+        user: Some("alice".to_string()),
+        command: Some("node server.js".to_string()),
+        ..Default::default()
+    };
+    
+    let result = Ok("Process 4242 gracefully terminated successfully".to_string());
+    let entry = kill_history_entry("4242".to_string(), "SIGTERM", Some(detail), &result, 1_000);
+    
+    assert_eq!(entry.name.as_deref(), Some("node"));
+    assert!(entry.success);
+    let captured = entry.detail.as_ref().unwrap();
+    assert_eq!(captured.user.as_deref(), Some("alice"));
+    assert_eq!(captured.port, "3000, 9229");
+    
+    let mut history = KillHistory::default();
+    history.record(entry);
+    let json = serde_json::to_value(history.newest_first()).unwrap();
+    assert_eq!(json[0]["detail"]["command"], "node server.js");
+}
+
+#[test]
+fn test_kill_history_entry_without_detail() {
+    // The process had already exited, so nothing could be captured
+    let result = Err(ProcessError::AlreadyGone("4242".to_string()));
+    let entry = kill_history_entry("4242".to_string(), "SIGKILL", None, &result, 1_000);
+    
+    assert_eq!(entry.name, None);
+    assert!(!entry.success);
+    assert!(entry.detail.is_none());
+}
+
+// Tests for the kill rate limiter
+
+#[test]
+fn test_kill_rate_limiter_disabled_by_default() {
+    let mut limiter = KillRateLimiter::default();
+    assert_eq!(limiter.limit(), None);
+    assert!((0..1_000).all(|_| limiter.try_acquire(5_000).is_ok()));
+}
+
+#[test]
+fn test_kill_rate_limiter_allows_n_then_rejects() {
+    let mut limiter = KillRateLimiter::default();
+    limiter.set_limit(Some(3));
+    
+    assert!(limiter.try_acquire(10_000).is_ok());
+    assert!(limiter.try_acquire(10_100).is_ok());
+    assert!(limiter.try_acquire(10_200).is_ok());
+    
+    // The fourth kill within the second waits for the first to leave the window
+    assert_eq!(limiter.try_acquire(10_300), Err(700));
+    assert_eq!(limiter.try_acquire(10_999), Err(1));
+    
+    assert!(limiter.try_acquire(11_000).is_ok());
+    assert_eq!(limiter.try_acquire(11_050), Err(50));
+}
+
+#[test]
+fn test_kill_rate_limiter_zero_disables() {
+    let mut limiter = KillRateLimiter::default();
+    limiter.set_limit(Some(1));
+    assert!(limiter.try_acquire(0).is_ok());
+    assert!(limiter.try_acquire(1).is_err());
+    
+    limiter.set_limit(Some(0));
+    assert_eq!(limiter.limit(), None);
+    assert!(limiter.try_acquire(2).is_ok());
+}
+
+#[test]
+fn test_rate_limited_serializes_retry_after() {
+    let error = ProcessError::RateLimited { max_per_second: 5, retry_after_ms: 120 };
+    let json = serde_json::to_value(&error).unwrap();
+    
+    assert_eq!(json["kind"], "RateLimited");
+    assert_eq!(json["retry_after_ms"], 120);
+    assert!(json["message"].as_str().unwrap().contains("at most 5 per second"));
+}
+
+// Tests for PID 0 and process-group rejection
+
+#[test]
+fn test_kill_rejects_pid_zero() {
+    let result = kill_process_with_signal("0".to_string(), true, None, None, false, &[]);
+    
+    assert!(matches!(result, Err(ProcessError::InvalidPid(_))));
+    assert!(result.unwrap_err().to_string().contains("PID 0"));
+    
+    // Zero-padded forms are rejected as well
+    assert!(matches!(validate_kill_pid("000"), Err(ProcessError::InvalidPid(_))));
+}
+
+#[test]
+fn test_kill_rejects_group_syntax() {
+    for pid in ["-1", "-1234", "+1234"] {
+        let result = kill_process_with_signal(pid.to_string(), false, None, None, false, &[]);
+        assert!(matches!(result, Err(ProcessError::InvalidPid(_))), "Should reject: {}", pid);
+    }
+    
+    assert_eq!(validate_kill_pid("1234").unwrap(), 1234);
+}
+
+#[test]
+fn test_process_error_serializes_kind_and_message() {
+    let error = ProcessError::InvalidPid("Invalid PID format: abc".to_string());
+    
+    let value = serde_json::to_value(&error).unwrap();
+    
+    assert_eq!(value["kind"], "InvalidPid");
+    assert_eq!(value["message"], "Invalid PID format: abc");
+}
+
+// Tests for searching by PID
+
+#[test]
+fn test_is_pid_query() {
+    assert!(is_pid_query("1234"));
+    assert!(is_pid_query(" 42 "));
+    
+    assert!(!is_pid_query("node"));
+    assert!(!is_pid_query("node2"));
+    assert!(!is_pid_query("12.5"));
+    assert!(!is_pid_query("-1"));
+    assert!(!is_pid_query(""));
+    assert!(!is_pid_query("99999999999")); // Too large for a PID
+}
+
+#[test]
+fn test_parse_pid_lookup_output_found() {
+    let ps_output = " 1234 node\n";
+    
+    let result = parse_pid_lookup_output(ps_output, "1234");
+    
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].pid, "1234");
+    assert_eq!(result[0].name, "node");
+    assert_eq!(result[0].port, "Unknown");
+}
+
+#[test]
+fn test_parse_pid_lookup_output_nonexistent_pid() {
+    // ps prints nothing for a PID that doesn't exist
+    assert!(parse_pid_lookup_output("", "999999").is_empty());
+    
+    // Rows for other PIDs are never reported as a match
+    assert!(parse_pid_lookup_output(" 1 launchd\n", "999999").is_empty());
+}
+
+// Tests for process name normalization
+
+#[test]
+fn test_normalize_process_name() {
+    assert_eq!(normalize_process_name("node\t\u{7}"), "node");
+    assert_eq!(normalize_process_name("  my   app \r\n"), "my app");
+    assert_eq!(normalize_process_name("no\u{1b}de"), "node");
+    assert_eq!(normalize_process_name("node应用\u{0}"), "node应用");
+    assert_eq!(normalize_process_name("\u{7}"), "");
+}
+
+#[test]
+fn test_parse_ps_output_strips_control_characters() {
+    let ps_output = "  1234 node\u{7}\t\n  5678 pyth\u{1}on\n";
+    
+    let result = parse_ps_output(ps_output, "node");
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].name, "node");
+    
+    // Matching happens against the cleaned name
+    let result = parse_ps_output(ps_output, "python");
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].name, "python");
+}
+
+#[test]
+fn test_parse_lsof_output_strips_control_characters() {
+    let lsof_output = "COMMAND   PID    USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME\n\
+node\u{7}应用 1234 testuser   20u  IPv4 0x1234567890abcdef      0t0  TCP *:3000 (LISTEN)\n";
+    
+    let result = parse_lsof_output(lsof_output, "3000");
+    
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].name, "node应用");
+}
+
+// Tests for search match highlight offsets
+
+#[test]
+fn test_find_match_range_case_insensitive() {
+    assert_eq!(find_match_range("NodeServer", "server"), Some((4, 10)));
+    assert_eq!(find_match_range("node", "node"), Some((0, 4)));
+    assert_eq!(find_match_range("nginx", "node"), None);
+    assert_eq!(find_match_range("nginx", ""), None);
+}
+
+#[test]
+fn test_find_match_range_multibyte() {
+    let name = "node应用";
+    
+    // "应用" starts after the 4 ASCII bytes and spans 6 bytes
+    let range = find_match_range(name, "应用").unwrap();
+    assert_eq!(range, (4, 10));
+    assert_eq!(&name[range.0..range.1], "应用");
+    
+    let range = find_match_range("我的Node应用", "node").unwrap();
+    assert_eq!(&"我的Node应用"[range.0..range.1], "Node");
+}
+
+#[test]
+fn test_find_match_range_length_changing_lowercase() {
+    // 'İ' (2 bytes) lowercases to "i̇" (3 bytes); offsets still index the original name
+    let name = "İstanbul-api";
+    let range = find_match_range(name, "api").unwrap();
+    assert_eq!(&name[range.0..range.1], "api");
+}
+
+#[test]
+fn test_parse_ps_output_match_range() {
+    let ps_output = "  1234 MyNodeApp\n  5678 node应用\n";
+    
+    let result = parse_ps_output(ps_output, "node");
+    
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].match_range, Some((2, 6)));
+    assert_eq!(result[1].match_range, Some((0, 4)));
+}
+
+// Tests for streaming ps parsing with a result cap
+
+// Reader wrapper that counts how many bytes the parser actually consumed
+struct CountingReader<'a> {
+    inner: &'a [u8],
+    consumed: std::rc::Rc<std::cell::Cell<usize>>,
+}
+
+impl std::io::Read for CountingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.consumed.set(self.consumed.get() + n);
+        Ok(n)
+    }
+}
+
+fn synthetic_ps_output(lines: usize) -> String {
+    (1..=lines)
+        .map(|i| {
+            let name = if i % 10 == 0 { "node" } else { "worker" };
+            format!("{:>7} {}\n", i, name)
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_ps_stream_large_input_uncapped() {
+    let ps_output = synthetic_ps_output(100_000);
+    
+    let result = parse_ps_stream(ps_output.as_bytes(), &SearchOptions::for_term("node"));
+    
+    assert_eq!(result.len(), 10_000);
+    assert!(result.iter().all(|p| p.name == "node"));
+    assert_eq!(result[0].pid, "10");
+}
+
+#[test]
+fn test_parse_ps_stream_stops_at_cap() {
+    let ps_output = synthetic_ps_output(100_000);
+    let consumed = std::rc::Rc::new(std::cell::Cell::new(0));
+    let reader = std::io::BufReader::with_capacity(1024, CountingReader {
+        inner: ps_output.as_bytes(),
+        consumed: consumed.clone(),
+    });
+    
+    let options = SearchOptions {
+        max_results: Some(50),
+        ..SearchOptions::for_term("node")
+    };
+    let result = parse_ps_stream(reader, &options);
+    
+    assert_eq!(result.len(), 50);
+    assert_eq!(result[49].pid, "500");
+    
+    // Only a small prefix of the input was read before short-circuiting
+    assert!(consumed.get() < ps_output.len() / 10, "consumed {} of {} bytes", consumed.get(), ps_output.len());
+}
+
+#[test]
+fn test_parse_ps_stream_matches_parse_ps_output() {
+    let ps_output = "  1234 node\n  5678 nginx\nbogus\n  9999 Node\n";
+    
+    let streamed = parse_ps_stream(ps_output.as_bytes(), &SearchOptions::for_term("node"));
+    let buffered = parse_ps_output(ps_output, "node");
+    
+    let streamed_pids: Vec<&str> = streamed.iter().map(|p| p.pid.as_str()).collect();
+    let buffered_pids: Vec<&str> = buffered.iter().map(|p| p.pid.as_str()).collect();
+    assert_eq!(streamed_pids, vec!["1234", "9999"]);
+    assert_eq!(streamed_pids, buffered_pids);
+}
+
+// Tests for structured command failure details
+
+#[cfg(unix)]
+#[test]
+fn test_process_error_from_failed_output() {
+    use std::os::unix::process::ExitStatusExt;
+    
+    // Raw wait status encodes the exit code in the high byte
+    let output = std::process::Output {
+        status: std::process::ExitStatus::from_raw(2 << 8),
+        stdout: vec![],
+        stderr: b"lsof: WARNING: can't stat() nfs file system /mnt/share\n".to_vec(),
+    };
+    
+    let error = ProcessError::from_output("Failed to execute lsof", &output);
+    
+    match &error {
+        ProcessError::CommandFailed { message, exit_code, stderr } => {
+            assert_eq!(message, "Failed to execute lsof");
+            assert_eq!(*exit_code, Some(2));
+            assert_eq!(stderr, "lsof: WARNING: can't stat() nfs file system /mnt/share\n");
+        }
+        other => panic!("Unexpected error variant: {:?}", other),
+    }
+    
+    // The human-readable summary is kept and the detail is surfaced to the frontend
+    assert_eq!(error.to_string(), "Failed to execute lsof");
+    let value = serde_json::to_value(&error).unwrap();
+    assert_eq!(value["kind"], "CommandFailed");
+    assert_eq!(value["exit_code"], 2);
+    assert!(value["stderr"].as_str().unwrap().contains("can't stat()"));
+}
+
+#[test]
+fn test_process_error_command_failed_without_output() {
+    let error = ProcessError::command_failed("Failed to execute kill command: No such file or directory");
+    
+    let value = serde_json::to_value(&error).unwrap();
+    
+    assert_eq!(value["kind"], "CommandFailed");
+    assert!(value["exit_code"].is_null());
+    assert_eq!(value["stderr"], "");
+}
+
+// Tests for the port-to-PID cache
+
+fn cached_listener(pid: &str, port: &str) -> ProcessInfo {
+    ProcessInfo {
+        pid: pid.to_string(),
+        name: "node".to_string(),
+        port: port.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_port_cache_hit_when_pid_alive() {
+    let mut cache = PortPidCache::with_ttl(5_000);
+    cache.insert(3000, vec![cached_listener("1234", "3000")], 1_000);
+    
+    let hit = cache.lookup(3000, 2_000, |pid| pid == "1234");
+    
+    assert_eq!(hit.unwrap()[0].pid, "1234");
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn test_port_cache_invalidated_when_pid_gone() {
+    let mut cache = PortPidCache::with_ttl(5_000);
+    cache.insert(3000, vec![cached_listener("1234", "3000"), cached_listener("5678", "3000")], 1_000);
+    
+    // One of the cached listeners exited, so the whole entry must be re-scanned
+    let hit = cache.lookup(3000, 2_000, |pid| pid == "1234");
+    
+    assert!(hit.is_none());
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_port_cache_expires_after_ttl() {
+    let mut cache = PortPidCache::with_ttl(5_000);
+    cache.insert(8080, vec![cached_listener("1234", "8080")], 1_000);
+    
+    assert!(cache.lookup(8080, 6_000, |_| true).is_some());
+    assert!(cache.lookup(8080, 6_001, |_| true).is_none());
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_port_cache_ignores_free_ports() {
+    let mut cache = PortPidCache::default();
+    cache.insert(3000, vec![cached_listener("1234", "3000")], 0);
+    
+    // A later scan finding nothing replaces the stale association
+    cache.insert(3000, vec![], 10);
+    
+    assert!(cache.is_empty());
+    assert!(cache.lookup(3000, 10, |_| true).is_none());
+    assert!(cache.lookup(4000, 10, |_| true).is_none());
+}
+
+// Tests for multi-term search
+
+const MULTI_TERM_PS_OUTPUT: &str = "  100 node\n  200 python3\n  300 java-tomcat\n  400 java\n  500 tomcat-helper\n  600 nginx\n";
+
+fn search_terms(terms: &[&str], combinator: TermCombinator) -> Vec<String> {
+    let options = SearchOptions {
+        terms: terms.iter().map(|t| t.to_string()).collect(),
+        combinator,
+        ..Default::default()
+    };
+    
+    parse_ps_stream(MULTI_TERM_PS_OUTPUT.as_bytes(), &options)
+        .into_iter()
+        .map(|p| p.pid)
+        .collect()
+}
+
+#[test]
+fn test_search_terms_any() {
+    assert_eq!(search_terms(&["node", "python"], TermCombinator::Any), vec!["100", "200"]);
+    assert_eq!(search_terms(&["NODE", "  ", "nginx"], TermCombinator::Any), vec!["100", "600"]);
+}
+
+#[test]
+fn test_search_terms_all() {
+    assert_eq!(search_terms(&["java", "tomcat"], TermCombinator::All), vec!["300"]);
+    assert!(search_terms(&["node", "python"], TermCombinator::All).is_empty());
+}
+
+#[test]
+fn test_search_single_term_unchanged() {
+    // One term behaves the same under both combinators and matches the plain parser
+    let any = search_terms(&["java"], TermCombinator::Any);
+    let all = search_terms(&["java"], TermCombinator::All);
+    let plain: Vec<String> = parse_ps_output(MULTI_TERM_PS_OUTPUT, "java").into_iter().map(|p| p.pid).collect();
+    
+    assert_eq!(any, vec!["300", "400"]);
+    assert_eq!(any, all);
+    assert_eq!(any, plain);
+}
+
+#[test]
+fn test_search_terms_match_range_uses_matched_term() {
+    let options = SearchOptions {
+        terms: vec!["python".to_string(), "node".to_string()],
+        combinator: TermCombinator::Any,
+        ..Default::default()
+    };
+    
+    let result = parse_ps_stream("  100 MyNode\n".as_bytes(), &options);
+    
+    assert_eq!(result[0].match_range, Some((2, 6)));
+}
+
+#[test]
+fn test_term_combinator_deserializes_lowercase() {
+    let any: TermCombinator = serde_json::from_str("\"any\"").unwrap();
+    let all: TermCombinator = serde_json::from_str("\"all\"").unwrap();
+    
+    assert_eq!(any, TermCombinator::Any);
+    assert_eq!(all, TermCombinator::All);
+}
+
+// Tests for container detection from cgroup membership
+
+const CONTAINER_ID: &str = "3f2c9a1b7d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8";
+
+#[test]
+fn test_parse_container_id_cgroup_v1_docker() {
+    let cgroup = format!(
+        "12:memory:/docker/{id}\n11:cpu,cpuacct:/docker/{id}\n1:name=systemd:/docker/{id}\n",
+        id = CONTAINER_ID
+    );
+    
+    assert_eq!(parse_container_id(&cgroup), Some(CONTAINER_ID.to_string()));
+}
+
+#[test]
+fn test_parse_container_id_cgroup_v2_scope() {
+    let docker = format!("0::/system.slice/docker-{}.scope\n", CONTAINER_ID);
+    let containerd = format!(
+        "0::/kubepods.slice/kubepods-besteffort.slice/kubepods-besteffort-pod1234.slice/cri-containerd-{}.scope\n",
+        CONTAINER_ID
+    );
+    
+    assert_eq!(parse_container_id(&docker), Some(CONTAINER_ID.to_string()));
+    assert_eq!(parse_container_id(&containerd), Some(CONTAINER_ID.to_string()));
+}
+
+#[test]
+fn test_parse_container_id_host_process() {
+    let host = "0::/user.slice/user-1000.slice/session-2.scope\n";
+    let systemd_v1 = "12:memory:/system.slice/sshd.service\n1:name=systemd:/init.scope\n";
+    
+    assert_eq!(parse_container_id(host), None);
+    assert_eq!(parse_container_id(systemd_v1), None);
+    assert_eq!(parse_container_id(""), None);
+}
+
+// Tests for excluding PIDs when killing by port
+
+const TWO_LISTENERS_LSOF: &str = r#"COMMAND   PID    USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+node     1234 testuser   20u  IPv4 0x1234567890abcdef      0t0  TCP *:3000 (LISTEN)
+node     1234 testuser   21u  IPv6 0x1234567890abcdef      0t0  TCP *:3000 (LISTEN)
+node     5678 testuser   20u  IPv4 0x9876543210fedcba      0t0  TCP *:3000 (LISTEN)
+"#;
+
+#[test]
+fn test_exclude_listeners_keeps_excluded_pid_alive() {
+    let listeners = parse_lsof_output(TWO_LISTENERS_LSOF, "3000");
+    assert_eq!(listeners.len(), 2);
+    
+    let targets = exclude_listeners(listeners, &["5678".to_string()]).unwrap();
+    
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].pid, "1234");
+}
+
+#[test]
+fn test_exclude_listeners_ignores_absent_pids() {
+    let listeners = parse_lsof_output(TWO_LISTENERS_LSOF, "3000");
+    
+    let targets = exclude_listeners(listeners, &["99999".to_string(), " 1234 ".to_string()]).unwrap();
+    
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].pid, "5678");
+}
+
+#[test]
+fn test_exclude_listeners_rejects_invalid_pid() {
+    let listeners = parse_lsof_output(TWO_LISTENERS_LSOF, "3000");
+    
+    let result = exclude_listeners(listeners, &["abc".to_string()]);
+    
+    assert!(matches!(result, Err(ProcessError::InvalidPid(_))));
+}
+
+// Tests for CPU core-equivalents and memory labelling
+
+#[test]
+fn test_parse_cpu_cores_single_core() {
+    assert_eq!(parse_cpu_cores("50.0"), Some(0.5));
+    assert_eq!(parse_cpu_cores("0.0"), Some(0.0));
+}
+
+#[test]
+fn test_parse_cpu_cores_multithreaded_exceeds_one() {
+    // Linux reports pcpu per core, so a busy multithreaded process can exceed 100%
+    let cores = parse_cpu_cores("256.8").unwrap();
+    assert!((cores - 2.568).abs() < 1e-9);
+    
+    let cores = parse_cpu_cores(" 400% ").unwrap();
+    assert!((cores - 4.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_parse_cpu_cores_locale_comma() {
+    let cores = parse_cpu_cores("12,5").unwrap();
+    assert!((cores - 0.125).abs() < 1e-9);
+}
+
+#[test]
+fn test_parse_cpu_cores_invalid() {
+    assert_eq!(parse_cpu_cores(""), None);
+    assert_eq!(parse_cpu_cores("abc"), None);
+    assert_eq!(parse_cpu_cores("-5"), None);
+    assert_eq!(parse_cpu_cores("NaN"), None);
+}
+
+#[test]
+fn test_format_memory_usage() {
+    assert_eq!(format_memory_usage("1.2"), "1.2% of RAM");
+    assert_eq!(format_memory_usage(" 0.0% "), "0.0% of RAM");
+}
+
+// Tests for the process -> ports reverse index
+
+#[test]
+fn test_group_ports_by_process_multi_pid_multi_port() {
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     5678 user   20u  IPv4 0x1234      0t0  TCP *:3000 (LISTEN)
+postgres  123 pg      5u  IPv6 0x2222      0t0  TCP [::1]:5432 (LISTEN)
+node     5678 user   21u  IPv4 0x1235      0t0  TCP 127.0.0.1:3000->127.0.0.1:51000 (ESTABLISHED)
+node     5678 user   22u  IPv4 0x1236      0t0  UDP *:5353
+postgres  123 pg      6u  IPv4 0x2223      0t0  TCP 127.0.0.1:5432 (LISTEN)
+node     5678 user   23u  IPv6 0x1237      0t0  TCP *:3000 (LISTEN)"#;
+    
+    let processes = group_ports_by_process(output);
+    
+    assert_eq!(processes.len(), 2);
+    
+    // Sorted by PID numerically, not lexically
+    assert_eq!(processes[0].pid, "123");
+    assert_eq!(processes[0].name, "postgres");
+    assert_eq!(processes[0].ports.len(), 1);
+    assert_eq!(processes[0].ports[0].port, 5432);
+    assert_eq!(processes[0].ports[0].state.as_deref(), Some("LISTEN"));
+    
+    assert_eq!(processes[1].pid, "5678");
+    let node_ports: Vec<(u16, &str, Option<&str>)> = processes[1].ports.iter()
+        .map(|p| (p.port, p.protocol.as_str(), p.state.as_deref()))
+        .collect();
+    assert_eq!(node_ports, vec![
+        (3000, "TCP", Some("ESTABLISHED")),
+        (3000, "TCP", Some("LISTEN")),
+        (5353, "UDP", None),
+    ]);
+}
+
+#[test]
+fn test_group_ports_by_process_empty_and_malformed() {
+    assert!(group_ports_by_process("").is_empty());
+    
+    let output = "COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME\nbroken line\n";
+    assert!(group_ports_by_process(output).is_empty());
+}
+
+// Tests for configurable tool paths
+
+#[test]
+fn test_tool_paths_default_to_bare_names() {
+    let tools = ToolPaths::default();
+    
+    assert_eq!(tools.lsof().get_program(), "lsof");
+    assert_eq!(tools.ps().get_program(), "ps");
+    assert_eq!(tools.kill().get_program(), "kill");
+}
+
+#[test]
+fn test_tool_paths_use_configured_path() {
+    let tools = ToolPaths {
+        lsof: Some("/usr/sbin/lsof".to_string()),
+        ps: Some("/nix/store/abc123-procps/bin/ps".to_string()),
+        kill: None,
+        max_output_bytes: None,
+    };
+    
+    assert_eq!(tools.lsof().get_program(), "/usr/sbin/lsof");
+    assert_eq!(tools.ps().get_program(), "/nix/store/abc123-procps/bin/ps");
+    assert_eq!(tools.kill().get_program(), "kill");
+}
+
+#[test]
+fn test_tool_paths_blank_falls_back() {
+    let tools = ToolPaths {
+        lsof: Some("   ".to_string()),
+        ps: Some(String::new()),
+        kill: Some(" /bin/kill ".to_string()),
+        max_output_bytes: None,
+    };
+    
+    assert_eq!(tools.lsof().get_program(), "lsof");
+    assert_eq!(tools.ps().get_program(), "ps");
+    assert_eq!(tools.kill().get_program(), "/bin/kill");
+}
+
+#[test]
+fn test_tool_paths_output_limit() {
+    assert_eq!(ToolPaths::default().output_limit(), DEFAULT_MAX_OUTPUT_BYTES);
+    
+    let tools = ToolPaths { max_output_bytes: Some(4096), ..ToolPaths::default() };
+    assert_eq!(tools.output_limit(), 4096);
+    
+    // Zero would discard all output, so it falls back to the default
+    let tools = ToolPaths { max_output_bytes: Some(0), ..ToolPaths::default() };
+    assert_eq!(tools.output_limit(), DEFAULT_MAX_OUTPUT_BYTES);
+}
+
+// Tests for smart kill (SIGTERM, grace period, SIGKILL)
+
+const SMART_KILL_TEST_GRACE: Duration = Duration::from_millis(3_000);
+
+#[test]
+fn test_smart_kill_terminated_gracefully() {
+    let signals = RefCell::new(Vec::new());
+    let checks = Cell::new(0);
+    
+    // Alive for the initial check and the first poll, gone afterwards
+    let status = smart_kill_with(
+        "1234",
+        SMART_KILL_TEST_GRACE,
+        |_, force| { signals.borrow_mut().push(force); Ok("ok".to_string()) },
+        |_| { checks.set(checks.get() + 1); checks.get() <= 2 },
+        |_| {},
+    );
+    
+    assert_eq!(status, SmartKillStatus::TerminatedGracefully);
+    assert_eq!(*signals.borrow(), vec![false]);
+}
+
+#[test]
+fn test_smart_kill_escalated() {
+    let signals = RefCell::new(Vec::new());
+    let slept = Cell::new(Duration::ZERO);
+    
+    let status = smart_kill_with(
+        "1234",
+        SMART_KILL_TEST_GRACE,
+        |_, force| { signals.borrow_mut().push(force); Ok("ok".to_string()) },
+        |_| !signals.borrow().contains(&true),
+        |d| slept.set(slept.get() + d),
+    );
+    
+    assert_eq!(status, SmartKillStatus::Escalated);
+    assert_eq!(*signals.borrow(), vec![false, true]);
+    assert!(slept.get() >= SMART_KILL_TEST_GRACE);
+}
+
+#[test]
+fn test_smart_kill_already_dead() {
+    let signals = RefCell::new(Vec::new());
+    
+    let status = smart_kill_with(
+        "1234",
+        SMART_KILL_TEST_GRACE,
+        |_, force| { signals.borrow_mut().push(force); Ok("ok".to_string()) },
+        |_| false,
+        |_| {},
+    );
+    
+    assert_eq!(status, SmartKillStatus::AlreadyDead);
+    assert!(signals.borrow().is_empty());
+}
+
+#[test]
+fn test_smart_kill_failed_when_signal_rejected() {
+    let status = smart_kill_with(
+        "1",
+        SMART_KILL_TEST_GRACE,
+        |_, _| Err(ProcessError::command_failed("Operation not permitted".to_string())),
+        |_| true,
+        |_| {},
+    );
+    
+    match status {
+        SmartKillStatus::Failed(message) => assert!(message.contains("not permitted")),
+        other => panic!("expected Failed, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_smart_kill_failed_when_survives_sigkill() {
+    let status = smart_kill_with(
+        "1234",
+        SMART_KILL_TEST_GRACE,
+        |_, _| Ok("ok".to_string()),
+        |_| true,
+        |_| {},
+    );
+    
+    assert!(matches!(status, SmartKillStatus::Failed(_)));
+}
+
+#[test]
+fn test_kill_async_escalates_when_not_cancelled() {
+    let signals = RefCell::new(Vec::new());
+    
+    let status = kill_async_with(
+        "1234",
+        SMART_KILL_TEST_GRACE,
+        || false,
+        |_, force| { signals.borrow_mut().push(force); Ok("ok".to_string()) },
+        |_| !signals.borrow().contains(&true),
+        |_| {},
+    );
+    
+    assert_eq!(status, SmartKillStatus::Escalated);
+    assert_eq!(*signals.borrow(), vec![false, true]);
+}
+
+#[test]
+fn test_kill_async_cancelled_during_grace_skips_sigkill() {
+    let signals = RefCell::new(Vec::new());
+    let sleeps = Cell::new(0);
+    
+    // Cancelled after a couple of polls while the process is still running
+    let status = kill_async_with(
+        "1234",
+        SMART_KILL_TEST_GRACE,
+        || sleeps.get() >= 2,
+        |_, force| { signals.borrow_mut().push(force); Ok("ok".to_string()) },
+        |_| true,
+        |_| sleeps.set(sleeps.get() + 1),
+    );
+    
+    assert_eq!(status, SmartKillStatus::Cancelled);
+    assert_eq!(*signals.borrow(), vec![false]);
+    assert_eq!(sleeps.get(), 2);
+}
+
+#[test]
+fn test_kill_complete_event_serialization() {
+    let event = KillCompleteEvent { pid: "1234".to_string(), status: SmartKillStatus::Cancelled };
+    let json = serde_json::to_string(&event).unwrap();
+    assert_eq!(json, r#"{"pid":"1234","status":{"status":"Cancelled"}}"#);
+}
+
+#[test]
+fn test_smart_kill_status_serialization() {
+    let json = serde_json::to_string(&SmartKillStatus::Escalated).unwrap();
+    assert_eq!(json, r#"{"status":"Escalated"}"#);
+    
+    let json = serde_json::to_string(&SmartKillStatus::Failed("boom".to_string())).unwrap();
+    assert_eq!(json, r#"{"status":"Failed","message":"boom"}"#);
+}
+
+// Tests for recovering names truncated by the kernel's comm length limit
+
+#[test]
+fn test_recover_full_name_from_args() {
+    // "my-long-service-daemon" truncated to 15 characters
+    assert_eq!(
+        recover_full_name("my-long-service", "/opt/app/bin/my-long-service-daemon --port 8080"),
+        "my-long-service-daemon"
+    );
+}
+
+#[test]
+fn test_recover_full_name_keeps_short_or_unrelated_comm() {
+    // Not at the truncation length
+    assert_eq!(recover_full_name("node", "/usr/local/bin/node-something server.js"), "node");
+    // At the limit, but argv[0] doesn't extend the comm (process renamed itself)
+    assert_eq!(recover_full_name("postgres: write", "postgres: writer process"), "postgres: write");
+    // Exactly 15 characters and not truncated
+    assert_eq!(recover_full_name("exactly15-chars", "./exactly15-chars"), "exactly15-chars");
+    assert_eq!(recover_full_name("exactly15-chars", ""), "exactly15-chars");
+}
+
+#[test]
+fn test_parse_ps_output_matches_full_name_behind_truncated_comm() {
+    let ps_output = r#"  1234 my-long-service /opt/app/bin/my-long-service-daemon --port 8080
+  5678 node node server.js"#;
+    
+    let result = parse_ps_output(ps_output, "service-daemon");
+    
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].pid, "1234");
+    assert_eq!(result[0].name, "my-long-service-daemon");
+    assert_eq!(result[0].match_range, Some((8, 22)));
+}
+
+// Tests for hiding kernel threads from name searches
+
+#[test]
+fn test_is_kernel_thread() {
+    assert!(is_kernel_thread("2", "kthreadd", "[kthreadd]"));
+    assert!(is_kernel_thread("87", "kworker/0:1", "[kworker/0:1-events]"));
+    assert!(is_kernel_thread("87", "[kworker/0:1]", ""));
+    assert!(is_kernel_thread("0", "kernel_task", ""));
+    
+    assert!(!is_kernel_thread("1234", "node", "node"));
+    assert!(!is_kernel_thread("1234", "node", "/usr/bin/node"));
+    assert!(!is_kernel_thread("1234", "[]", "[]"));
+}
+
+const KERNEL_THREAD_PS_OUTPUT: &str = "    2 kthreadd [kthreadd]\n   87 kworker/0:1 [kworker/0:1-events]\n  300 kworker-monitor /usr/bin/kworker-monitor\n";
+
+#[test]
+fn test_search_hides_kernel_threads_by_default() {
+    let result = parse_ps_stream(KERNEL_THREAD_PS_OUTPUT.as_bytes(), &SearchOptions::for_term("kworker"));
+    
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].pid, "300");
+}
+
+#[test]
+fn test_process_basename() {
+    assert_eq!(process_basename("/usr/bin/node"), "node");
+    assert_eq!(process_basename("node"), "node");
+    assert_eq!(process_basename("/Applications/Google Chrome.app/Contents/MacOS/Google Chrome"), "Google Chrome");
+    assert_eq!(process_basename("/usr/bin/"), "bin");
+    assert_eq!(process_basename("/"), "/");
+}
+
+#[test]
+fn test_search_shows_basename_of_full_path_comm() {
+    let ps_output = "  100 /usr/bin/node\n  200 node\n  300 /usr/local/bin/python3\n";
+    let options = SearchOptions {
+        match_mode: MatchMode::Exact,
+        ..SearchOptions::for_term("node")
+    };
+    
+    let processes = parse_ps_stream(ps_output.as_bytes(), &options);
+    
+    let found: Vec<(&str, &str)> = processes.iter().map(|p| (p.pid.as_str(), p.name.as_str())).collect();
+    assert_eq!(found, vec![("100", "node"), ("200", "node")]);
+}
+
+#[test]
+fn test_search_by_path_matches_full_comm() {
+    let ps_output = "  100 /usr/bin/node\n  200 node\n  300 /opt/node/bin/node\n";
+    let options = SearchOptions {
+        match_mode: MatchMode::Exact,
+        ..SearchOptions::for_term("/usr/bin/node")
+    };
+    
+    let processes = parse_ps_stream(ps_output.as_bytes(), &options);
+    
+    assert_eq!(processes.len(), 1);
+    assert_eq!(processes[0].pid, "100");
+    assert_eq!(processes[0].name, "/usr/bin/node");
+}
+
+#[test]
+fn test_search_can_include_kernel_threads() {
+    let options = SearchOptions {
+        hide_kernel_threads: false,
+        ..SearchOptions::for_term("kworker")
+    };
+    
+    let pids: Vec<String> = parse_ps_stream(KERNEL_THREAD_PS_OUTPUT.as_bytes(), &options)
+        .into_iter()
+        .map(|p| p.pid)
+        .collect();
+    
+    assert_eq!(pids, vec!["87", "300"]);
+}
+
+// Tests for per-process connection details
+
+#[test]
+fn test_parse_connections_listen_established_udp() {
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 user   20u  IPv4 0x1234      0t0  TCP *:3000 (LISTEN)
+node     1234 user   21u  IPv4 0x1235      0t0  TCP 127.0.0.1:3000->127.0.0.1:51000 (ESTABLISHED)
+node     1234 user   22u  IPv6 0x1236      0t0  TCP [::1]:3000->[::1]:51001 (CLOSE_WAIT)
+node     1234 user   23u  IPv4 0x1237      0t0  UDP *:5353
+other    9999 user   24u  IPv4 0x1238      0t0  TCP *:4000 (LISTEN)"#;
+    
+    let connections = parse_connections(output, "1234");
+    
+    assert_eq!(connections.len(), 4);
+    
+    assert_eq!(connections[0].local, "*:3000");
+    assert_eq!(connections[0].remote, None);
+    assert_eq!(connections[0].state.as_deref(), Some("LISTEN"));
+    assert_eq!(connections[0].protocol, "TCP");
+    
+    assert_eq!(connections[1].local, "127.0.0.1:3000");
+    assert_eq!(connections[1].remote.as_deref(), Some("127.0.0.1:51000"));
+    assert_eq!(connections[1].state.as_deref(), Some("ESTABLISHED"));
+    
+    assert_eq!(connections[2].local, "[::1]:3000");
+    assert_eq!(connections[2].remote.as_deref(), Some("[::1]:51001"));
+    
+    assert_eq!(connections[3].local, "*:5353");
+    assert_eq!(connections[3].remote, None);
+    assert_eq!(connections[3].state, None);
+    assert_eq!(connections[3].protocol, "UDP");
+}
+
+#[test]
+fn test_parse_connections_name_not_at_index_eight() {
+    // SIZE/OFF is missing, so NAME sits at index 7
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE NODE NAME
+node     1234 user   21u  IPv4 0x1235  TCP 127.0.0.1:3000->127.0.0.1:51000 (ESTABLISHED)"#;
+    
+    let connections = parse_connections(output, "1234");
+    
+    assert_eq!(connections.len(), 1);
+    assert_eq!(connections[0].local, "127.0.0.1:3000");
+    assert_eq!(connections[0].remote.as_deref(), Some("127.0.0.1:51000"));
+    assert_eq!(connections[0].state.as_deref(), Some("ESTABLISHED"));
+    assert_eq!(connections[0].protocol, "TCP");
+}
+
+#[test]
+fn test_parse_connections_empty() {
+    assert!(parse_connections("", "1234").is_empty());
+    assert!(parse_connections("COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME\n", "1234").is_empty());
+}
+
+// Tests for guarding kills against PID reuse
+
+#[test]
+fn test_verify_expected_name_rejects_reused_pid() {
+    let result = verify_expected_name("1234", "node", Some("postgres"));
+    
+    match result {
+        Err(ProcessError::PidReused { pid, expected, actual }) => {
+            assert_eq!(pid, "1234");
+            assert_eq!(expected, "node");
+            assert_eq!(actual, "postgres");
+        }
+        other => panic!("expected PidReused, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_verify_expected_name_accepts_same_process() {
+    assert!(verify_expected_name("1234", "node", Some("node")).is_ok());
+    // macOS ps reports the full executable path
+    assert!(verify_expected_name("1234", "node", Some("/usr/local/bin/node")).is_ok());
+    // lsof truncates COMMAND to 9 characters, comm to 15
+    assert!(verify_expected_name("1234", "com.docke", Some("com.docker.back")).is_ok());
+    assert!(verify_expected_name("1234", "my-long-service-daemon", Some("my-long-service")).is_ok());
+}
+
+#[test]
+fn test_verify_expected_name_allows_exited_process() {
+    // Nothing runs at the PID any more, so there is nothing to protect
+    assert!(verify_expected_name("1234", "node", None).is_ok());
+}
+
+#[test]
+fn test_pid_reused_error_serialization() {
+    let error = ProcessError::PidReused {
+        pid: "1234".to_string(),
+        expected: "node".to_string(),
+        actual: "postgres".to_string(),
+    };
+    let value = serde_json::to_value(&error).unwrap();
+    
+    assert_eq!(value["kind"], "PidReused");
+    assert!(value["message"].as_str().unwrap().contains("postgres"));
+}
+
+// Tests for port patterns in port checks
+
+#[test]
+fn test_parse_port_pattern_wildcard() {
+    assert_eq!(parse_port_pattern("8*").unwrap(), 8000..=8999);
+    assert_eq!(parse_port_pattern("80*").unwrap(), 8000..=8099);
+    assert_eq!(parse_port_pattern("300*").unwrap(), 3000..=3009);
+    assert_eq!(parse_port_pattern("6553*").unwrap(), 65530..=65535);
+}
+
+#[test]
+fn test_parse_port_pattern_above_and_ranges() {
+    assert_eq!(parse_port_pattern(">1024").unwrap(), 1025..=65535);
+    assert_eq!(parse_port_pattern(" > 1024 ").unwrap(), 1025..=65535);
+    assert_eq!(parse_port_pattern("3000-3010").unwrap(), 3000..=3010);
+    assert_eq!(parse_port_pattern("3000").unwrap(), 3000..=3000);
+}
+
+#[test]
+fn test_parse_port_pattern_rejects_malformed() {
+    let malformed = vec!["*", "8**", "8*0", "*8", "0*", "7000*", ">65535", ">abc", "9000-3000", "3000-", "abc", ""];
+    
+    for pattern in malformed {
+        let result = parse_port_pattern(pattern);
+        assert!(
+            matches!(result, Err(ProcessError::InvalidPortPattern { .. })),
+            "pattern {:?} should be rejected, got {:?}", pattern, result
+        );
+    }
+    
+    let message = parse_port_pattern("8**").unwrap_err().to_string();
+    assert!(message.contains("8**"));
+}
+
+#[test]
+fn test_is_port_pattern() {
+    assert!(is_port_pattern("8*"));
+    assert!(is_port_pattern(">1024"));
+    assert!(is_port_pattern("3000-3010"));
+    assert!(!is_port_pattern("3000"));
+    assert!(!is_port_pattern("abc"));
+}
+
+// Tests for telling "no ports" apart from "permission denied" in port lookups
+
+#[test]
+fn test_interpret_port_lookup_permission_denied() {
+    let stderr = "lsof: WARNING: can't stat() tcp file system /proc/1/fd\n      Output information may be incomplete.\n      assuming \"dev=0\" from mount table\nlsof: no pwd entry for UID 0\nlsof: /proc/1/fd: Permission denied\n";
+    
+    let lookup = interpret_port_lookup("1", "", stderr);
+    
+    assert_eq!(lookup, PortLookup::PermissionDenied);
+    assert_eq!(lookup.describe(), "Requires privileges");
+}
+
+#[test]
+fn test_interpret_port_lookup_no_ports() {
+    let lookup = interpret_port_lookup("1234", "", "");
+    
+    assert_eq!(lookup, PortLookup::NoPorts);
+    assert_eq!(lookup.describe(), "Unknown");
+}
+
+#[test]
+fn test_interpret_port_lookup_ports_despite_warnings() {
+    let stdout = r#"COMMAND  PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node    1234 user   20u  IPv4 0x1234      0t0  TCP *:3000 (LISTEN)
+node    1234 user   21u  IPv6 0x1235      0t0  TCP *:3000 (LISTEN)
+node    1234 user   22u  IPv4 0x1236      0t0  TCP *:9229 (LISTEN)"#;
+    
+    let lookup = interpret_port_lookup("1234", stdout, "lsof: WARNING: Permission denied on /proc/1/fd");
+    
+    assert_eq!(lookup, PortLookup::Ports(vec!["3000".to_string(), "9229".to_string()]));
+    assert_eq!(lookup.describe(), "3000, 9229");
+}
+
+#[test]
+fn test_interpret_port_lookup_ignores_other_pids() {
+    let stdout = r#"COMMAND  PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node    1234 user   20u  IPv4 0x1234      0t0  TCP *:3000 (LISTEN)
+nginx  12345 www     6u  IPv4 0x1111      0t0  TCP *:80 (LISTEN)
+sshd     999 root    3u  IPv4 0x2222      0t0  TCP *:22 (LISTEN)"#;
+    
+    assert_eq!(interpret_port_lookup("1234", stdout, ""), PortLookup::Ports(vec!["3000".to_string()]));
+    assert_eq!(interpret_port_lookup("4242", stdout, ""), PortLookup::NoPorts);
+}
+
+// Tests for inspecting a port's listeners in one call
+
+#[test]
+fn test_assemble_port_details_combines_listeners() {
+    let listeners = parse_lsof_output(TWO_LISTENERS_LSOF, "3000");
+    
+    let details = assemble_port_details(listeners, 16, |pid| {
+        Ok(ProcessDetail {
+            pid: pid.to_string(),
+            name: format!("detail-{}", pid),
+            port: "3000, 9229".to_string(),
+            user: Some("user".to_string()),
+            ..Default::default()
+        })
+    });
+    
+    assert_eq!(details.len(), 2);
+    assert_eq!(details[0].pid, "1234");
+    assert_eq!(details[0].name, "detail-1234");
+    assert_eq!(details[1].pid, "5678");
+    assert_eq!(details[1].port, "3000, 9229");
+}
+
+#[test]
+fn test_assemble_port_details_falls_back_on_failure() {
+    let listeners = parse_lsof_output(TWO_LISTENERS_LSOF, "3000");
+    let expected_name = listeners[1].name.clone();
+    
+    let details = assemble_port_details(listeners, 16, |pid| {
+        if pid == "5678" {
+            Err("process exited".to_string())
+        } else {
+            Ok(ProcessDetail { pid: pid.to_string(), user: Some("user".to_string()), ..Default::default() })
+        }
+    });
+    
+    assert_eq!(details.len(), 2);
+    assert!(details[0].user.is_some());
+    assert_eq!(details[1].pid, "5678");
+    assert_eq!(details[1].name, expected_name);
+    assert_eq!(details[1].port, "3000");
+    assert!(details[1].user.is_none());
+}
+
+#[test]
+fn test_assemble_port_details_respects_bound() {
+    let listeners: Vec<ProcessInfo> = (1..=5)
+        .map(|pid| ProcessInfo { pid: pid.to_string(), name: "node".to_string(), port: "3000".to_string(), ..Default::default() })
+        .collect();
+    
+    let details = assemble_port_details(listeners, 3, |pid| Ok(ProcessDetail { pid: pid.to_string(), ..Default::default() }));
+    
+    let pids: Vec<&str> = details.iter().map(|d| d.pid.as_str()).collect();
+    assert_eq!(pids, vec!["1", "2", "3"]);
+}
+
+// Tests for keeping every lsof row when deduplication is off
+
+#[test]
+fn test_parse_lsof_rows_without_dedup_keeps_every_fd() {
+    let lsof_output = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 user   20u  IPv4 0x1234      0t0  TCP *:3000 (LISTEN)
+node     1234 user   21u  IPv6 0x5678      0t0  TCP *:3000 (LISTEN)
+node     5678 user   22u  IPv4 0x9abc      0t0  TCP *:3000 (LISTEN)"#;
+    
+    let all_rows = parse_lsof_rows(lsof_output, "3000", DedupKey::Row);
+    let deduped = parse_lsof_rows(lsof_output, "3000", DedupKey::Pid);
+    
+    assert_eq!(all_rows.len(), 3);
+    assert_eq!(all_rows[0].pid, "1234");
+    assert_eq!(all_rows[1].pid, "1234");
+    assert_eq!(all_rows[2].pid, "5678");
+    
+    assert_eq!(deduped.len(), 2);
+    assert_eq!(deduped.len(), parse_lsof_output(lsof_output, "3000").len());
+}
+
+// Tests for prefix/suffix/exact name matching
+
+const MATCH_MODE_PS_OUTPUT: &str = "  100 postgres\n  200 postgresql\n  300 mypostgres-wrapper\n  400 pg-postgres\n  500 Postgres\n";
+
+fn search_mode(term: &str, match_mode: MatchMode) -> Vec<String> {
+    let options = SearchOptions {
+        match_mode,
+        ..SearchOptions::for_term(term)
+    };
+    
+    parse_ps_stream(MATCH_MODE_PS_OUTPUT.as_bytes(), &options)
+        .into_iter()
+        .map(|p| p.pid)
+        .collect()
+}
+
+#[test]
+fn test_match_mode_substring_is_default() {
+    assert_eq!(search_mode("postgres", MatchMode::default()), vec!["100", "200", "300", "400", "500"]);
+}
+
+#[test]
+fn test_match_mode_prefix() {
+    assert_eq!(search_mode("postgres", MatchMode::Prefix), vec!["100", "200", "500"]);
+}
+
+#[test]
+fn test_match_mode_suffix() {
+    assert_eq!(search_mode("postgres", MatchMode::Suffix), vec!["100", "400", "500"]);
+}
+
+#[test]
+fn test_match_mode_exact() {
+    assert_eq!(search_mode("postgres", MatchMode::Exact), vec!["100", "500"]);
+}
+
+#[test]
+fn test_match_mode_suffix_range_points_at_end() {
+    let options = SearchOptions {
+        match_mode: MatchMode::Suffix,
+        ..SearchOptions::for_term("node")
+    };
+    
+    let result = parse_ps_stream("  100 node-to-node\n".as_bytes(), &options);
+    
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].match_range, Some((8, 12)));
+}
+
+// Tests for counting every match while keeping only the capped results
+
+#[test]
+fn test_count_ps_stream_counts_beyond_cap() {
+    let ps_output = synthetic_ps_output(100_000);
+    let options = SearchOptions {
+        max_results: Some(50),
+        ..SearchOptions::for_term("node")
+    };
+    
+    let (processes, total_matches) = count_ps_stream(ps_output.as_bytes(), &options);
+    
+    assert_eq!(processes.len(), 50);
+    assert_eq!(processes[49].pid, "500");
+    assert_eq!(total_matches, 10_000);
+}
+
+#[test]
+fn test_count_ps_stream_uncapped_total_matches_len() {
+    let (processes, total_matches) = count_ps_stream(MULTI_TERM_PS_OUTPUT.as_bytes(), &SearchOptions::for_term("java"));
+    
+    assert_eq!(processes.len(), 2);
+    assert_eq!(total_matches, 2);
+}
+
+// Tests for merging port, PID and name matches of a numeric search query
+
+#[test]
+fn test_merge_search_results_numeric_query_matches_name_and_port() {
+    let lsof_output = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 user   20u  IPv4 0x1234      0t0  TCP *:3000 (LISTEN)
+node     1234 user   21u  IPv6 0x5678      0t0  TCP *:3000 (LISTEN)"#;
+    let ps_output = "  1234 node\n  4321 server3000\n  5555 nginx\n";
+    
+    let port_listeners = parse_lsof_output(lsof_output, "3000");
+    let name_matches = parse_ps_output(ps_output, "3000");
+    
+    let merged = merge_search_results(vec![port_listeners, vec![], name_matches]);
+    
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0].pid, "1234");
+    assert_eq!(merged[0].port, "3000");
+    assert_eq!(merged[1].pid, "4321");
+    assert_eq!(merged[1].port, "Unknown");
+    assert_eq!(merged[1].match_range, Some((6, 10)));
+}
+
+#[test]
+fn test_merge_search_results_dedups_by_pid() {
+    let listener = ProcessInfo { pid: "3000".to_string(), name: "node".to_string(), port: "8080".to_string(), ..Default::default() };
+    let by_pid = ProcessInfo { pid: "3000".to_string(), name: "node".to_string(), port: "Unknown".to_string(), ..Default::default() };
+    let by_name = ProcessInfo { pid: "3000".to_string(), name: "node".to_string(), port: "Unknown".to_string(), match_range: Some((0, 4)), score: None, socket_type: None, fd: None, fd_type: None, note: None, depth: None, is_privileged_port: false };
+    
+    let merged = merge_search_results(vec![vec![listener], vec![by_pid], vec![by_name]]);
+    
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].port, "8080");
+    assert_eq!(merged[0].match_range, Some((0, 4)));
+}
+
+// Tests for finding zombie children to reap
+
+#[test]
+fn test_find_zombie_children() {
+    let ps_output = r#"    1     0 Ss
+  500     1 S
+  501   500 Z
+  502   500 Z+
+  503   500 S
+  504   600 Z
+  505   500 R+
+  506   500 Zs"#;
+    
+    assert_eq!(find_zombie_children(ps_output, 500), vec![501, 502, 506]);
+    assert_eq!(find_zombie_children(ps_output, 600), vec![504]);
+    assert!(find_zombie_children(ps_output, 1).is_empty());
+}
+
+#[test]
+fn test_find_zombie_children_skips_malformed_lines() {
+    let ps_output = "  501   500\nabc 500 Z\n  502   500 Z\n\n";
+    
+    assert_eq!(find_zombie_children(ps_output, 500), vec![502]);
+}
+
+// Tests for ISO start timestamps computed from elapsed time
+
+#[test]
+fn test_parse_etime_formats() {
+    assert_eq!(parse_etime("00:05"), Some(5));
+    assert_eq!(parse_etime("12:34"), Some(12 * 60 + 34));
+    assert_eq!(parse_etime("01:02:03"), Some(3600 + 2 * 60 + 3));
+    assert_eq!(parse_etime("2-01:02:03"), Some(2 * 86_400 + 3600 + 2 * 60 + 3));
+    assert_eq!(parse_etime("  3-00:00:00 "), Some(3 * 86_400));
+}
+
+#[test]
+fn test_parse_etime_invalid() {
+    assert_eq!(parse_etime(""), None);
+    assert_eq!(parse_etime("abc"), None);
+    assert_eq!(parse_etime("5"), None);
+    assert_eq!(parse_etime("1:2:3:4"), None);
+    assert_eq!(parse_etime("x-01:00"), None);
+}
+
+#[test]
+fn test_start_time_iso_from_etime() {
+    // 1700000000 is 2023-11-14T22:13:20Z; the process has been running 1 day, 1 hour
+    assert_eq!(start_time_iso(1_700_000_000, "1-01:00:00").as_deref(), Some("2023-11-13T21:13:20Z"));
+    assert_eq!(start_time_iso(1_700_000_000, "00:00").as_deref(), Some("2023-11-14T22:13:20Z"));
+}
+
+#[test]
+fn test_start_time_iso_rejects_bad_input() {
+    assert_eq!(start_time_iso(1_700_000_000, "garbage"), None);
+    // Elapsed time longer than the clock allows
+    assert_eq!(start_time_iso(10, "01:00"), None);
+}
+
+// Tests for the port watch registry
+
+#[test]
+fn test_watch_registry_add_and_remove() {
+    let mut registry = WatchRegistry::default();
+    let handle = WatchHandle::default();
+    
+    assert!(!registry.add(3000, handle.clone()));
+    assert!(registry.contains(3000));
+    assert_eq!(registry.len(), 1);
+    
+    assert!(registry.remove(3000));
+    assert!(handle.is_cancelled());
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn test_watch_registry_remove_is_idempotent() {
+    let mut registry = WatchRegistry::default();
+    registry.add(3000, WatchHandle::default());
+    
+    assert!(registry.remove(3000));
+    assert!(!registry.remove(3000));
+    assert!(!registry.remove(8080));
+}
+
+#[test]
+fn test_watch_registry_replacing_cancels_previous() {
+    let mut registry = WatchRegistry::default();
+    let first = WatchHandle::default();
+    let second = WatchHandle::default();
+    
+    registry.add(3000, first.clone());
+    assert!(registry.add(3000, second.clone()));
+    
+    assert!(first.is_cancelled());
+    assert!(!second.is_cancelled());
+    assert_eq!(registry.len(), 1);
+}
+
+#[test]
+fn test_watch_registry_abort_all() {
+    let mut registry = WatchRegistry::default();
+    let handles: Vec<WatchHandle> = (0..3).map(|_| WatchHandle::default()).collect();
+    for (port, handle) in [3000, 3001, 3002].into_iter().zip(&handles) {
+        registry.add(port, handle.clone());
+    }
+    
+    assert_eq!(registry.abort_all(), 3);
+    assert!(handles.iter().all(|h| h.is_cancelled()));
+    assert!(registry.is_empty());
+    assert_eq!(registry.abort_all(), 0);
+}
+
+// Tests for adaptive watch polling
+
+#[test]
+fn test_adaptive_interval_backs_off_and_resets() {
+    let ms = Duration::from_millis;
+    let mut interval = AdaptiveInterval::new(ms(250), ms(4_000));
+    assert_eq!(interval.current(), ms(250));
+    
+    let polls = [true, false, false, false, false, false, false, true, false];
+    let intervals: Vec<u64> = polls.iter().map(|&changed| interval.next(changed).as_millis() as u64).collect();
+    
+    assert_eq!(intervals, vec![250, 500, 1_000, 2_000, 4_000, 4_000, 4_000, 250, 500]);
+}
+
+#[test]
+fn test_adaptive_interval_max_below_min() {
+    let mut interval = AdaptiveInterval::new(Duration::from_millis(500), Duration::from_millis(100));
+    assert_eq!(interval.next(false), Duration::from_millis(500));
+}
+
+#[test]
+fn test_watch_intervals() {
+    let ms = Duration::from_millis;
+    assert_eq!(watch_intervals(None, None, None), (ms(WATCH_PORT_FAST_INTERVAL_MS), ms(WATCH_PORT_SLOW_INTERVAL_MS)));
+    
+    // A lone interval keeps the fixed rate callers asked for before
+    assert_eq!(watch_intervals(Some(1_000), None, None), (ms(1_000), ms(1_000)));
+    
+    assert_eq!(watch_intervals(Some(1_000), None, Some(8_000)), (ms(1_000), ms(8_000)));
+    assert_eq!(watch_intervals(None, Some(200), Some(2_000)), (ms(200), ms(2_000)));
+    assert_eq!(watch_intervals(None, Some(10), Some(50)), (ms(WATCH_PORT_MIN_INTERVAL_MS), ms(WATCH_PORT_MIN_INTERVAL_MS)));
+}
+
+// Tests for the kill allowlist
+
+fn kiosk_allowlist() -> Vec<String> {
+    vec!["node".to_string(), "Python".to_string()]
+}
+
+#[test]
+fn test_check_allowlist_allows_listed_names() {
+    assert!(check_allowlist("1234", Some("node"), &kiosk_allowlist()).is_ok());
+    assert!(check_allowlist("1234", Some("python"), &kiosk_allowlist()).is_ok());
+    assert!(check_allowlist("1234", Some("/usr/local/bin/node"), &kiosk_allowlist()).is_ok());
+}
+
+#[test]
+fn test_check_allowlist_rejects_unlisted_names() {
+    let result = check_allowlist("1234", Some("postgres"), &kiosk_allowlist());
+    
+    match result {
+        Err(ProcessError::NotAllowed { pid, name }) => {
+            assert_eq!(pid, "1234");
+            assert_eq!(name.as_deref(), Some("postgres"));
+        }
+        other => panic!("expected NotAllowed, got {:?}", other),
+    }
+    
+    // Substrings of allowed names don't count
+    assert!(check_allowlist("1234", Some("nodemon"), &kiosk_allowlist()).is_err());
+    // A PID whose name can't be resolved is refused
+    assert!(check_allowlist("1234", None, &kiosk_allowlist()).is_err());
+}
+
+#[test]
+fn test_kill_process_with_signal_enforces_allowlist() {
+    // The test binary isn't on the allowlist, so it is refused before any signal is sent
+    let result = kill_process_with_signal(std::process::id().to_string(), false, None, None, false, &kiosk_allowlist());
+    
+    assert!(matches!(result, Err(ProcessError::NotAllowed { .. })), "{:?}", result);
+}
+
+#[test]
+fn test_check_allowlist_empty_allows_everything() {
+    assert!(check_allowlist("1234", Some("postgres"), &[]).is_ok());
+    assert!(check_allowlist("1234", None, &[]).is_ok());
+}
+
+// Tests for the killable hint derived from the process state
+
+#[test]
+fn test_is_killable_state_running_and_sleeping() {
+    for stat in ["R", "R+", "S", "Ss", "S<l", "I", "T", "Ssl+"] {
+        assert!(is_killable_state(stat), "{} should be killable", stat);
+    }
+}
+
+#[test]
+fn test_is_killable_state_uninterruptible_and_zombie() {
+    for stat in ["D", "D+", "Ds", "Z", "Z+"] {
+        assert!(!is_killable_state(stat), "{} should not be killable", stat);
+    }
+}
+
+#[test]
+fn test_process_detail_defaults_to_killable() {
+    assert!(ProcessDetail::default().is_killable);
+}
+
+#[test]
+fn test_smart_kill_failure_mentions_stuck_syscall() {
+    let status = smart_kill_with("1234", SMART_KILL_TEST_GRACE, |_, _| Ok("ok".to_string()), |_| true, |_| {});
+    
+    match status {
+        SmartKillStatus::Failed(message) => assert!(message.contains("uninterruptible")),
+        other => panic!("expected Failed, got {:?}", other),
+    }
+}
+
+// Tests for killing every listener in a port range
+
+const PORT_RANGE_LSOF: &str = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 user   20u  IPv4 0x1234      0t0  TCP *:3000 (LISTEN)
+node     1234 user   21u  IPv4 0x1235      0t0  TCP *:3001 (LISTEN)
+python   2222 user    5u  IPv4 0x2222      0t0  TCP *:3005 (LISTEN)
+launchd     1 root    7u  IPv4 0x1111      0t0  TCP *:3007 (LISTEN)
+self      999 user    8u  IPv4 0x9999      0t0  TCP *:3008 (LISTEN)
+nginx    4444 user    6u  IPv4 0x4444      0t0  TCP *:8080 (LISTEN)"#;
+
+#[test]
+fn test_kill_port_range_expands_and_kills() {
+    let killed = RefCell::new(Vec::new());
+    let ports = parse_port_pattern("3000-3010").unwrap();
+    
+    let outcomes = kill_port_range_with(ports, parse_listening_ports(PORT_RANGE_LSOF), true, false, 999, |pid, force| {
+        killed.borrow_mut().push((pid.clone(), force));
+        Ok(format!("Process {} force killed successfully", pid))
+    });
+    
+    // Each PID is signalled once, nothing outside the range is touched
+    assert_eq!(*killed.borrow(), vec![("1234".to_string(), true), ("2222".to_string(), true)]);
+    
+    let summary: Vec<(u16, &str, bool, bool)> = outcomes.iter()
+        .map(|o| (o.port, o.pid.as_str(), o.skipped, o.success))
+        .collect();
+    assert_eq!(summary, vec![
+        (3000, "1234", false, true),
+        (3001, "1234", false, true),
+        (3005, "2222", false, true),
+        (3007, "1", true, false),
+        (3008, "999", true, false),
+    ]);
+}
+
+#[test]
+fn test_kill_port_range_dry_run_signals_nothing() {
+    let ports = parse_port_pattern("3000-3005").unwrap();
+    
+    let outcomes = kill_port_range_with(ports, parse_listening_ports(PORT_RANGE_LSOF), false, true, 999, |_, _| {
+        panic!("dry run must not kill")
+    });
+    
+    assert_eq!(outcomes.len(), 3);
+    assert!(outcomes.iter().all(|o| o.skipped && o.success));
+    assert!(outcomes[0].message.contains("Would terminate PID 1234"));
+}
+
+#[test]
+fn test_kill_port_range_reports_failures() {
+    let ports = parse_port_pattern("3005").unwrap();
+    
+    let outcomes = kill_port_range_with(ports, parse_listening_ports(PORT_RANGE_LSOF), false, false, 999, |_, _| {
+        Err(ProcessError::command_failed("Operation not permitted"))
+    });
+    
+    assert_eq!(outcomes.len(), 1);
+    assert!(!outcomes[0].success);
+    assert!(outcomes[0].message.contains("not permitted"));
+}
+
+// Tests for multi-criteria kill queries
+
+const KILL_QUERY_PS: &str = "    1 100-02:00:00 root     /sbin/launchd
+ 1234    05:00 alice    /usr/local/bin/node
+ 2222    00:30 alice    node
+ 3333    10:00 bob      node
+ 4444    20:00 alice    /usr/bin/python3
+ 5555    15:00 alice    Google Chrome Helper
+";
+
+#[test]
+fn test_parse_kill_candidates() {
+    let candidates = parse_kill_candidates(KILL_QUERY_PS);
+    
+    assert_eq!(candidates.len(), 6);
+    assert_eq!(candidates[1].pid, "1234");
+    assert_eq!(candidates[1].name, "node");
+    assert_eq!(candidates[1].user, "alice");
+    assert_eq!(candidates[1].age_seconds, Some(300));
+    assert_eq!(candidates[5].name, "Google Chrome Helper");
+}
+
+#[test]
+fn test_resolve_kill_query_intersects_criteria() {
+    let query = KillQuery {
+        name: Some("node*".to_string()),
+        user: Some("alice".to_string()),
+        ports: Some("3000-3010".to_string()),
+        min_age_seconds: Some(60),
+    };
+    let listeners = parse_listening_ports(r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 alice  20u  IPv4 0x1234      0t0  TCP *:3000 (LISTEN)
+node     1234 alice  21u  IPv4 0x1235      0t0  TCP *:9229 (LISTEN)
+node     2222 alice  20u  IPv4 0x2222      0t0  TCP *:3001 (LISTEN)
+node     3333 bob    20u  IPv4 0x3333      0t0  TCP *:3002 (LISTEN)
+python3  4444 alice   5u  IPv4 0x4444      0t0  TCP *:3003 (LISTEN)"#);
+    
+    let matches = resolve_kill_query(&query, parse_kill_candidates(KILL_QUERY_PS), &listeners, 999).unwrap();
+    
+    // 2222 is too young, 3333 belongs to bob, 4444 isn't node, 5555 isn't listening
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].pid, "1234");
+    assert_eq!(matches[0].ports, vec![3000]);
+}
+
+#[test]
+fn test_resolve_kill_query_single_criterion() {
+    let query = KillQuery { user: Some("alice".to_string()), ..KillQuery::default() };
+    let matches = resolve_kill_query(&query, parse_kill_candidates(KILL_QUERY_PS), &[], 999).unwrap();
+    let pids: Vec<&str> = matches.iter().map(|m| m.pid.as_str()).collect();
+    assert_eq!(pids, vec!["1234", "2222", "4444", "5555"]);
+    
+    // Critical PIDs never match, however broad the query
+    let query = KillQuery { min_age_seconds: Some(0), ..KillQuery::default() };
+    let matches = resolve_kill_query(&query, parse_kill_candidates(KILL_QUERY_PS), &[], 999).unwrap();
+    assert!(matches.iter().all(|m| m.pid != "1"));
+    assert_eq!(matches.len(), 5);
+}
+
+#[test]
+fn test_resolve_kill_query_rejects_empty_and_invalid() {
+    let empty = KillQuery { name: Some("  ".to_string()), ..KillQuery::default() };
+    assert!(resolve_kill_query(&empty, vec![], &[], 999).is_err());
+    
+    let bad_ports = KillQuery { ports: Some("abc".to_string()), ..KillQuery::default() };
+    assert!(matches!(
+        resolve_kill_query(&bad_ports, vec![], &[], 999),
+        Err(ProcessError::InvalidPortPattern { .. })
+    ));
+}
+
+// Tests for structured connection info
+
+#[test]
+fn test_connection_info_serializes_camel_case() {
+    let connection = ConnectionInfo {
+        pid: "1234".to_string(),
+        name: "node".to_string(),
+        user: "user".to_string(),
+        protocol: "TCP".to_string(),
+        address: "127.0.0.1".to_string(),
+        port: Some(3000),
+        state: Some("LISTEN".to_string()),
+        fd: Some("20u".to_string()),
+        fd_type: Some("IPv4".to_string()),
+        zone: None,
+    };
+    
+    let json = serde_json::to_value(&connection).unwrap();
+    assert_eq!(json, serde_json::json!({
+        "pid": "1234",
+        "name": "node",
+        "user": "user",
+        "protocol": "TCP",
+        "address": "127.0.0.1",
+        "port": 3000,
+        "state": "LISTEN",
+        "fd": "20u",
+        "fdType": "IPv4",
+        "zone": null
+    }));
+    
+    let back: ConnectionInfo = serde_json::from_value(json).unwrap();
+    assert_eq!(back, connection);
+}
+
+#[test]
+fn test_parse_connection_info() {
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 user   20u  IPv4 0x1234      0t0  TCP *:3000 (LISTEN)
+node     1234 user   21u  IPv4 0x1235      0t0  TCP 127.0.0.1:3000->127.0.0.1:51000 (ESTABLISHED)
+mDNSResp  321 root   23u  IPv6 0x1237      0t0  UDP [::1]:5353
+ntpd      400 root   24u  IPv4 0x1238      0t0  UDP *:*"#;
+    
+    let connections = parse_connection_info(output);
+    
+    assert_eq!(connections.len(), 4);
+    assert_eq!(connections[0].address, "*");
+    assert_eq!(connections[0].port, Some(3000));
+    assert_eq!(connections[0].state.as_deref(), Some("LISTEN"));
+    
+    assert_eq!(connections[1].address, "127.0.0.1");
+    assert_eq!(connections[1].state.as_deref(), Some("ESTABLISHED"));
+    
+    assert_eq!(connections[2].pid, "321");
+    assert_eq!(connections[2].user, "root");
+    assert_eq!(connections[2].protocol, "UDP");
+    assert_eq!(connections[2].address, "::1");
+    assert_eq!(connections[2].port, Some(5353));
+    assert_eq!(connections[2].state, None);
+    
+    assert_eq!(connections[3].address, "*");
+    assert_eq!(connections[3].port, None);
+    assert!(connections.iter().all(|c| c.zone.is_none()));
+}
+
+#[test]
+fn test_parse_connection_info_name_not_at_index_eight() {
+    // SIZE/OFF is missing, so NAME sits at index 7
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE NODE NAME
+java      4321 user   45u  IPv6 0xabcdef  TCP [::1]:8080 (LISTEN)
+mDNSResp   321 root   23u  IPv4 0x1237    UDP *:5353"#;
+    
+    let connections = parse_connection_info(output);
+    
+    assert_eq!(connections.len(), 2);
+    assert_eq!(connections[0].address, "::1");
+    assert_eq!(connections[0].port, Some(8080));
+    assert_eq!(connections[0].protocol, "TCP");
+    assert_eq!(connections[0].state.as_deref(), Some("LISTEN"));
+    assert_eq!(connections[1].port, Some(5353));
+    assert_eq!(connections[1].protocol, "UDP");
+    assert_eq!(connections[1].state, None);
+}
+
+#[test]
+fn test_parse_connection_info_link_local_zone() {
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 user   20u  IPv6 0x1234      0t0  TCP [fe80::1%en0]:8080 (LISTEN)
+node     1234 user   21u  IPv6 0x1235      0t0  TCP [fe80::1%en0]:8080->[fe80::2%en0]:52000 (ESTABLISHED)"#;
+    
+    let connections = parse_connection_info(output);
+    
+    assert_eq!(connections.len(), 2);
+    assert_eq!(connections[0].address, "fe80::1");
+    assert_eq!(connections[0].zone.as_deref(), Some("en0"));
+    assert_eq!(connections[0].port, Some(8080));
+    assert_eq!(connections[1].address, "fe80::1");
+    assert_eq!(connections[1].zone.as_deref(), Some("en0"));
+}
+
+#[test]
+fn test_split_zone() {
+    assert_eq!(split_zone("fe80::1%en0"), ("fe80::1", Some("en0")));
+    assert_eq!(split_zone("fe80::1%"), ("fe80::1%", None));
+    assert_eq!(split_zone("::1"), ("::1", None));
+    assert_eq!(split_zone("10.0.0.5"), ("10.0.0.5", None));
+    
+    // The port still comes off the bracketed endpoint as before
+    let (host, port) = split_host_port("[fe80::1%en0]:8080").unwrap();
+    assert_eq!(split_zone(host), ("fe80::1", Some("en0")));
+    assert_eq!(port, "8080");
+    assert_eq!(extract_port("[fe80::1%en0]:8080"), Some(8080));
+}
+
+// Tests for fuzzy process name search
+
+const FUZZY_PS_OUTPUT: &str = "  100 nginx\n  101 nginx: worker process\n  200 postgres\n  300 engine\n";
+
+#[test]
+fn test_fuzzy_search_finds_near_miss() {
+    let options = SearchOptions {
+        fuzzy: true,
+        ..SearchOptions::for_term("ngnix")
+    };
+    
+    let (processes, total_matches) = count_ps_stream(FUZZY_PS_OUTPUT.as_bytes(), &options);
+    
+    assert_eq!(total_matches, 2);
+    let pids: Vec<&str> = processes.iter().map(|p| p.pid.as_str()).collect();
+    assert_eq!(pids, vec!["100", "101"]);
+    assert!(processes.iter().all(|p| p.score.unwrap() >= FUZZY_THRESHOLD));
+    // The best-matching word is highlighted
+    assert_eq!(processes[1].match_range, Some((0, 5)));
+}
+
+#[test]
+fn test_fuzzy_search_rejects_distant_query() {
+    let options = SearchOptions {
+        fuzzy: true,
+        ..SearchOptions::for_term("redis")
+    };
+    
+    let (processes, total_matches) = count_ps_stream(FUZZY_PS_OUTPUT.as_bytes(), &options);
+    
+    assert_eq!(total_matches, 0);
+    assert!(processes.is_empty());
+}
+
+#[test]
+fn test_fuzzy_search_ranks_by_score_before_capping() {
+    let options = SearchOptions {
+        fuzzy: true,
+        fuzzy_threshold: 0.5,
+        max_results: Some(1),
+        ..SearchOptions::for_term("postgre")
+    };
+    
+    let (processes, total_matches) = count_ps_stream(FUZZY_PS_OUTPUT.as_bytes(), &options);
+    
+    assert!(total_matches >= 1);
+    assert_eq!(processes.len(), 1);
+    assert_eq!(processes[0].pid, "200");
+    assert_eq!(processes[0].score, Some(1.0));
+}
+
+#[test]
+fn test_fuzzy_score_substring_is_exact() {
+    assert_eq!(fuzzy_score("nginx", "gin"), (1.0, Some((1, 4))));
+    assert!(fuzzy_score("nginx", "ngnix").0 >= FUZZY_THRESHOLD);
+    assert!(fuzzy_score("nginx", "redis").0 < FUZZY_THRESHOLD);
+    assert_eq!(fuzzy_score("", "nginx"), (0.0, None));
+}
+
+#[test]
+fn test_substring_search_has_no_score() {
+    let (processes, _) = count_ps_stream(FUZZY_PS_OUTPUT.as_bytes(), &SearchOptions::for_term("ngnix"));
+    assert!(processes.is_empty());
+    
+    let (processes, _) = count_ps_stream(FUZZY_PS_OUTPUT.as_bytes(), &SearchOptions::for_term("nginx"));
+    assert_eq!(processes.len(), 2);
+    assert!(processes.iter().all(|p| p.score.is_none()));
+}
+
+// Tests for aggregate resource totals of search results
+
+#[test]
+fn test_sum_resource_rows() {
+    let output = " 101  12.5  1.2  102400\n 102   0.0  0.3   20480\n 103 150,5  2.0  409600\n";
+    
+    let totals = sum_resource_rows(output);
+    
+    assert!((totals.total_cpu.unwrap() - 163.0).abs() < 1e-9);
+    assert!((totals.total_mem.unwrap() - 3.5).abs() < 1e-9);
+    assert_eq!(totals.total_rss_bytes, Some((102400 + 20480 + 409600) * 1024));
+}
+
+#[test]
+fn test_sum_resource_rows_skips_malformed() {
+    let totals = sum_resource_rows("garbage\n 101 abc 1.0 xyz\n");
+    assert_eq!(totals.total_cpu, None);
+    assert_eq!(totals.total_mem, Some(1.0));
+    assert_eq!(totals.total_rss_bytes, None);
+    
+    assert_eq!(sum_resource_rows(""), ResourceTotals::default());
+}
+
+#[test]
+fn test_search_result_totals_serialize_flat() {
+    let result = ProcessSearchResult {
+        processes: vec![],
+        total_matches: 0,
+        totals: ResourceTotals {
+            total_cpu: Some(12.5),
+            total_mem: Some(1.5),
+            total_rss_bytes: Some(2048),
+        },
+        error: None,
+        duration_ms: None,
+        output_truncated: false,
+        schema_version: SCHEMA_VERSION,
+    };
+    
+    let value = serde_json::to_value(&result).unwrap();
+    assert_eq!(value["total_cpu"], 12.5);
+    assert_eq!(value["total_mem"], 1.5);
+    assert_eq!(value["total_rss_bytes"], 2048);
+    
+    // Older payloads without totals still deserialize
+    let old: ProcessSearchResult = serde_json::from_str(r#"{"processes":[],"total_matches":0,"error":null}"#).unwrap();
+    assert_eq!(old.totals, ResourceTotals::default());
+}
+
+// Tests for validating custom signals against the platform's signal set
+
+#[test]
+fn test_parse_signal_accepts_names_and_numbers() {
+    assert_eq!(parse_signal("TERM").unwrap(), "TERM");
+    assert_eq!(parse_signal("sigterm").unwrap(), "TERM");
+    assert_eq!(parse_signal(" SIGHUP ").unwrap(), "HUP");
+    assert_eq!(parse_signal("9").unwrap(), "KILL");
+    assert_eq!(parse_signal("15").unwrap(), "TERM");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_parse_signal_platform_specific_linux() {
+    assert_eq!(parse_signal("PWR").unwrap(), "PWR");
+    assert_eq!(parse_signal("10").unwrap(), "USR1");
+    assert!(matches!(parse_signal("INFO"), Err(ProcessError::InvalidSignal { .. })));
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn test_parse_signal_platform_specific_macos() {
+    assert_eq!(parse_signal("INFO").unwrap(), "INFO");
+    assert_eq!(parse_signal("30").unwrap(), "USR1");
+    assert!(matches!(parse_signal("PWR"), Err(ProcessError::InvalidSignal { .. })));
+}
+
+#[test]
+fn test_parse_signal_rejects_unknown_with_valid_list() {
+    let error = parse_signal("BOGUS").unwrap_err();
+    
+    assert_eq!(error.kind(), "InvalidSignal");
+    let message = error.to_string();
+    assert!(message.contains("'BOGUS'"));
+    for (name, _) in SIGNALS {
+        assert!(message.contains(name), "missing {} in: {}", name, message);
+    }
+    
+    assert!(parse_signal("0").is_err());
+    assert!(parse_signal("99").is_err());
+    assert!(parse_signal("").is_err());
+}
+
+// Tests for the raw command tool allowlist
+
+fn raw_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn test_raw_command_allows_listed_tools() {
+    assert!(validate_raw_command("lsof", &raw_args(&["-iTCP", "-sTCP:LISTEN", "-P", "-n"])).is_ok());
+    assert!(validate_raw_command("ps", &raw_args(&["-A", "-o", "pid=,comm=,args="])).is_ok());
+    assert!(validate_raw_command("ss", &raw_args(&["-tlnp"])).is_ok());
+    assert!(validate_raw_command("lsof", &raw_args(&["-nPcfirefox"])).is_ok());
+    assert!(validate_raw_command("ps", &[]).is_ok());
+}
+
+#[test]
+fn test_raw_command_rejects_arbitrary_binaries() {
+    for tool in ["sh", "bash", "rm", "/bin/ps", "kill", "lsof ", ""] {
+        let error = validate_raw_command(tool, &raw_args(&["-A"])).unwrap_err();
+        assert_eq!(error.kind(), "InvalidCommand");
+        assert!(error.to_string().contains("only lsof, ps, ss can be run"), "{}", error);
+    }
+}
+
+#[test]
+fn test_raw_command_rejects_unsafe_args() {
+    assert!(validate_raw_command("lsof", &raw_args(&["-r", "1"])).is_err());
+    assert!(validate_raw_command("lsof", &raw_args(&["+r1"])).is_err());
+    assert!(validate_raw_command("ss", &raw_args(&["-K", "dport", "=", "3000"])).is_err());
+    assert!(validate_raw_command("ss", &raw_args(&["--diag=/tmp/out"])).is_err());
+    assert!(validate_raw_command("ss", &raw_args(&["-tK", "dport", "=", "3000"])).is_err());
+    assert!(validate_raw_command("ss", &raw_args(&["-tlnD", "/tmp/out"])).is_err());
+    assert!(validate_raw_command("lsof", &raw_args(&["-nPr1"])).is_err());
+    assert!(validate_raw_command("lsof", &raw_args(&["+Pr"])).is_err());
+    assert!(validate_raw_command("ps", &raw_args(&["-A\n-o"])).is_err());
+    assert!(validate_raw_command("ps", &raw_args(&[&"x".repeat(300)])).is_err());
+    assert!(validate_raw_command("ps", &vec!["-A".to_string(); 33]).is_err());
+}
+
+// Tests for ps rows with spaced or truncated user names
+
+#[test]
+fn test_split_leading_fields_keeps_spaced_user() {
+    let row = " 1234  0.5  1.2 Ss   John Smith\n";
+    
+    let (fields, user) = split_leading_fields(row, 4).unwrap();
+    
+    assert_eq!(fields, vec!["1234", "0.5", "1.2", "Ss"]);
+    assert_eq!(parse_ps_user(user), Some(("John Smith".to_string(), false)));
+}
+
+#[test]
+fn test_split_leading_fields_keeps_command_spacing() {
+    let (fields, command) = split_leading_fields("  42 node /usr/bin/node  server.js --port 3000", 2).unwrap();
+    
+    assert_eq!(fields, vec!["42", "node"]);
+    assert_eq!(command, "/usr/bin/node  server.js --port 3000");
+    
+    assert!(split_leading_fields("  42", 2).is_none());
+    assert_eq!(split_leading_fields("42 node", 2).unwrap().1, "");
+}
+
+#[test]
+fn test_parse_ps_user_detects_truncation() {
+    let (fields, user) = split_leading_fields(" 1234  0.0  0.1 S    CORP\\jsmit+", 4).unwrap();
+    
+    assert_eq!(fields[3], "S");
+    assert_eq!(parse_ps_user(user), Some(("CORP\\jsmit".to_string(), true)));
+    assert_eq!(parse_ps_user("root"), Some(("root".to_string(), false)));
+    assert_eq!(parse_ps_user("+"), Some(("+".to_string(), false)));
+    assert_eq!(parse_ps_user("   "), None);
+}
+
+// Tests for the listening port summary
+
+#[test]
+fn test_summarize_ports_mixed_dataset() {
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 user   20u  IPv4 0x1234      0t0  TCP *:3000 (LISTEN)
+node     1234 user   21u  IPv4 0x1235      0t0  TCP 127.0.0.1:3000->127.0.0.1:51000 (ESTABLISHED)
+postgres  200 user    5u  IPv6 0x2001      0t0  TCP [::1]:5432 (LISTEN)
+postgres  200 user    6u  IPv4 0x2002      0t0  TCP 127.0.0.1:5432 (LISTEN)
+nginx     300 root    7u  IPv4 0x3001      0t0  TCP 0.0.0.0:80 (LISTEN)
+api       400 user    8u  IPv4 0x4001      0t0  TCP 192.168.1.10:8080 (LISTEN)
+mDNSResp  500 root    9u  IPv4 0x5001      0t0  UDP *:5353
+dnsmasq   600 root   10u  IPv4 0x6001      0t0  UDP 127.0.0.1:53
+ntpd      700 root   11u  IPv4 0x7001      0t0  UDP *:*"#;
+    
+    let summary = summarize_ports(&parse_connection_info(output));
+    
+    assert_eq!(summary, PortSummary {
+        tcp_listeners: 5,
+        udp_listeners: 2,
+        loopback: 3,
+        wildcard: 3,
+        specific: 1,
+    });
+}
+
+#[test]
+fn test_bind_scope() {
+    assert_eq!(bind_scope("*"), BindScope::Wildcard);
+    assert_eq!(bind_scope("0.0.0.0"), BindScope::Wildcard);
+    assert_eq!(bind_scope("::"), BindScope::Wildcard);
+    assert_eq!(bind_scope("127.0.0.1"), BindScope::Loopback);
+    assert_eq!(bind_scope("127.0.1.1"), BindScope::Loopback);
+    assert_eq!(bind_scope("::1"), BindScope::Loopback);
+    assert_eq!(bind_scope("localhost"), BindScope::Loopback);
+    assert_eq!(bind_scope("10.0.0.5"), BindScope::Specific);
+    assert_eq!(bind_scope("fe80::1%en0"), BindScope::Specific);
+}
+
+// Tests for ps output that includes a header row
+
+#[test]
+fn test_parse_ps_output_skips_header() {
+    let with_header = "  PID COMMAND\n  100 command-runner\n  200 nginx\n";
+    let without_header = "  100 command-runner\n  200 nginx\n";
+    
+    for output in [with_header, without_header] {
+        let processes = parse_ps_output(output, "comm");
+        assert_eq!(processes.len(), 1, "{:?}", output);
+        assert_eq!(processes[0].pid, "100");
+        
+        assert_eq!(parse_ps_output(output, "nginx").len(), 1);
+    }
+}
+
+#[test]
+fn test_parse_ps_output_header_with_leading_blank_line() {
+    let output = "\n  PID COMMAND\n  100 node\n";
+    
+    assert!(parse_ps_output(output, "command").is_empty());
+    assert_eq!(parse_ps_output(output, "node").len(), 1);
+}
+
+// Tests for killing by executable path
+
+#[test]
+fn test_filter_by_exe_path_exact_match_only() {
+    let candidate = |pid: &str| ProcessInfo {
+        pid: pid.to_string(),
+        name: "node".to_string(),
+        port: "Unknown".to_string(),
+        ..Default::default()
+    };
+    let candidates = vec![candidate("100"), candidate("200"), candidate("300"), candidate("400"), candidate("500")];
+    
+    let matched = filter_by_exe_path(candidates, "/usr/local/bin/node", |pid| match pid {
+        "100" => Some("/usr/local/bin/node".to_string()),
+        "200" => Some("/opt/homebrew/bin/node".to_string()),
+        "300" => Some("/usr/local/bin/node (deleted)".to_string()),
+        "400" => Some("/usr/local/bin/node-old".to_string()),
+        _ => None, // Exited, or not ours to inspect
+    });
+    
+    let pids: Vec<&str> = matched.iter().map(|p| p.pid.as_str()).collect();
+    assert_eq!(pids, vec!["100", "300"]);
+}
+
+#[test]
+fn test_protected_pid_reason() {
+    assert_eq!(protected_pid_reason("1", 999), Some("it is a critical system process"));
+    assert_eq!(protected_pid_reason("999", 999), Some("it is this application"));
+    assert_eq!(protected_pid_reason("1234", 999), None);
+    assert_eq!(protected_pid_reason("abc", 999), None);
+}
+
+// Tests for humanized uptime strings
+
+#[test]
+fn test_format_uptime() {
+    assert_eq!(format_uptime(0), "0s");
+    assert_eq!(format_uptime(45), "45s");
+    assert_eq!(format_uptime(60), "1m");
+    assert_eq!(format_uptime(3599), "59m");
+    assert_eq!(format_uptime(3700), "1h 1m");
+    assert_eq!(format_uptime(8040), "2h 14m");
+    assert_eq!(format_uptime(90000), "1d 1h");
+    assert_eq!(format_uptime(30 * 86_400), "30d 0h");
+}
+
+#[test]
+fn test_format_uptime_from_etime() {
+    assert_eq!(parse_etime("02:14:00").map(format_uptime).as_deref(), Some("2h 14m"));
+    assert_eq!(parse_etime("1-01:00:00").map(format_uptime).as_deref(), Some("1d 1h"));
+}
+
+// Tests for Unix domain socket checks
+
+#[test]
+fn test_parse_unix_socket_output_linux() {
+    let output = r#"COMMAND   PID USER   FD   TYPE             DEVICE SIZE/OFF    NODE NAME
+app      1234 user    3u  unix 0xffff888012345678      0t0  123456 /tmp/app.sock type=STREAM
+app      1234 user    7u  unix 0xffff888012345679      0t0  123457 /tmp/app.sock type=STREAM
+client   5678 user    4u  unix 0xffff88801234567a      0t0  123458 type=STREAM
+other    9999 user    5u  unix 0xffff88801234567b      0t0  123459 /tmp/app.sock.lock type=STREAM"#;
+    
+    let processes = parse_unix_socket_output(output, "/tmp/app.sock");
+    
+    assert_eq!(processes.len(), 1);
+    assert_eq!(processes[0].pid, "1234");
+    assert_eq!(processes[0].name, "app");
+    assert_eq!(processes[0].port, "/tmp/app.sock");
+    assert_eq!(processes[0].socket_type.as_deref(), Some("unix"));
+}
+
+#[test]
+fn test_parse_unix_socket_output_macos() {
+    let output = r#"COMMAND   PID USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+app      1234 user    3u  unix 0x1234567890abcdef      0t0      /tmp/app.sock
+app      4321 user    3u  unix 0x1234567890abcdf0      0t0      ->0x1234567890abcdef"#;
+    
+    let processes = parse_unix_socket_output(output, "/tmp/app.sock");
+    
+    assert_eq!(processes.len(), 1);
+    assert_eq!(processes[0].pid, "1234");
+    assert!(parse_unix_socket_output(output, "/tmp/missing.sock").is_empty());
+}
+
+#[test]
+fn test_lsof_rows_carry_socket_type() {
+    let processes = parse_listening_ports(TWO_LISTENERS_LSOF);
+    assert!(processes.iter().all(|p| p.socket_type.as_deref() == Some("TCP")));
+}
+
+// Tests for restarting a process
+
+#[test]
+fn test_split_command_line_plain() {
+    assert_eq!(
+        split_command_line("/usr/local/bin/node  server.js --port 3000"),
+        vec!["/usr/local/bin/node", "server.js", "--port", "3000"]
+    );
+    assert!(split_command_line("   ").is_empty());
+}
+
+#[test]
+fn test_split_command_line_quotes_and_escapes() {
+    assert_eq!(
+        split_command_line(r#"python -c 'print("hi there")' --name "my app" path\ with\ spaces """#),
+        vec!["python", "-c", "print(\"hi there\")", "--name", "my app", "path with spaces", ""]
+    );
+    assert_eq!(split_command_line(r#"echo "a\"b" 'c\d'"#), vec!["echo", "a\"b", "c\\d"]);
+}
+
+#[test]
+fn test_wait_for_exit() {
+    let polls = Cell::new(0);
+    let exited = poll_for_exit("1234", SMART_KILL_TEST_GRACE, |_| { polls.set(polls.get() + 1); polls.get() < 3 }, |_| {});
+    assert!(exited);
+    assert_eq!(polls.get(), 3);
+    
+    assert!(!poll_for_exit("1234", SMART_KILL_TEST_GRACE, |_| true, |_| {}));
+}
+
+// Tests for IPv4/IPv6 binding of a port
+
+#[test]
+fn test_port_binding_dual_stack() {
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 user   20u  IPv4 0x1234      0t0  TCP 127.0.0.1:3000 (LISTEN)
+node     1234 user   21u  IPv6 0x1235      0t0  TCP [::1]:3000 (LISTEN)"#;
+    
+    assert_eq!(port_binding(output).as_deref(), Some("dual"));
+    // Merging rows by PID must not hide the second family
+    assert_eq!(parse_lsof_output(output, "3000").len(), 1);
+}
+
+#[test]
+fn test_port_binding_ipv6_only() {
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 user   21u  IPv6 0x1235      0t0  TCP [::1]:3000 (LISTEN)"#;
+    
+    assert_eq!(port_binding(output).as_deref(), Some("ipv6"));
+}
+
+#[test]
+fn test_port_binding_ipv4_and_wildcard_v6() {
+    let ipv4 = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+nginx     300 root    7u  IPv4 0x3001      0t0  TCP 0.0.0.0:80 (LISTEN)"#;
+    let wildcard_v6 = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+java      400 user    8u  IPv6 0x4001      0t0  TCP *:8080 (LISTEN)"#;
+    
+    assert_eq!(port_binding(ipv4).as_deref(), Some("ipv4"));
+    assert_eq!(port_binding(wildcard_v6).as_deref(), Some("dual"));
+    assert_eq!(port_binding(""), None);
+}
+
+#[test]
+fn test_port_binding_name_not_at_index_eight() {
+    // SIZE/OFF is missing, so NAME sits at index 7
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE NODE NAME
+java      400 user    8u  IPv6 0x4001  TCP *:8080 (LISTEN)"#;
+    
+    assert_eq!(port_binding(output).as_deref(), Some("dual"));
+}
+
+#[test]
+fn test_port_cache_keeps_binding() {
+    let mut cache = PortPidCache::with_ttl(1_000);
+    let listener = ProcessInfo { pid: "1234".to_string(), ..Default::default() };
+    
+    cache.insert(3000, vec![listener], 0);
+    cache.set_binding(3000, Some("dual".to_string()));
+    
+    assert!(cache.lookup(3000, 500, |_| true).is_some());
+    assert_eq!(cache.binding(3000).as_deref(), Some("dual"));
+    assert_eq!(cache.binding(4000), None);
+}
+
+// Tests for the log output modes
+
+#[test]
+fn test_quiet_mode_drops_every_log_line() {
+    for line in ["[INFO] Starting port check for port: 3000", "[DEBUG] lsof raw output:\nnode 1234", "[ERROR] Failed", "no prefix"] {
+        assert_eq!(render_log_line(LogMode::Quiet, line, 0), LogOutput::Dropped);
+    }
+}
+
+#[test]
+fn test_text_mode_prints_to_stdout_unchanged() {
+    assert_eq!(
+        render_log_line(LogMode::Text, "[WARN] Skipping malformed line 3", 0),
+        LogOutput::Stdout("[WARN] Skipping malformed line 3".to_string())
+    );
+}
+
+#[test]
+fn test_json_mode_writes_structured_lines_to_stderr() {
+    let LogOutput::Stderr(line) = render_log_line(LogMode::Json, "[DEBUG] Executing command: lsof -i :3000", 1_700_000_000_000) else {
+        panic!("json logs must go to stderr");
+    };
+    
+    assert!(!line.contains('\n'));
+    let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(value["level"], "debug");
+    assert_eq!(value["message"], "Executing command: lsof -i :3000");
+    assert_eq!(value["timestamp"], 1_700_000_000_000u64);
+    
+    // Multi-line messages stay on one JSON line
+    let LogOutput::Stderr(line) = render_log_line(LogMode::Json, "[DEBUG] raw:\na\nb", 0) else { panic!() };
+    assert_eq!(line.lines().count(), 1);
+}
+
+#[test]
+fn test_log_mode_deserializes_lowercase() {
+    assert_eq!(serde_json::from_str::<LogMode>("\"quiet\"").unwrap(), LogMode::Quiet);
+    assert_eq!(serde_json::from_str::<LogMode>("\"json\"").unwrap(), LogMode::Json);
+    assert_eq!(LogMode::default(), LogMode::Text);
+}
+
+#[test]
+fn test_set_log_mode_quiet_still_returns_results() {
+    set_log_mode(LogMode::Quiet);
+    assert_eq!(log_mode(), LogMode::Quiet);
+    
+    // Parsing logs heavily; results are unaffected by where the logs go
+    let processes = parse_listening_ports(TWO_LISTENERS_LSOF);
+    
+    set_log_mode(LogMode::Text);
+    assert_eq!(log_mode(), LogMode::Text);
+    assert!(!processes.is_empty());
+}
+
+// Tests for finding processes by open file
+
+#[test]
+fn test_parse_lsof_file_output_dedups_by_pid() {
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF     NODE NAME
+app      1234 user    3w   REG    8,1    20480  1048577 /var/log/app.log
+app      1234 user    4w   REG    8,1    20480  1048577 /var/log/app.log
+tail     5678 user    3r   REG    8,1    20480  1048577 /var/log/app.log
+logrotat 9012 root    5u   REG    8,1    20480  1048577 /var/log/app.log"#;
+    
+    let processes = parse_lsof_file_output(output);
+    
+    let pids: Vec<&str> = processes.iter().map(|p| p.pid.as_str()).collect();
+    assert_eq!(pids, vec!["1234", "5678", "9012"]);
+    assert!(processes.iter().all(|p| p.port == "/var/log/app.log"));
+    assert_eq!(processes[1].name, "tail");
+}
+
+#[test]
+fn test_parse_lsof_file_output_path_with_spaces() {
+    let output = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF     NODE NAME
+Preview  4321 user   12r   REG    1,4   102400   555555 /Users/me/My Documents/report final.pdf"#;
+    
+    let processes = parse_lsof_file_output(output);
+    
+    assert_eq!(processes.len(), 1);
+    assert_eq!(processes[0].port, "/Users/me/My Documents/report final.pdf");
+    assert!(parse_lsof_file_output("").is_empty());
+}
+
+// Tests for the bulk liveness check
+
+#[test]
+fn test_parse_alive_pids_in_input_order() {
+    let requested: Vec<String> = ["300", "100", "200", "abc", "0", "-1", " 100 "]
+        .iter()
+        .map(|pid| pid.to_string())
+        .collect();
+    
+    let alive = parse_alive_pids("  100\n  300\n", &requested);
+    
+    assert_eq!(alive, vec![
+        ("300".to_string(), true),
+        ("100".to_string(), true),
+        ("200".to_string(), false),
+        ("abc".to_string(), false),
+        ("0".to_string(), false),
+        ("-1".to_string(), false),
+        (" 100 ".to_string(), true),
+    ]);
+}
+
+#[test]
+fn test_parse_alive_pids_empty_output() {
+    let requested = vec!["1234".to_string()];
+    assert_eq!(parse_alive_pids("", &requested), vec![("1234".to_string(), false)]);
+    assert!(parse_alive_pids("1234\n", &[]).is_empty());
+}
+
+// Tests for glob search terms
+
+const GLOB_PS_OUTPUT: &str = "  100 node\n  101 nodemon\n  102 node-api-server\n  103 my-node\n  200 python\n  201 python3\n  202 pyXhon\n";
+
+fn glob_search(term: &str) -> Vec<String> {
+    let options = SearchOptions {
+        glob: true,
+        ..SearchOptions::for_term(term)
+    };
+    count_ps_stream(GLOB_PS_OUTPUT.as_bytes(), &options).0.into_iter().map(|p| p.pid).collect()
+}
+
+#[test]
+fn test_glob_search_star() {
+    assert_eq!(glob_search("node*"), vec!["100", "101", "102"]);
+    assert_eq!(glob_search("node*server"), vec!["102"]);
+    assert_eq!(glob_search("NODE"), vec!["100"]);
+}
+
+#[test]
+fn test_glob_search_question_mark() {
+    assert_eq!(glob_search("py?hon"), vec!["200", "202"]);
+    
+    let options = SearchOptions { glob: true, ..SearchOptions::for_term("py?hon3") };
+    let (processes, _) = count_ps_stream(GLOB_PS_OUTPUT.as_bytes(), &options);
+    assert_eq!(processes[0].match_range, Some((0, 7)));
+}
+
+#[test]
+fn test_invalid_glob_is_rejected() {
+    let error = compile_globs(&["node[".to_string()]).unwrap_err();
+    
+    assert_eq!(error.kind(), "InvalidPattern");
+    assert!(error.to_string().contains("'node['"));
+    assert!(glob_search("node[").is_empty());
+}
+
+// Tests for whole-word search
+
+#[test]
+fn test_find_whole_word() {
+    assert_eq!(find_whole_word("/usr/bin/ssh host", "ssh"), Some((9, 12)));
+    assert_eq!(find_whole_word("ssh", "ssh"), Some((0, 3)));
+    assert_eq!(find_whole_word("sshd", "ssh"), None);
+    assert_eq!(find_whole_word("openssh-sftp-server", "ssh"), None);
+    assert_eq!(find_whole_word("ssh_agent", "ssh"), None);
+    
+    // A later bounded occurrence is found after an unbounded one
+    assert_eq!(find_whole_word("sshd ssh", "ssh"), Some((5, 8)));
+    assert_eq!(find_whole_word("SSH-Agent", "ssh"), Some((0, 3)));
+}
+
+#[test]
+fn test_whole_word_search() {
+    let ps_output = "  100 ssh\n  101 sshd\n  102 openssh-sftp-server\n  103 ssh-agent\n";
+    let search = |whole_word: bool| -> Vec<String> {
+        let options = SearchOptions { whole_word, ..SearchOptions::for_term("ssh") };
+        count_ps_stream(ps_output.as_bytes(), &options).0.into_iter().map(|p| p.pid).collect()
+    };
+    
+    assert_eq!(search(false), vec!["100", "101", "102", "103"]);
+    assert_eq!(search(true), vec!["100", "103"]);
+}
+
+// Tests for real vs effective user ids
+
+#[test]
+fn test_parse_user_ids_same_user() {
+    let (fields, command) = split_leading_fields("  4321  1000  1000 node     node server.js", 4).unwrap();
+    
+    let ids = parse_user_ids(fields[1], fields[2]).unwrap();
+    
+    assert_eq!((ids.ruid, ids.euid), (1000, 1000));
+    assert!(!ids.is_setuid());
+    assert_eq!(fields[3], "node");
+    assert_eq!(command, "node server.js");
+}
+
+#[test]
+fn test_parse_user_ids_setuid_differs() {
+    let (fields, _) = split_leading_fields("  5555  1000     0 passwd   passwd alice", 4).unwrap();
+    
+    let ids = parse_user_ids(fields[1], fields[2]).unwrap();
+    
+    assert_eq!((ids.ruid, ids.euid), (1000, 0));
+    assert!(ids.is_setuid());
+    assert!(parse_user_ids("-", "0").is_none());
+}
+
+// Tests for the process name histogram
+
+#[test]
+fn test_tally_process_names() {
+    let output = "chrome\nnode\nchrome\npython\n/Applications/Google Chrome.app/Contents/MacOS/Google Chrome\nchrome\nnode\n\n   \npython\nbash\nGoogle Chrome\n";
+    
+    let counts = tally_process_names(output);
+    
+    assert_eq!(counts, vec![
+        ("chrome".to_string(), 3),
+        ("Google Chrome".to_string(), 2),
+        ("node".to_string(), 2),
+        ("python".to_string(), 2),
+        ("bash".to_string(), 1),
+    ]);
+}
+
+#[test]
+fn test_tally_process_names_empty() {
+    assert!(tally_process_names("").is_empty());
+}
+
+// Tests for locating the port in lsof rows of varying width
+
+#[test]
+fn test_find_row_port_standard_row() {
+    let line = "node      1234 user   23u  IPv4 0x1234567890      0t0  TCP *:3000 (LISTEN)";
+    assert_eq!(find_row_port(line), Some(3000));
+}
+
+#[test]
+fn test_find_row_port_name_not_at_index_eight() {
+    // SIZE/OFF is missing, so NAME sits at index 7
+    let line = "java      4321 user   45u  IPv6 0xabcdef  TCP [::1]:8080 (LISTEN)";
+    assert_eq!(find_row_port(line), Some(8080));
+    
+    let lookup = interpret_port_lookup("4321", &format!("COMMAND PID USER FD TYPE DEVICE NODE NAME\n{}\n", line), "");
+    assert_eq!(lookup, PortLookup::Ports(vec!["8080".to_string()]));
+}
+
+#[test]
+fn test_find_row_port_connection_uses_local_side() {
+    let line = "curl 99 user 5u IPv4 0x1 0t0 TCP 10.0.0.2:51000->93.184.216.34:443 (ESTABLISHED)";
+    assert_eq!(find_row_port(line), Some(51000));
+}
+
+#[test]
+fn test_find_row_port_row_without_port() {
+    let line = "nginx     555 root   6u  sock  0,9      0t0  12345 protocol: TCP";
+    assert_eq!(find_row_port(line), None);
+    assert_eq!(find_row_port("short row"), None);
+}
+
+// Tests for cumulative CPU time parsing
+
+#[test]
+fn test_parse_cpu_time_linux_formats() {
+    assert_eq!(parse_cpu_time("00:00:00"), Some(0));
+    assert_eq!(parse_cpu_time("00:01:05"), Some(65));
+    assert_eq!(parse_cpu_time("01:23:45"), Some(5025));
+    assert_eq!(parse_cpu_time("2-03:00:10"), Some(2 * 86_400 + 3 * 3600 + 10));
+}
+
+#[test]
+fn test_parse_cpu_time_macos_fractional_seconds() {
+    assert_eq!(parse_cpu_time("0:00.04"), Some(0));
+    assert_eq!(parse_cpu_time("1:02.37"), Some(62));
+    assert_eq!(parse_cpu_time("125:10.99"), Some(7510));
+}
+
+#[test]
+fn test_parse_cpu_time_invalid() {
+    assert_eq!(parse_cpu_time(""), None);
+    assert_eq!(parse_cpu_time("abc"), None);
+    assert_eq!(parse_cpu_time("1:02."), None);
+}
+
+// Tests for the minimum process age guard
+
+#[test]
+fn test_check_min_age_refuses_young_process() {
+    let result = check_min_age("4242", Some(3), Some(10), false);
+    match result {
+        Err(ProcessError::TooYoung { pid, age_seconds, min_age_seconds }) => {
+            assert_eq!(pid, "4242");
+            assert_eq!(age_seconds, 3);
+            assert_eq!(min_age_seconds, 10);
+        }
+        other => panic!("expected TooYoung, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_check_min_age_allows_old_process() {
+    assert!(check_min_age("4242", Some(3600), Some(10), false).is_ok());
+    assert!(check_min_age("4242", Some(10), Some(10), false).is_ok());
+}
+
+#[test]
+fn test_check_min_age_force_and_unknown_age() {
+    assert!(check_min_age("4242", Some(3), Some(10), true).is_ok());
+    assert!(check_min_age("4242", None, Some(10), false).is_ok());
+    assert!(check_min_age("4242", Some(3), None, false).is_ok());
+}
+
+// Tests for the confirmation step on dangerous kills
+
+#[test]
+fn test_check_confirmation_required_for_root_owned() {
+    let result = check_confirmation("812", Some("sshd"), Some("root"), Some(0), 4000, false);
+    match result {
+        Err(ProcessError::ConfirmationRequired { pid, name, user, is_critical }) => {
+            assert_eq!(pid, "812");
+            assert_eq!(name.as_deref(), Some("sshd"));
+            assert_eq!(user.as_deref(), Some("root"));
+            assert!(!is_critical);
+        }
+        other => panic!("expected ConfirmationRequired, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_check_confirmation_required_for_critical() {
+    let result = check_confirmation("1", Some("systemd"), Some("root"), Some(0), 4000, false);
+    assert!(matches!(result, Err(ProcessError::ConfirmationRequired { is_critical: true, .. })));
+    
+    // This application counts as critical even when owned by an ordinary user
+    let result = check_confirmation("4000", Some("kill-process"), Some("alice"), Some(501), 4000, false);
+    assert!(matches!(result, Err(ProcessError::ConfirmationRequired { is_critical: true, .. })));
+}
+
+#[test]
+fn test_check_confirmation_confirmed_proceeds() {
+    assert!(check_confirmation("812", Some("sshd"), Some("root"), Some(0), 4000, true).is_ok());
+    assert!(check_confirmation("1", Some("systemd"), Some("root"), Some(0), 4000, true).is_ok());
+}
+
+#[test]
+fn test_check_confirmation_ordinary_process_proceeds() {
+    assert!(check_confirmation("2345", Some("node"), Some("alice"), Some(501), 4000, false).is_ok());
+    // A process that can't be looked up is left to the kill to report
+    assert!(check_confirmation("2345", None, None, None, 4000, false).is_ok());
+}
+
+#[test]
+fn test_confirmation_required_serializes_context() {
+    let error = ProcessError::ConfirmationRequired {
+        pid: "812".to_string(),
+        name: Some("sshd".to_string()),
+        user: Some("root".to_string()),
+        is_critical: false,
+    };
+    let json = serde_json::to_value(&error).unwrap();
+    
+    assert_eq!(json["kind"], "ConfirmationRequired");
+    assert_eq!(json["name"], "sshd");
+    assert_eq!(json["user"], "root");
+    assert_eq!(json["is_critical"], false);
+    assert!(json["message"].as_str().unwrap().contains("owned by root"));
+}
+
+// Tests for the kill precheck
+
+fn precheck_target(name: &str, uid: u32, stat: &str) -> KillTargetState {
+    KillTargetState { name: name.to_string(), uid: Some(uid), stat: stat.to_string() }
+}
+
+fn precheck_reason(precheck: KillPrecheck) -> String {
+    assert!(!precheck.killable);
+    precheck.reason.unwrap()
+}
+
+#[test]
+fn test_kill_precheck_killable() {
+    let target = precheck_target("node", 501, "S+");
+    assert_eq!(kill_precheck("2345", Some(&target), Some(501), 4000, &[]), KillPrecheck { killable: true, reason: None });
+    
+    // Root may kill other users' processes
+    assert!(kill_precheck("2345", Some(&target), Some(0), 4000, &[]).killable);
+}
+
+#[test]
+fn test_kill_precheck_not_running() {
+    assert!(precheck_reason(kill_precheck("2345", None, Some(501), 4000, &[])).contains("not running"));
+}
+
+#[test]
+fn test_kill_precheck_critical_and_self() {
+    let init = precheck_target("launchd", 0, "Ss");
+    assert!(precheck_reason(kill_precheck("1", Some(&init), Some(0), 4000, &[])).contains("critical"));
+    
+    let me = precheck_target("kill-process", 501, "S");
+    assert!(precheck_reason(kill_precheck("4000", Some(&me), Some(501), 4000, &[])).contains("this application"));
+}
+
+#[test]
+fn test_kill_precheck_allowlist() {
+    let target = precheck_target("node", 501, "S");
+    let reason = precheck_reason(kill_precheck("2345", Some(&target), Some(501), 4000, &["python".to_string()]));
+    assert!(reason.contains("allowlist"));
+    
+    assert!(kill_precheck("2345", Some(&target), Some(501), 4000, &["node".to_string()]).killable);
+}
+
+#[test]
+fn test_kill_precheck_zombie_and_uninterruptible() {
+    let zombie = precheck_target("node", 501, "Z+");
+    assert!(precheck_reason(kill_precheck("2345", Some(&zombie), Some(501), 4000, &[])).contains("zombie"));
+    
+    let stuck = precheck_target("node", 501, "D");
+    assert!(precheck_reason(kill_precheck("2345", Some(&stuck), Some(501), 4000, &[])).contains("uninterruptible"));
+}
+
+#[test]
+fn test_kill_precheck_other_user() {
+    let target = precheck_target("sshd", 0, "Ss");
+    assert!(precheck_reason(kill_precheck("812", Some(&target), Some(501), 4000, &[])).contains("another user"));
+    
+    // With either uid unknown the kill itself decides
+    let unknown = KillTargetState { uid: None, ..target };
+    assert!(kill_precheck("812", Some(&unknown), Some(501), 4000, &[]).killable);
+}
+
+// Tests for the memory breakdown in process detail
+
+#[test]
+fn test_kib_to_bytes() {
+    assert_eq!(kib_to_bytes("0"), Some(0));
+    assert_eq!(kib_to_bytes("1"), Some(1024));
+    assert_eq!(kib_to_bytes(" 524288 "), Some(536_870_912));
+    assert_eq!(kib_to_bytes("-"), None);
+    assert_eq!(kib_to_bytes(""), None);
+}
+
+#[test]
+fn test_parse_statm_shared() {
+    let statm = "6135 1834 1283 187 0 1044 0\n";
+    assert_eq!(parse_statm_shared(statm, 4096), Some(1283 * 4096));
+    assert_eq!(parse_statm_shared(statm, 16384), Some(1283 * 16384));
+}
+
+#[test]
+fn test_parse_statm_shared_malformed() {
+    assert_eq!(parse_statm_shared("", 4096), None);
+    assert_eq!(parse_statm_shared("6135 1834", 4096), None);
+    assert_eq!(parse_statm_shared("6135 1834 x 187", 4096), None);
+}
+
+#[test]
+fn test_parse_oom_value() {
+    assert_eq!(parse_oom_value("667\n"), Some(667));
+    assert_eq!(parse_oom_value("-1000\n"), Some(-1000));
+    assert_eq!(parse_oom_value("0"), Some(0));
+}
+
+#[test]
+fn test_parse_oom_value_malformed() {
+    assert_eq!(parse_oom_value(""), None);
+    assert_eq!(parse_oom_value("high\n"), None);
+}
+
+// Tests for resolving port service names
+
+const SERVICES_SAMPLE: &str = "# Network services, Internet style
+#
+tcpmux          1/tcp                           # TCP port service multiplexer
+http            80/tcp          www             # WorldWideWeb HTTP
+http            80/udp
+domain          53/udp
+postgresql      5432/tcp        postgres        # PostgreSQL Database
+myapp           9123/tcp
+myapp-dup       9123/tcp
+metrics         9123/udp
+broken          notaport/tcp
+nofield
+";
+
+#[test]
+fn test_parse_services() {
+    let services = parse_services(SERVICES_SAMPLE);
+    
+    assert_eq!(services.get(&(1, "tcp".to_string())).map(String::as_str), Some("tcpmux"));
+    assert_eq!(services.get(&(53, "udp".to_string())).map(String::as_str), Some("domain"));
+    assert_eq!(services.get(&(53, "tcp".to_string())), None);
+    // The first entry for a port and protocol wins
+    assert_eq!(services.get(&(9123, "tcp".to_string())).map(String::as_str), Some("myapp"));
+    assert_eq!(services.get(&(9123, "udp".to_string())).map(String::as_str), Some("metrics"));
+    assert_eq!(services.len(), 7);
+}
+
+#[test]
+fn test_resolve_service_name() {
+    let services = parse_services(SERVICES_SAMPLE);
+    
+    // Built-in table first, then the services file
+    assert_eq!(resolve_service_name(22, "tcp", &services).as_deref(), Some("ssh"));
+    assert_eq!(resolve_service_name(9123, "tcp", &services).as_deref(), Some("myapp"));
+    assert_eq!(resolve_service_name(9123, "UDP", &services).as_deref(), Some("metrics"));
+    assert_eq!(resolve_service_name(9999, "tcp", &services), None);
+}
+
+#[test]
+fn test_resolve_service_name_without_services_file() {
+    let services = HashMap::new();
+    assert_eq!(resolve_service_name(443, "tcp", &services).as_deref(), Some("https"));
+    assert_eq!(resolve_service_name(9123, "tcp", &services), None);
+}
+
+// Tests for clearing every cache at once
+
+#[test]
+fn test_clear_caches_empties_all_caches() {
+    let state = AppState::default();
+    let listener = ProcessInfo {
+        pid: "1234".to_string(),
+        name: "node".to_string(),
+        port: "3000".to_string(),
+        match_range: None,
+        score: None,
+        socket_type: None,
+        fd: None,
+        fd_type: None,
+        note: None,
+        depth: None,
+        is_privileged_port: false,
+    };
+    state.port_cache.lock().unwrap().insert(3000, vec![listener], 1_000);
+    *state.services.lock().unwrap() = Some(parse_services(SERVICES_SAMPLE));
+    assert_eq!(state.port_cache.lock().unwrap().len(), 1);
+    
+    state.clear_caches();
+    
+    assert!(state.port_cache.lock().unwrap().is_empty());
+    assert!(state.services.lock().unwrap().is_none());
+}
+
+// Tests for capturing lsof FD and TYPE columns
+
+#[test]
+fn test_parse_lsof_file_output_captures_fd_and_type() {
+    let output = "COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF    NODE NAME
+bash      100 user  cwd    DIR  259,2     4096 1048577 /home/user/project
+node      200 user  txt    REG  259,2 96336296 2228231 /usr/bin/node
+python    300 user  mem    REG  259,2  2029592  657139 /usr/lib/x86_64-linux-gnu/libc.so.6
+vim       400 user    3r   REG  259,2      512 1048600 /home/user/project/notes.txt
+";
+    let processes = parse_lsof_file_output(output);
+    let fds: Vec<(&str, Option<&str>, Option<&str>)> = processes
+        .iter()
+        .map(|p| (p.pid.as_str(), p.fd.as_deref(), p.fd_type.as_deref()))
+        .collect();
+    
+    assert_eq!(fds, vec![
+        ("100", Some("cwd"), Some("DIR")),
+        ("200", Some("txt"), Some("REG")),
+        ("300", Some("mem"), Some("REG")),
+        ("400", Some("3r"), Some("REG")),
+    ]);
+}
+
+#[test]
+fn test_parse_lsof_output_captures_socket_fd() {
+    let output = "COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+node 1234 user 20u IPv4 0x1234 0t0 TCP *:3000 (LISTEN)
+nginx 555 root 6u IPv6 0x5678 0t0 TCP *:80 (LISTEN)
+";
+    let processes = parse_lsof_output(output, "3000");
+    assert_eq!(processes[0].fd.as_deref(), Some("20u"));
+    assert_eq!(processes[0].fd_type.as_deref(), Some("IPv4"));
+    
+    let connections = parse_connection_info(output);
+    assert_eq!(connections[1].fd.as_deref(), Some("6u"));
+    assert_eq!(connections[1].fd_type.as_deref(), Some("IPv6"));
+}
+
+// Tests for killing a process group
+
+#[test]
+fn test_process_group_kill_args_negates_pgid() {
+    assert_eq!(process_group_kill_args("4242", false, 1000).unwrap(), vec!["-TERM", "-4242"]);
+    assert_eq!(process_group_kill_args("4242", true, 1000).unwrap(), vec!["-KILL", "-4242"]);
+    assert_eq!(process_group_kill_args("04242", false, 1000).unwrap(), vec!["-TERM", "-4242"]);
+}
+
+#[test]
+fn test_process_group_kill_args_rejects_group_zero() {
+    for pgid in ["0", "000"] {
+        let error = process_group_kill_args(pgid, false, 1000).unwrap_err();
+        assert!(matches!(error, ProcessError::InvalidPid(_)));
+        assert!(error.to_string().contains("own process group"), "{}", error);
+    }
+}
+
+#[test]
+fn test_process_group_kill_args_rejects_unsafe_groups() {
+    // Group 1 would become `kill -1`, our own PID's group is this application
+    assert!(process_group_kill_args("1", true, 1000).is_err());
+    assert!(process_group_kill_args("1000", true, 1000).is_err());
+    
+    for pgid in ["", "-4242", "+4242", "12a", "99999999999"] {
+        assert!(matches!(process_group_kill_args(pgid, false, 1000), Err(ProcessError::InvalidPid(_))), "Should reject: {}", pgid);
+    }
+}
+
+// Tests for Unicode case folding in name search
+
+#[test]
+fn test_fold_case_ascii_matches_lowercase() {
+    for name in ["Node", "NGINX", "python3.11", "Google Chrome Helper", "kworker/0:1-events"] {
+        assert_eq!(fold_case(name), name.to_lowercase());
+    }
+}
+
+#[test]
+fn test_parse_ps_output_folds_sharp_s() {
+    let ps_output = "  1234 Straße-Daemon\n  5678 nginx";
+    
+    // `ß` and `SS` fold to the same `ss`
+    let result = parse_ps_output(ps_output, "STRASSE");
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].pid, "1234");
+    assert_eq!(result[0].match_range, Some((0, "Straße".len())));
+    
+    assert_eq!(parse_ps_output(ps_output, "straße").len(), 1);
+}
+
+#[test]
+fn test_parse_ps_output_folds_ligature() {
+    let ps_output = "  1234 ﬁrefox\n  5678 nginx";
+    
+    let result = parse_ps_output(ps_output, "Firefox");
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].match_range, Some((0, "ﬁrefox".len())));
+}
+
+#[test]
+fn test_parse_ps_output_turkish_i_not_tailored() {
+    // Default folding has no Turkish tailoring: dotless `ı` is its own letter
+    let ps_output = "  1234 kısa\n  5678 kisa";
+    
+    let result = parse_ps_output(ps_output, "KISA");
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].pid, "5678");
+}
+
+// Tests for session notes on PIDs
+
+#[test]
+fn test_process_notes_set_and_get() {
+    let mut notes = ProcessNotes::default();
+    notes.set("1234", "node", "frontend");
+    notes.set("5678", "postgres", "  db  ");
+    
+    assert_eq!(notes.get("1234", Some("node")).as_deref(), Some("frontend"));
+    assert_eq!(notes.get("5678", Some("postgres")).as_deref(), Some("db"));
+    assert_eq!(notes.get("9999", Some("node")), None);
+    assert_eq!(notes.len(), 2);
+}
+
+#[test]
+fn test_process_notes_truncated_name_still_matches() {
+    let mut notes = ProcessNotes::default();
+    notes.set("1234", "/usr/local/bin/node-inspector-server", "debugger");
+    
+    // lsof truncates names to 9 characters
+    assert_eq!(notes.get("1234", Some("node-insp")).as_deref(), Some("debugger"));
+}
+
+#[test]
+fn test_process_notes_cleared_on_name_mismatch() {
+    let mut notes = ProcessNotes::default();
+    notes.set("1234", "node", "frontend");
+    
+    // The PID was reused by another program: the note is dropped for good
+    assert_eq!(notes.get("1234", Some("python3")), None);
+    assert!(notes.is_empty());
+    assert_eq!(notes.get("1234", Some("node")), None);
+    
+    // A process that has gone loses its note too
+    notes.set("5678", "postgres", "db");
+    assert_eq!(notes.get("5678", None), None);
+    assert!(notes.is_empty());
+}
+
+#[test]
+fn test_process_notes_blank_note_removes() {
+    let mut notes = ProcessNotes::default();
+    notes.set("1234", "node", "frontend");
+    notes.set("1234", "node", "   ");
+    
+    assert!(notes.is_empty());
+}
+
+// Tests for building process detail from partial ps output
+
+#[test]
+fn test_parse_process_detail_full_output() {
+    let detail = parse_process_detail(
+        " 4242   501   501 node     node server.js --port 3000\n",
+        " 4242  12.5  1.2 409600 51200 S    alice\n",
+        " 4242    01:05 00:00:07 Mon Jan 15 10:30:00 2024\n",
+        1_700_000_000,
+    ).unwrap();
+    
+    assert_eq!(detail.pid, "4242");
+    assert_eq!(detail.name, "node");
+    assert_eq!(detail.command.as_deref(), Some("node server.js --port 3000"));
+    assert_eq!(detail.user.as_deref(), Some("alice"));
+    assert_eq!(detail.real_user.as_deref(), Some("alice"));
+    assert_eq!((detail.ruid, detail.euid), (Some(501), Some(501)));
+    assert_eq!(detail.cpu_usage.as_deref(), Some("12.5%"));
+    assert_eq!(detail.rss_bytes, Some(51200 * 1024));
+    assert_eq!(detail.elapsed_seconds, Some(65));
+    assert_eq!(detail.cpu_time_seconds, Some(7));
+    assert_eq!(detail.start_time.as_deref(), Some("Mon Jan 15 10:30:00 2024"));
+    assert!(detail.is_killable);
+}
+
+#[test]
+fn test_parse_process_detail_missing_user() {
+    let detail = parse_process_detail(
+        " 4242   501   501 node     node server.js\n",
+        " 4242  12.5  1.2 409600 51200 S\n",
+        " 4242    01:05 00:00:07 Mon Jan 15 10:30:00 2024\n",
+        1_700_000_000,
+    ).unwrap();
+    
+    assert_eq!(detail.name, "node");
+    assert_eq!(detail.user, None);
+    assert_eq!(detail.cpu_usage.as_deref(), Some("12.5%"));
+    assert_eq!(detail.elapsed_seconds, Some(65));
+}
+
+#[test]
+fn test_parse_process_detail_only_basic_info() {
+    // Resource and time queries failed, e.g. for a restricted process
+    let detail = parse_process_detail(" 4242   0   0 launchd\n", "", "", 1_700_000_000).unwrap();
+    
+    assert_eq!(detail.pid, "4242");
+    assert_eq!(detail.name, "launchd");
+    assert_eq!(detail.command, None);
+    assert_eq!(detail.user, None);
+    assert_eq!(detail.cpu_usage, None);
+    assert_eq!(detail.memory_usage, None);
+    assert_eq!(detail.start_time, None);
+    assert_eq!(detail.elapsed_seconds, None);
+    assert!(detail.is_killable);
+}
+
+#[test]
+fn test_parse_process_detail_short_basic_row() {
+    // Without the uid columns the last column is still taken as the name
+    let detail = parse_process_detail(" 4242 node\n", "", "", 1_700_000_000).unwrap();
+    assert_eq!(detail.pid, "4242");
+    assert_eq!(detail.name, "node");
+    assert_eq!(detail.ruid, None);
+}
+
+#[test]
+fn test_parse_process_detail_requires_pid() {
+    assert!(parse_process_detail("", "", "", 1_700_000_000).is_none());
+    assert!(parse_process_detail("   \n", " 4242 1.0 1.0", "", 1_700_000_000).is_none());
+    assert!(parse_process_detail("PID RUID UID COMM ARGS\n", "", "", 1_700_000_000).is_none());
+}
+
+// Tests for restricting name search to listening processes
+
+#[test]
+fn test_filter_to_listeners_keeps_listening_subset() {
+    let matches = parse_ps_output("  100 api-gateway\n  200 api-worker\n  300 api-server\n", "api");
+    let listeners = parse_listening_ports("COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+api-gatew 100 user 20u IPv4 0x1 0t0 TCP *:8080 (LISTEN)
+api-serve 300 user 21u IPv4 0x2 0t0 TCP 127.0.0.1:9000 (LISTEN)
+api-serve 300 user 22u IPv6 0x3 0t0 TCP [::1]:9001 (LISTEN)
+nginx 400 root 6u IPv4 0x4 0t0 TCP *:80 (LISTEN)
+");
+    
+    let filtered = filter_to_listeners(matches, &listeners);
+    let summary: Vec<(&str, &str, &str)> = filtered
+        .iter()
+        .map(|p| (p.pid.as_str(), p.name.as_str(), p.port.as_str()))
+        .collect();
+    
+    assert_eq!(summary, vec![
+        ("100", "api-gateway", "8080"),
+        ("300", "api-server", "9000, 9001"),
+    ]);
+    assert_eq!(filtered[0].socket_type.as_deref(), Some("TCP"));
+}
+
+#[test]
+fn test_filter_to_listeners_none_listening() {
+    let matches = parse_ps_output("  100 api-gateway\n", "api");
+    assert!(filter_to_listeners(matches, &[]).is_empty());
+}
+
+// Tests for recognizing kills of processes that already exited
+
+#[test]
+fn test_already_gone_recognizes_no_such_process() {
+    let stderrs = [
+        "kill: (4242) - No such process\n",      // procps
+        "kill: (4242): No such process\n",       // util-linux
+        "kill: 4242: No such process\n",         // macOS, shell builtins
+        "kill: can't kill pid 4242: No such process\n", // BusyBox
+    ];
+    
+    for stderr in stderrs {
+        match already_gone("4242", stderr) {
+            Some(ProcessError::AlreadyGone(pid)) => assert_eq!(pid, "4242"),
+            other => panic!("expected AlreadyGone for {:?}, got {:?}", stderr, other),
+        }
+    }
+}
+
+#[test]
+fn test_already_gone_ignores_other_failures() {
+    assert!(already_gone("1", "kill: (1) - Operation not permitted\n").is_none());
+    assert!(already_gone("4242", "").is_none());
+}
+
+#[test]
+fn test_already_gone_serializes_kind() {
+    let json = serde_json::to_value(ProcessError::AlreadyGone("4242".to_string())).unwrap();
+    assert_eq!(json["kind"], "AlreadyGone");
+    assert_eq!(json["message"], "Process 4242 had already terminated");
+}
+
+// Tests for the exit polling loop behind wait_for_exit
+
+#[test]
+fn test_poll_for_exit_returns_on_first_poll_after_exit() {
+    let slept = Cell::new(Duration::ZERO);
+    let exited = poll_for_exit("1234", Duration::from_secs(10), |_| false, |d| slept.set(slept.get() + d));
+    
+    assert!(exited);
+    assert_eq!(slept.get(), Duration::from_millis(SMART_KILL_POLL_MS));
+}
+
+#[test]
+fn test_poll_for_exit_times_out() {
+    let slept = Cell::new(Duration::ZERO);
+    let checks = Cell::new(0);
+    let timeout = Duration::from_millis(SMART_KILL_POLL_MS * 5);
+    
+    let exited = poll_for_exit("1234", timeout, |_| { checks.set(checks.get() + 1); true }, |d| slept.set(slept.get() + d));
+    
+    assert!(!exited);
+    assert_eq!(checks.get(), 5);
+    assert_eq!(slept.get(), timeout);
+}
+
+#[test]
+fn test_poll_for_exit_zero_timeout() {
+    assert!(!poll_for_exit("1234", Duration::ZERO, |_| panic!("should not poll"), |_| panic!("should not sleep")));
+}
+
+// Tests for batched process detail
+
+#[test]
+fn test_parse_process_details_multiple_rows() {
+    let basic = "  100   501   501 node     node server.js\n  200     0     0 nginx    nginx: master process\n  300   501   501 python3  python3 -m http.server\n";
+    // Rows may come back in a different order, and PID 200 exited before the time query
+    let resource = "  300   0.0  0.1 20000  8000 S    alice\n  100  12.5  1.2 409600 51200 R    alice\n  200   0.1  0.2 30000 10000 Ss   root\n";
+    let time = "  100    01:05 00:00:07 Mon Jan 15 10:30:00 2024\n  300    10:00 00:00:01 Mon Jan 15 10:20:00 2024\n";
+    
+    let details = parse_process_details(basic, resource, time, 1_700_000_000);
+    
+    let summary: Vec<(&str, &str, Option<&str>)> = details
+        .iter()
+        .map(|d| (d.pid.as_str(), d.name.as_str(), d.user.as_deref()))
+        .collect();
+    assert_eq!(summary, vec![
+        ("100", "node", Some("alice")),
+        ("200", "nginx", Some("root")),
+        ("300", "python3", Some("alice")),
+    ]);
+    assert_eq!(details[0].cpu_usage.as_deref(), Some("12.5%"));
+    assert_eq!(details[0].elapsed_seconds, Some(65));
+    assert_eq!(details[1].command.as_deref(), Some("nginx: master process"));
+    assert_eq!(details[1].elapsed_seconds, None);
+    assert_eq!(details[2].elapsed_seconds, Some(600));
+}
+
+#[test]
+fn test_parse_process_details_skips_missing_pids() {
+    // Only PIDs present in the basic query are reported
+    let details = parse_process_details("  100   501   501 node     node\n", "  100 1.0 1.0 1 1 S alice\n  999 1.0 1.0 1 1 S bob\n", "", 1_700_000_000);
+    assert_eq!(details.len(), 1);
+    assert_eq!(details[0].pid, "100");
+    
+    assert!(parse_process_details("", "", "", 1_700_000_000).is_empty());
+}
+
+#[test]
+fn test_group_ports_by_pid() {
+    let output = "COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+node 100 user 20u IPv4 0x1 0t0 TCP *:3000 (LISTEN)
+node 100 user 21u IPv6 0x2 0t0 TCP *:3000 (LISTEN)
+node 100 user 22u IPv4 0x3 0t0 TCP 127.0.0.1:3000->127.0.0.1:51000 (ESTABLISHED)
+nginx 200 root 6u IPv4 0x4 0t0 TCP *:80 (LISTEN)
+nginx 200 root 7u IPv4 0x5 0t0 TCP *:443 (LISTEN)
+";
+    let ports = group_ports_by_pid(output);
+    
+    assert_eq!(ports.get("100"), Some(&vec!["3000".to_string()]));
+    assert_eq!(ports.get("200"), Some(&vec!["80".to_string(), "443".to_string()]));
+    assert_eq!(ports.get("300"), None);
+}
+
+// Tests for choosing which detail fields to fetch
+
+fn fields(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn test_plan_detail_fields_cpu_only() {
+    let plan = plan_detail_fields(&fields(&["cpu"])).unwrap();
+    
+    assert_eq!(plan.queries.len(), 1);
+    assert_eq!(plan.queries[0].format(), "pid=,pcpu=");
+    assert!(!plan.port, "cpu alone must not run lsof");
+    assert!(!plan.shared && !plan.container && !plan.oom);
+}
+
+#[test]
+fn test_plan_detail_fields_variable_columns_get_own_queries() {
+    let plan = plan_detail_fields(&fields(&["name", "cpu", "command", "memory", "port", "cpu"])).unwrap();
+    let formats: Vec<String> = plan.queries.iter().map(PsQuery::format).collect();
+    
+    assert_eq!(formats, vec!["pid=,pcpu=,pmem=,vsz=,rss=,comm=", "pid=,args="]);
+    assert!(plan.port);
+    assert!(plan.shared);
+}
+
+#[test]
+fn test_plan_detail_fields_port_only_still_checks_pid() {
+    let plan = plan_detail_fields(&fields(&["port"])).unwrap();
+    assert_eq!(plan.queries[0].format(), "pid=");
+    assert!(plan.port);
+}
+
+#[test]
+fn test_plan_detail_fields_unknown_field() {
+    let error = plan_detail_fields(&fields(&["cpu", "colour"])).unwrap_err();
+    
+    assert!(matches!(&error, ProcessError::InvalidField { field, .. } if field == "colour"));
+    let message = error.to_string();
+    assert!(message.contains("colour"));
+    assert!(message.contains("name, command, user, cpu, memory, state, time, port, container"));
+}
+
+#[test]
+fn test_apply_ps_query_fills_requested_columns() {
+    let plan = plan_detail_fields(&fields(&["user", "time"])).unwrap();
+    let mut detail = ProcessDetail::default();
+    
+    apply_ps_query(&mut detail, &plan.queries[0], " 4242   501     0   01:05 00:00:07 Directory User\n", 1_700_000_000);
+    apply_ps_query(&mut detail, &plan.queries[1], " 4242 Mon Jan 15 10:30:00 2024\n", 1_700_000_000);
+    
+    assert_eq!(plan.queries[0].format(), "pid=,ruid=,uid=,etime=,time=,user=");
+    assert_eq!((detail.ruid, detail.euid), (Some(501), Some(0)));
+    assert_eq!(detail.user.as_deref(), Some("Directory User"));
+    assert_eq!(detail.elapsed_seconds, Some(65));
+    assert_eq!(detail.cpu_time_seconds, Some(7));
+    assert_eq!(detail.start_time.as_deref(), Some("Mon Jan 15 10:30:00 2024"));
+    assert_eq!(detail.cpu_usage, None);
+}
+
+// Tests for scan timing
+
+#[test]
+fn test_timed_output_reports_positive_duration() {
+    let own_pid = std::process::id().to_string();
+    let (output, duration_ms) = timed_output(ToolPaths::default().ps().args(["-p", &own_pid, "-o", "pid="]), DEFAULT_MAX_OUTPUT_BYTES);
+    
+    assert!(output.is_ok());
+    assert!(duration_ms > 0);
+}
+
+#[test]
+fn test_elapsed_ms_rounds_up() {
+    assert!(elapsed_ms(std::time::Instant::now()) <= 1);
+    
+    let started = std::time::Instant::now() - Duration::from_micros(2_500);
+    assert!(elapsed_ms(started) >= 3);
+}
+
+#[test]
+fn test_duration_ms_defaults_when_missing() {
+    let json = r#"{"is_occupied":false,"processes":[],"binding":null,"error":null}"#;
+    let result: PortCheckResult = serde_json::from_str(json).unwrap();
+    assert_eq!(result.duration_ms, None);
+}
+
+// Tests for process environment parsing
+
+#[test]
+fn test_parse_environ_nul_separated() {
+    let data = "PORT=8080\0PATH=/usr/bin:/bin\0DATABASE_URL=postgres://u@h/db?sslmode=require\0NOEQUALS\0\0";
+    let vars = parse_environ(data);
+    
+    assert_eq!(vars, vec![
+        ("PORT".to_string(), "8080".to_string()),
+        ("PATH".to_string(), "/usr/bin:/bin".to_string()),
+        ("DATABASE_URL".to_string(), "postgres://u@h/db?sslmode=require".to_string()),
+    ]);
+}
+
+#[test]
+fn test_parse_ps_env_strips_command() {
+    let command = "node server.js --port=3000\n";
+    let output = "node server.js --port=3000 PORT=3000 GREETING=hello world HOME=/Users/dev\n";
+    let vars = parse_ps_env(output, command);
+    
+    assert_eq!(vars, vec![
+        ("PORT".to_string(), "3000".to_string()),
+        ("GREETING".to_string(), "hello world".to_string()),
+        ("HOME".to_string(), "/Users/dev".to_string()),
+    ]);
+}
+
+#[test]
+fn test_parse_ps_env_without_environment() {
+    assert!(parse_ps_env("/usr/sbin/sshd -D\n", "/usr/sbin/sshd -D\n").is_empty());
+}
+
+#[test]
+fn test_redact_env_replaces_listed_values() {
+    let vars = vec![
+        ("PORT".to_string(), "8080".to_string()),
+        ("API_TOKEN".to_string(), "s3cret".to_string()),
+    ];
+    
+    let redacted = redact_env(vars.clone(), &["api_token".to_string()]);
+    assert_eq!(redacted[0].1, "8080");
+    assert_eq!(redacted[1].1, REDACTED_VALUE);
+    
+    assert_eq!(redact_env(vars.clone(), &[]), vars);
+}
+
+// Tests for the command output size guard
+
+#[test]
+fn test_read_capped_truncates_over_limit() {
+    let data = "x".repeat(100);
+    let mut reader = std::io::Cursor::new(data.as_bytes());
+    
+    let (kept, truncated) = read_capped(&mut reader, 64).unwrap();
+    assert_eq!(kept.len(), 64);
+    assert!(truncated);
+}
+
+#[test]
+fn test_read_capped_keeps_output_within_limit() {
+    let mut reader = std::io::Cursor::new(b"exactly 16 bytes".as_slice());
+    
+    let (kept, truncated) = read_capped(&mut reader, 16).unwrap();
+    assert_eq!(kept, b"exactly 16 bytes");
+    assert!(!truncated);
+}
+
+#[test]
+fn test_capped_output_truncates_command_output() {
+    let output = capped_output(ToolPaths::default().ps().args(["-A", "-o", "pid=,args="]), 32).unwrap();
+    
+    assert_eq!(output.stdout.len(), 32);
+    assert!(output.truncated);
+}
+
+// Tests for the dev port scan
+
+#[test]
+fn test_filter_dev_ports_keeps_preset_ports() {
+    let lsof_output = r#"COMMAND   PID    USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+node     1234 testuser   20u  IPv4 0x1234567890abcdef      0t0  TCP *:3000 (LISTEN)
+postgres  555 testuser    7u  IPv4 0x1234567890abcdef      0t0  TCP 127.0.0.1:5432 (LISTEN)
+sshd       77 root        3u  IPv4 0x1234567890abcdef      0t0  TCP *:22 (LISTEN)
+"#;
+    
+    let statuses = filter_dev_ports(parse_listening_ports(lsof_output), DEV_PORTS);
+    
+    assert_eq!(statuses.len(), DEV_PORTS.len());
+    let occupied: Vec<u16> = statuses.iter().filter(|s| s.is_occupied).map(|s| s.port).collect();
+    assert_eq!(occupied, vec![3000, 5432]);
+    
+    let node = statuses.iter().find(|s| s.port == 3000).unwrap();
+    assert_eq!(node.processes.len(), 1);
+    assert_eq!(node.processes[0].pid, "1234");
+    assert!(statuses.iter().all(|s| s.port != 22));
+}
+
+#[test]
+fn test_filter_dev_ports_uses_override_list() {
+    let lsof_output = r#"COMMAND   PID    USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+sshd       77 root        3u  IPv4 0x1234567890abcdef      0t0  TCP *:22 (LISTEN)
+"#;
+    
+    let statuses = filter_dev_ports(parse_listening_ports(lsof_output), &[22, 9999, 22]);
+    
+    assert_eq!(statuses.len(), 2);
+    assert!(statuses[0].is_occupied);
+    assert_eq!(statuses[0].processes[0].name, "sshd");
+    assert!(!statuses[1].is_occupied);
+}
+
+// Tests for result schema versioning and round-trips
+
+fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> T {
+    serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+}
+
+fn sample_listener() -> ProcessInfo {
+    ProcessInfo {
+        pid: "1234".to_string(),
+        name: "node".to_string(),
+        port: "3000".to_string(),
+        match_range: Some((0, 4)),
+        score: Some(0.75),
+        socket_type: Some("TCP".to_string()),
+        note: Some("api".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_port_check_result_round_trip() {
+    let result = PortCheckResult {
+        is_occupied: true,
+        processes: vec![sample_listener()],
+        binding: Some("dual".to_string()),
+        error: None,
+        duration_ms: Some(12),
+        output_truncated: false,
+        schema_version: SCHEMA_VERSION,
+    };
+    
+    assert_eq!(round_trip(&result), result);
+}
+
+#[test]
+fn test_process_search_result_round_trip() {
+    let result = ProcessSearchResult {
+        processes: vec![sample_listener()],
+        total_matches: 3,
+        totals: ResourceTotals {
+            total_cpu: Some(12.5),
+            total_mem: Some(1.5),
+            total_rss_bytes: Some(2048),
+        },
+        error: Some("partial".to_string()),
+        duration_ms: Some(7),
+        output_truncated: true,
+        schema_version: SCHEMA_VERSION,
+    };
+    
+    assert_eq!(round_trip(&result), result);
+}
+
+#[test]
+fn test_snapshot_and_diff_round_trip() {
+    let snapshot = ProcessSnapshot {
+        listening_ports: vec![sample_listener()],
+        timestamp: 42,
+        schema_version: SCHEMA_VERSION,
+    };
+    assert_eq!(round_trip(&snapshot), snapshot);
+    
+    let diff = SnapshotDiff {
+        added: vec![sample_listener()],
+        removed: vec![],
+        changed: vec![],
+        schema_version: SCHEMA_VERSION,
+    };
+    assert_eq!(round_trip(&diff), diff);
+}
+
+#[test]
+fn test_kill_query_result_round_trip() {
+    let result = KillQueryResult {
+        matches: vec![KillCandidate {
+            pid: "1234".to_string(),
+            name: "node".to_string(),
+            user: "testuser".to_string(),
+            age_seconds: Some(90),
+            ports: vec![3000],
+        }],
+        results: vec![KillResult {
+            pid: "1234".to_string(),
+            success: true,
+            message: "Process 1234 gracefully terminated successfully".to_string(),
+        }],
+        schema_version: SCHEMA_VERSION,
+    };
+    
+    assert_eq!(round_trip(&result), result);
+}
+
+#[test]
+fn test_schema_version_serialized_and_defaults_for_old_data() {
+    let snapshot = build_snapshot(vec![], None, 0);
+    let value = serde_json::to_value(&snapshot).unwrap();
+    assert_eq!(value["schema_version"], SCHEMA_VERSION);
+    
+    // Snapshots saved before versioning still load, marked as version 0
+    let old: ProcessSnapshot = serde_json::from_str(r#"{"listening_ports":[],"timestamp":42}"#).unwrap();
+    assert_eq!(old.schema_version, 0);
+}
+
+#[test]
+fn test_process_detail_defaults_for_old_data() {
+    let old: ProcessDetail = serde_json::from_str(
+        r#"{"pid":"1234","name":"node","port":"3000","user":"testuser","command":"node server.js","cpu_usage":"1.5","memory_usage":"0.8","start_time":null}"#,
+    )
+    .unwrap();
+    
+    assert!(old.is_killable);
+    assert_eq!(old.cpu_cores, None);
+    assert_eq!(old.start_time_iso, None);
+    assert_eq!(old.container_id, None);
+}
+
+// Tests for port watch events
+
+fn listener(pid: &str, name: &str) -> ProcessInfo {
+    ProcessInfo {
+        pid: pid.to_string(),
+        name: name.to_string(),
+        port: "8080".to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_port_watch_event_reports_old_and_new_owner() {
+    let before = vec![listener("100", "nginx")];
+    let after = vec![listener("200", "node")];
+    
+    let event = port_watch_event(8080, Some(&before), &after).unwrap();
+    assert!(event.is_occupied);
+    assert_eq!(event.previous[0].name, "nginx");
+    assert_eq!(event.processes[0].name, "node");
+    
+    let value = serde_json::to_value(&event).unwrap();
+    assert_eq!(value["previous"][0]["pid"], "100");
+    assert_eq!(value["processes"][0]["pid"], "200");
+}
+
+#[test]
+fn test_port_watch_event_skips_unchanged_owners() {
+    let before = vec![listener("100", "nginx"), listener("100", "nginx")];
+    let after = vec![listener("100", "nginx")];
+    
+    assert!(port_watch_event(8080, Some(&before), &after).is_none());
+    assert!(port_watch_event(8080, Some(&[]), &[]).is_none());
+}
+
+#[test]
+fn test_port_watch_event_first_poll_and_freed_port() {
+    let first = port_watch_event(8080, None, &[]).unwrap();
+    assert!(!first.is_occupied);
+    assert!(first.previous.is_empty());
+    
+    let freed = port_watch_event(8080, Some(&[listener("100", "nginx")]), &[]).unwrap();
+    assert!(!freed.is_occupied);
+    assert_eq!(freed.previous[0].pid, "100");
+}
+
+// Tests for companion file cleanup after a kill
+
+fn killed(pid: &str, success: bool) -> KillResult {
+    KillResult { pid: pid.to_string(), success, message: String::new() }
+}
+
+#[test]
+fn test_remove_companion_files_only_after_confirmed_dead() {
+    let results = vec![killed("100", true), killed("200", true)];
+    let files = vec!["/tmp/app.pid".to_string(), "/tmp/app.sock".to_string()];
+    let checked = std::cell::RefCell::new(vec![]);
+    let removed = std::cell::RefCell::new(vec![]);
+    
+    // Every PID is checked before the first file goes
+    let outcomes = remove_companion_files(
+        &results,
+        &files,
+        |pid| {
+            assert!(removed.borrow().is_empty(), "removed a file before PID {} was confirmed dead", pid);
+            checked.borrow_mut().push(pid.to_string());
+            true
+        },
+        |path| {
+            removed.borrow_mut().push(path.to_path_buf());
+            Ok(())
+        },
+    );
+    
+    assert_eq!(checked.into_inner(), vec!["100", "200"]);
+    assert_eq!(removed.into_inner().len(), 2);
+    assert!(outcomes.iter().all(|outcome| outcome.removed && outcome.reason.is_none()));
+}
+
+#[test]
+fn test_remove_companion_files_keeps_files_while_process_runs() {
+    let files = vec!["/tmp/app.pid".to_string()];
+    let mut removals = 0;
+    
+    let outcomes = remove_companion_files(&[killed("100", true)], &files, |_| false, |_| {
+        removals += 1;
+        Ok(())
+    });
+    assert_eq!(removals, 0);
+    assert!(!outcomes[0].removed);
+    assert_eq!(outcomes[0].reason.as_deref(), Some("PID 100 is still running"));
+    
+    // A failed kill or no kill at all also leaves the files alone
+    let outcomes = remove_companion_files(&[killed("100", false)], &files, |_| true, |_| panic!("must not remove"));
+    assert_eq!(outcomes[0].reason.as_deref(), Some("PID 100 was not killed"));
+    let outcomes = remove_companion_files(&[], &files, |_| true, |_| panic!("must not remove"));
+    assert_eq!(outcomes[0].reason.as_deref(), Some("no process was killed"));
+}
+
+#[test]
+fn test_remove_companion_files_rejects_relative_and_reports_missing() {
+    let files = vec!["app.pid".to_string(), "/tmp/gone.pid".to_string()];
+    
+    let outcomes = remove_companion_files(&[killed("100", true)], &files, |_| true, |path| {
+        assert_eq!(path, std::path::Path::new("/tmp/gone.pid"));
+        Err(std::io::Error::from(std::io::ErrorKind::NotFound))
+    });
+    
+    assert_eq!(outcomes[0].reason.as_deref(), Some("not an absolute path"));
+    assert_eq!(outcomes[1].reason.as_deref(), Some("file not found"));
+    assert!(outcomes.iter().all(|outcome| !outcome.removed));
+}
+
+#[test]
+fn test_port_kill_result_reports_cleanup() {
+    let results = vec![killed("100", true)];
+    let files = vec!["/tmp/app.pid".to_string(), "/tmp/app.lock".to_string()];
+    let cleaned_files = remove_companion_files(&results, &files, |_| true, |path| match path.to_str() {
+        Some("/tmp/app.pid") => Ok(()),
+        _ => Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied)),
+    });
+    
+    let value = serde_json::to_value(PortKillResult { port: 3000, results, cleaned_files }).unwrap();
+    
+    assert_eq!(value["port"], 3000);
+    assert_eq!(value["results"][0]["pid"], "100");
+    assert_eq!(value["cleaned_files"][0]["path"], "/tmp/app.pid");
+    assert_eq!(value["cleaned_files"][0]["removed"], true);
+    assert_eq!(value["cleaned_files"][1]["path"], "/tmp/app.lock");
+    assert_eq!(value["cleaned_files"][1]["removed"], false);
+    assert!(value["cleaned_files"][1]["reason"].as_str().is_some());
+}
+
+// Tests for the command audit trail
+
+#[test]
+fn test_command_audit_records_args_and_exit_code() {
+    // A unique argument picks this probe out of commands other tests run concurrently
+    let marker = format!("audit-probe-{}", std::process::id());
+    let output = std::process::Command::new("sh")
+        .args(["-c", "exit 3", &marker])
+        .audited_output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    
+    let audit = command_audit();
+    let entry = audit.iter().find(|entry| entry.args.contains(&marker)).expect("probe was not recorded");
+    assert_eq!(entry.program, "sh");
+    assert_eq!(entry.args, vec!["-c".to_string(), "exit 3".to_string(), marker.clone()]);
+    assert_eq!(entry.exit_code, Some(3));
+    assert!(entry.error.is_none());
+    assert!(entry.timestamp > 0);
+}
+
+#[test]
+fn test_command_audit_records_failed_start() {
+    let program = format!("/nonexistent/audit-probe-{}", std::process::id());
+    assert!(std::process::Command::new(&program).audited_output().is_err());
+    
+    let audit = command_audit();
+    let entry = audit.iter().find(|entry| entry.program == program).expect("failed start was not recorded");
+    assert_eq!(entry.exit_code, None);
+    assert!(entry.error.is_some());
+}
+
+// Tests for killing all processes of a user
+
+#[test]
+fn test_resolve_user_target() {
+    assert_eq!(resolve_user_target("me", Some(501)).unwrap(), "501");
+    assert_eq!(resolve_user_target(" ME ", Some(501)).unwrap(), "501");
+    assert_eq!(resolve_user_target("alice", Some(501)).unwrap(), "alice");
+    assert_eq!(resolve_user_target("1000", None).unwrap(), "1000");
+    assert!(resolve_user_target("me", None).is_err());
+    
+    for user in ["", "-A", "alice bob", "alice,root"] {
+        let error = resolve_user_target(user, Some(501)).unwrap_err();
+        assert_eq!(error.kind(), "InvalidUser", "{:?} should be rejected", user);
+    }
+}
+
+#[test]
+fn test_parse_user_processes_filters_protected_pids() {
+    let own_pid = 4242;
+    let output = "    1 systemd\n  300 /usr/bin/node\n 4242 kill-process\n  301 Google Chrome Helper\n  abc bogus\n";
+    
+    let matches = parse_user_processes(output, "alice", own_pid);
+    let pids: Vec<&str> = matches.iter().map(|m| m.pid.as_str()).collect();
+    
+    assert_eq!(pids, vec!["300", "301"]);
+    assert_eq!(matches[0].name, "node");
+    assert_eq!(matches[1].name, "Google Chrome Helper");
+    assert!(matches.iter().all(|m| m.user == "alice"));
+}
+
+// Tests for flagging privileged ports
+
+#[test]
+fn test_is_privileged_port() {
+    assert!(is_privileged_port(1));
+    assert!(is_privileged_port(80));
+    assert!(is_privileged_port(1023));
+    assert!(!is_privileged_port(1024));
+    assert!(!is_privileged_port(8080));
+    assert!(!is_privileged_port(0));
+}
+
+#[test]
+fn test_listeners_flag_privileged_ports() {
+    let lsof_output = r#"COMMAND   PID    USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+nginx     100 root        6u  IPv4 0x1234567890abcdef      0t0  TCP *:80 (LISTEN)
+sshd      200 root        3u  IPv4 0x1234567890abcdef      0t0  TCP *:1023 (LISTEN)
+node      300 testuser   20u  IPv4 0x1234567890abcdef      0t0  TCP *:1024 (LISTEN)
+node      301 testuser   21u  IPv4 0x1234567890abcdef      0t0  TCP *:3000 (LISTEN)
+"#;
+    
+    let flags: Vec<(String, bool)> = parse_listening_ports(lsof_output)
+        .into_iter()
+        .map(|p| (p.port, p.is_privileged_port))
+        .collect();
+    assert_eq!(flags, vec![
+        ("80".to_string(), true),
+        ("1023".to_string(), true),
+        ("1024".to_string(), false),
+        ("3000".to_string(), false),
+    ]);
+    
+    assert!(parse_lsof_output(lsof_output, "80")[0].is_privileged_port);
+    assert!(!parse_lsof_output(lsof_output, "3000")[0].is_privileged_port);
+}
+
+// Tests for narrowing port checks to an IP family
+
+#[test]
+fn test_ip_family_lsof_address() {
+    assert_eq!(IpFamily::Any.lsof_address(8080), ":8080");
+    assert_eq!(IpFamily::V4.lsof_address(8080), "4:8080");
+    assert_eq!(IpFamily::V6.lsof_address(443), "6:443");
+}
+
+#[test]
+fn test_ip_family_narrows_port_pattern_scan() {
+    // Patterns scan every listener, so the family has to go into that lsof selection
+    assert!(is_port_pattern("3000-3010"));
+    assert_eq!(IpFamily::Any.lsof_tcp_selection(), "-iTCP");
+    assert_eq!(IpFamily::V4.lsof_tcp_selection(), "-i4TCP");
+    assert_eq!(IpFamily::V6.lsof_tcp_selection(), "-i6TCP");
+}
+
+#[test]
+fn test_ip_family_deserializes_lowercase() {
+    let family: IpFamily = serde_json::from_str(r#""v6""#).unwrap();
+    assert_eq!(family, IpFamily::V6);
+    assert_eq!(serde_json::to_string(&IpFamily::V4).unwrap(), r#""v4""#);
+    assert_eq!(IpFamily::default(), IpFamily::Any);
+}
+
+// Tests for strict UTF-8 handling of names used as kill targets
+
+#[test]
+fn test_decode_name_flags_replacement() {
+    assert_eq!(decode_name(b"node"), ("node".to_string(), false));
+    assert_eq!(decode_name("node应用".as_bytes()), ("node应用".to_string(), false));
+    
+    let (name, replaced) = decode_name(b"no\xffde");
+    assert!(replaced);
+    assert_eq!(name, "no\u{FFFD}de");
+}
+
+#[test]
+fn test_strict_name_refuses_replaced_name() {
+    let error = strict_name("1234", Some(decode_name(b"no\xffde"))).unwrap_err();
+    assert_eq!(error.kind(), "UndecodableName");
+    assert!(error.to_string().contains("1234"));
+    
+    assert_eq!(strict_name("1234", Some(decode_name(b"node"))).unwrap(), Some("node".to_string()));
+    assert_eq!(strict_name("1234", None).unwrap(), None);
+}
+
+#[test]
+fn test_verify_expected_name_refuses_lossy_expected_name() {
+    // Identical lossy names would otherwise match, though the original bytes may differ
+    let error = verify_expected_name("1234", "no\u{FFFD}", Some("no\u{FFFD}")).unwrap_err();
+    assert_eq!(error.kind(), "UndecodableName");
+    assert!(verify_expected_name("1234", "node", Some("node")).is_ok());
+}
+
+// Tests for verifying which process owns a port
+
+const PORT_OWNER_LSOF: &str = r#"COMMAND   PID    USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+node     1234 testuser   20u  IPv4 0x1234567890abcdef      0t0  TCP *:8080 (LISTEN)
+node     1234 testuser   21u  IPv6 0x1234567890abcdef      0t0  TCP *:8080 (LISTEN)
+nginx    5678 www        6u   IPv4 0x1234567890abcdef      0t0  TCP 127.0.0.1:8080 (LISTEN)
+"#;
+
+#[test]
+fn test_is_port_owner_pid_present() {
+    let listeners = parse_lsof_output(PORT_OWNER_LSOF, "8080");
+    
+    assert!(is_port_owner(&listeners, "1234"));
+    assert!(is_port_owner(&listeners, "5678"));
+}
+
+#[test]
+fn test_is_port_owner_pid_absent() {
+    let listeners = parse_lsof_output(PORT_OWNER_LSOF, "8080");
+    
+    assert!(!is_port_owner(&listeners, "4321"));
+    assert!(!is_port_owner(&listeners, "123"), "a PID prefix must not count");
+    assert!(!is_port_owner(&[], "1234"));
+}
+
+// Tests for choosing the lsof dedup key
+
+const DEDUP_LSOF: &str = r#"COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 user   20u  IPv4 0x1234      0t0  TCP *:3000 (LISTEN)
+node     1234 user   21u  IPv6 0x5678      0t0  TCP *:3000 (LISTEN)
+node     1234 user   22u  IPv4 0x9abc      0t0  TCP 127.0.0.1:3001 (LISTEN)
+node     1234 user   23u  IPv4 0xdef0      0t0  TCP *:3000 (LISTEN)
+nginx    5678 www     6u  IPv4 0x1111      0t0  TCP *:3000 (LISTEN)"#;
+
+fn dedup_fds(key: DedupKey) -> Vec<String> {
+    parse_lsof_rows(DEDUP_LSOF, "3000", key)
+        .into_iter()
+        .map(|p| p.fd.unwrap_or_default())
+        .collect()
+}
+
+#[test]
+fn test_dedup_key_pid() {
+    assert_eq!(dedup_fds(DedupKey::Pid), vec!["20u", "6u"]);
+    assert_eq!(DedupKey::default(), DedupKey::Pid);
+    assert_eq!(parse_lsof_output(DEDUP_LSOF, "3000").len(), 2);
+}
+
+#[test]
+fn test_dedup_key_pid_port() {
+    assert_eq!(dedup_fds(DedupKey::PidPort), vec!["20u", "22u", "6u"]);
+}
+
+#[test]
+fn test_dedup_key_pid_address() {
+    // The IPv6 wildcard is its own address; the second IPv4 wildcard socket is not
+    assert_eq!(dedup_fds(DedupKey::PidAddress), vec!["20u", "21u", "22u", "6u"]);
+}
+
+#[test]
+fn test_dedup_key_reads_shifted_name_column() {
+    // Without SIZE/OFF the NAME column moves left of the ninth position
+    let lsof_output = r#"COMMAND   PID USER   FD   TYPE DEVICE NODE NAME
+node     1234 user   20u  IPv4 0x1234  TCP *:3000 (LISTEN)
+node     1234 user   22u  IPv4 0x9abc  TCP 127.0.0.1:3001 (LISTEN)"#;
+    
+    assert_eq!(parse_lsof_rows(lsof_output, "3000", DedupKey::PidPort).len(), 2);
+    assert_eq!(parse_lsof_rows(lsof_output, "3000", DedupKey::PidAddress).len(), 2);
+}
+
+#[test]
+fn test_dedup_key_row() {
+    assert_eq!(dedup_fds(DedupKey::Row), vec!["20u", "21u", "22u", "23u", "6u"]);
+}